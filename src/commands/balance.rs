@@ -0,0 +1,115 @@
+use chrono::{DateTime, Local};
+use std::process::exit;
+
+use crate::user_interaction::convert_input::convert_input_to_seconds;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::balance::{
+    get_balance_ledger, write_balance_ledger, BalanceAdjustment, BalanceLedger,
+};
+use crate::utils::config::{get_config, update_config, Config};
+
+pub fn balance(now: &DateTime<Local>, other_args: Vec<String>) {
+    if other_args.len() == 0 {
+        view_balance();
+        return;
+    }
+    let rest: Vec<String> = other_args[1..].to_vec();
+    match other_args[0].as_str() {
+        "show" => view_balance(),
+        "adjust" => adjust_balance(now, rest),
+        other => {
+            eprintln!(
+                "'{}' is not a valid subcommand for 'punch balance'. Try 'show' or 'adjust'.",
+                other
+            );
+            exit(1);
+        }
+    }
+}
+
+fn view_balance() {
+    let config: Config = get_config();
+    let show_times_in_hours: bool = config.show_times_in_hours_or_default();
+    println!(
+        "Time behind overall: {}",
+        render_seconds_human_readable(config.get_seconds_behind(), show_times_in_hours)
+    );
+    let ledger: BalanceLedger = get_balance_ledger();
+    if ledger.get_adjustments().len() > 0 {
+        println!("Balance adjustments:");
+        println!(
+            "{}",
+            render_balance_adjustments(ledger.get_adjustments(), show_times_in_hours)
+        );
+    } else {
+        println!("No balance adjustments recorded.");
+    }
+}
+
+fn adjust_balance(now: &DateTime<Local>, other_args: Vec<String>) {
+    let parsed_args: Result<(i64, Option<String>), String> = parse_args_for_adjust(other_args);
+    if let Err(err_msg) = parsed_args {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let (seconds, reason) = parsed_args.expect("Error already handled!");
+
+    let adjustment: BalanceAdjustment = BalanceAdjustment::new(now, seconds, reason);
+    let mut config: Config = get_config();
+    config.update_time_behind(adjustment.get_time_behind_delta());
+    let mut ledger: BalanceLedger = get_balance_ledger();
+    ledger.add_adjustment(adjustment);
+    write_balance_ledger(&ledger);
+    let show_times_in_hours: bool = config.show_times_in_hours_or_default();
+    let new_seconds_behind: i64 = config.get_seconds_behind();
+    update_config(config);
+
+    println!(
+        "Adjusted balance by {}. Time behind overall is now: {}",
+        render_seconds_human_readable(seconds, show_times_in_hours),
+        render_seconds_human_readable(new_seconds_behind, show_times_in_hours)
+    );
+}
+
+fn parse_args_for_adjust(other_args: Vec<String>) -> Result<(i64, Option<String>), String> {
+    if other_args.len() == 0 {
+        return Err("'punch balance adjust' needs an amount, e.g. '-2h' or '30m'.".to_string());
+    }
+    let seconds: i64 = convert_input_to_seconds(&other_args[0])?;
+    return match other_args.len() {
+        1 => Ok((seconds, None)),
+        3 => {
+            let flag: &String = &other_args[1];
+            if (flag != "--reason") & (flag != "-r") {
+                return Err(format!("Unrecognised argument or flag: {}", flag));
+            }
+            Ok((seconds, Some(other_args[2].to_owned())))
+        }
+        _ => Err(
+            "'punch balance adjust' takes an amount and optionally '--reason <reason>'."
+                .to_string(),
+        ),
+    };
+}
+
+pub fn render_balance_adjustments(
+    adjustments: &Vec<BalanceAdjustment>,
+    show_times_in_hours: bool,
+) -> String {
+    let lines: Vec<String> = adjustments
+        .iter()
+        .map(|x| {
+            let reason: String = match x.get_reason() {
+                Some(reason) => format!(" ({})", reason),
+                None => "".to_string(),
+            };
+            format!(
+                "\t{}: {}{}",
+                x.get_time().0.format("%d/%m/%Y %H:%M:%S"),
+                render_seconds_human_readable(x.get_seconds(), show_times_in_hours),
+                reason
+            )
+        })
+        .collect();
+    return lines.join("\n");
+}
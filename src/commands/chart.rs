@@ -0,0 +1,160 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::HashMap;
+use std::process::exit;
+
+use crate::units::day::Day;
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::user_interaction::charts::{
+    render_balance_line, render_heatmap, render_stacked_bars, use_colour,
+};
+use crate::user_interaction::convert_input::convert_input_to_seconds;
+use crate::user_interaction::date_expressions::{
+    join_date_words, parse_date, parse_date_span, DateContext, DateSpan,
+};
+use crate::utils::balance::get_balance_ledger;
+use crate::utils::config::{get_config, Config};
+use crate::utils::dates_and_times::{get_local_now, get_month_range, get_year_range, DateRange};
+
+const BAR_WIDTH: usize = 40;
+const BALANCE_HEIGHT: usize = 10;
+
+pub fn chart(other_args: Vec<String>) {
+    let other_args: Vec<String> = join_date_words(other_args);
+    let result: Result<(), String> = match other_args.first().map(|x| x.as_str()) {
+        Some("heatmap") => heatmap(&other_args[1..]),
+        Some("tasks") => tasks(&other_args[1..]),
+        Some("balance") => balance(&other_args[1..]),
+        _ => Err(
+            "'punch chart' takes one of: 'heatmap [month|year] [date]', 'tasks <start> [end]' or 'balance <start> [end] [initial time behind]'."
+                .to_string(),
+        ),
+    };
+    if let Err(err_msg) = result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn parse_date_range_args(args: &[String]) -> Result<(NaiveDate, NaiveDate), String> {
+    let context: DateContext = DateContext::from_config();
+    let start_span: DateSpan = match args.first() {
+        Some(date_expr) => parse_date_span(date_expr, &context)?,
+        None => return Err("A start date is needed.".to_string()),
+    };
+    let end_date: NaiveDate = match args.get(1) {
+        Some(date_expr) => parse_date_span(date_expr, &context)?.end,
+        // A single date charts everything since then, while a period charts just that period.
+        None if start_span.is_single_date() => context.today,
+        None => start_span.end,
+    };
+    return Ok((start_span.start, end_date));
+}
+
+fn read_days(start_date: NaiveDate, end_date: NaiveDate) -> Result<Vec<(NaiveDate, Day)>, String> {
+    let local_now: DateTime<Local> = get_local_now();
+    let split_at_midnight: bool = get_config().split_days_at_midnight_or_default();
+    let days_in_range: DaysInRange =
+        read_days_in_range(start_date, end_date, &local_now, split_at_midnight)?;
+    return Ok(days_in_range.days);
+}
+
+fn heatmap(args: &[String]) -> Result<(), String> {
+    let (period, date_arg): (&str, Option<&String>) = match args.first().map(|x| x.as_str()) {
+        Some("month") | Some("year") => (args[0].as_str(), args.get(1)),
+        _ => ("month", args.first()),
+    };
+    let date: NaiveDate = match date_arg {
+        Some(date_expr) => parse_date(date_expr, &DateContext::from_config())?,
+        None => get_local_now().date_naive(),
+    };
+    let (start_date, end_date) = match period {
+        "year" => get_year_range(date),
+        _ => get_month_range(date),
+    };
+    let mut times_done: HashMap<NaiveDate, i64> = HashMap::new();
+    for (date, day) in read_days(start_date, end_date)? {
+        *times_done.entry(date).or_insert(0) += day.get_time_done_secs().unwrap_or(0);
+    }
+    let config: Config = get_config();
+    println!(
+        "Time done per day from {} to {}:",
+        start_date.format("%Y-%m-%d"),
+        end_date.format("%Y-%m-%d")
+    );
+    println!(
+        "{}",
+        render_heatmap(
+            &times_done,
+            start_date,
+            end_date,
+            config.day_in_minutes() * 60,
+            use_colour()
+        )
+    );
+    return Ok(());
+}
+
+fn tasks(args: &[String]) -> Result<(), String> {
+    let (start_date, end_date) = parse_date_range_args(args)?;
+    let mut rows: Vec<(NaiveDate, Vec<(String, i64)>)> = Vec::new();
+    for (date, day) in read_days(start_date, end_date)? {
+        let break_task_names = day.get_break_task_names();
+        let task_times: HashMap<String, i64> = day.get_task_times_secs();
+        let tasks: Vec<(String, i64)> = day
+            .get_tasks_in_chronological_order()
+            .into_iter()
+            .filter(|x| !break_task_names.contains(x))
+            .map(|x| {
+                let secs: i64 = *task_times.get(&x).unwrap_or(&0);
+                (x, secs)
+            })
+            .collect();
+        // Days split at midnight can have more than one part on the same date.
+        match rows.last_mut() {
+            Some((last_date, last_tasks)) if *last_date == date => last_tasks.extend(tasks),
+            _ => rows.push((date, tasks)),
+        }
+    }
+    if rows.len() == 0 {
+        println!("No days to chart.");
+        return Ok(());
+    }
+    let show_times_in_hours: bool = get_config().show_times_in_hours_or_default();
+    println!(
+        "{}",
+        render_stacked_bars(&rows, BAR_WIDTH, use_colour(), show_times_in_hours)
+    );
+    return Ok(());
+}
+
+fn balance(args: &[String]) -> Result<(), String> {
+    if args.len() > 3 {
+        return Err("'punch chart balance' takes at most three arguments.".to_string());
+    }
+    let (start_date, end_date) = parse_date_range_args(&args[..args.len().min(2)])?;
+    let initial_time_behind: i64 = match args.get(2) {
+        Some(time_str) => convert_input_to_seconds(time_str)?,
+        None => 0,
+    };
+    let mut change_per_date: HashMap<NaiveDate, i64> = HashMap::new();
+    for (date, day) in read_days(start_date, end_date)? {
+        *change_per_date.entry(date).or_insert(0) += day.get_time_left_secs().unwrap_or(0);
+    }
+    for adjustment in get_balance_ledger().get_adjustments_between(start_date, end_date) {
+        *change_per_date.entry(adjustment.get_date()).or_insert(0) +=
+            adjustment.get_time_behind_delta();
+    }
+    let mut running_time_behind: i64 = initial_time_behind;
+    let mut points: Vec<(NaiveDate, i64)> = Vec::new();
+    for date in DateRange(start_date, end_date) {
+        running_time_behind += change_per_date.get(&date).unwrap_or(&0);
+        points.push((date, running_time_behind));
+    }
+    let show_times_in_hours: bool = get_config().show_times_in_hours_or_default();
+    println!("Time behind at the end of each day:");
+    println!(
+        "{}",
+        render_balance_line(&points, BALANCE_HEIGHT, show_times_in_hours)
+    );
+    return Ok(());
+}
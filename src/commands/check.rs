@@ -0,0 +1,128 @@
+use chrono::{DateTime, Local};
+use serde_json::json;
+use std::process::exit;
+
+use crate::units::day::{get_current_day, Day};
+use crate::units::interval::DATETIME_FMT;
+use crate::units::reminders::{find_due_reminders, Reminder, ReminderConfig, ReminderThresholds};
+use crate::utils::config::{get_config, Config};
+use crate::utils::file_io::{expand_path, read_file, write_file};
+use crate::utils::hooks::run_hook_command_with_input;
+use crate::utils::lock::without_data_lock;
+use crate::utils::profiles::get_data_dir;
+
+const REMINDERS_FILE: &str = "reminders";
+const DRY_RUN_FLAG: &str = "--dry-run";
+
+/// Meant to be run every few minutes from cron or a systemd timer, so it only speaks up about
+/// reminders it hasn't sent yet.
+pub fn check(now: &DateTime<Local>, other_args: Vec<String>) {
+    let dry_run: bool = match other_args.iter().map(|x| x.as_str()).collect::<Vec<_>>()[..] {
+        [] => false,
+        [DRY_RUN_FLAG] => true,
+        _ => {
+            eprintln!("'punch check' only takes '{}'.", DRY_RUN_FLAG);
+            exit(1);
+        }
+    };
+    let config: Config = get_config();
+    let reminder_config: &ReminderConfig = match config.reminders() {
+        Some(reminder_config) => reminder_config,
+        None => {
+            eprintln!("There are no reminders set. Add 'reminders' to the config with 'punch edit-config' first.");
+            exit(1);
+        }
+    };
+    if let Err(err_msg) = reminder_config.check_hook_names() {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let day: Day = match get_current_day(now) {
+        Ok(day) => day,
+        // Not being punched in is normal between days, so there's nothing to remind about.
+        Err(_) => return,
+    };
+
+    let thresholds: ReminderThresholds = ReminderThresholds::new(
+        reminder_config,
+        config
+            .break_policy()
+            .and_then(|x| x.get_max_minutes_without_break()),
+        config.stale_day_hours_or_default(),
+    );
+    let day_start_str: String = day.get_day_start_as_str();
+    let mut sent_keys: Vec<String> = read_sent_reminder_keys(&day_start_str);
+    let due_reminders: Vec<Reminder> = find_due_reminders(
+        &day,
+        now,
+        &thresholds,
+        config.show_times_in_hours_or_default(),
+    );
+    for reminder in due_reminders {
+        if sent_keys.contains(&reminder.key) {
+            continue;
+        }
+        println!("{}", reminder.message);
+        if dry_run {
+            continue;
+        }
+        if let Some(hook) = reminder_config.get_hook(reminder.event) {
+            let payload: String = render_reminder_payload(&reminder, &day, now);
+            let env_vars: Vec<(String, String)> = vec![(
+                "PUNCH_EVENT".to_string(),
+                reminder.event.as_str().to_string(),
+            )];
+            let hook_result: Result<(), String> =
+                without_data_lock(|| run_hook_command_with_input(hook, env_vars, &payload));
+            if let Err(err_msg) = hook_result {
+                // It's tried again next time rather than being marked as sent.
+                eprintln!("{}", err_msg);
+                continue;
+            }
+        }
+        sent_keys.push(reminder.key);
+    }
+    if !dry_run {
+        write_sent_reminder_keys(&day_start_str, &sent_keys);
+    }
+}
+
+fn render_reminder_payload(reminder: &Reminder, day: &Day, now: &DateTime<Local>) -> String {
+    let mut day_so_far: Day = day.clone();
+    day_so_far
+        .end_day_at(now, false)
+        .expect("Only days that haven't ended have reminders");
+    let payload = json!({
+        "event": reminder.event.as_str(),
+        "message": reminder.message,
+        "time": now.format(DATETIME_FMT).to_string(),
+        "day_start": day.get_day_start_as_str(),
+        "task": day.get_latest_task_name(),
+        "on_break": day.on_break,
+        "time_done_secs": day_so_far.get_time_done_secs(),
+        "time_left_secs": day_so_far.get_time_left_secs(),
+    });
+    return payload.to_string();
+}
+
+fn get_reminders_file_path() -> String {
+    return expand_path(&get_data_dir()) + REMINDERS_FILE;
+}
+
+/// The reminders already sent for the day starting at `day_start_str`. The file only keeps the latest day.
+fn read_sent_reminder_keys(day_start_str: &str) -> Vec<String> {
+    let contents: String = read_file(&get_reminders_file_path()).unwrap_or_default();
+    let mut lines = contents.lines();
+    if lines.next() != Some(day_start_str) {
+        return Vec::new();
+    }
+    return lines.map(|x| x.to_string()).collect();
+}
+
+fn write_sent_reminder_keys(day_start_str: &str, keys: &[String]) {
+    let mut contents: String = day_start_str.to_string() + "\n";
+    for key in keys.iter() {
+        contents += &(key.clone() + "\n");
+    }
+    write_file(&get_reminders_file_path(), contents);
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::process::exit;
+
+use crate::units::break_policy::{render_violations_by_date, BreakPolicy, BreakViolation};
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date_range, DateContext};
+use crate::user_interaction::render_list_for_user::render_list_for_user;
+use crate::utils::config::{get_config, Config};
+use crate::utils::dates_and_times::get_local_now;
+
+pub fn check_breaks(args: Vec<String>) {
+    if let Err(err_msg) = check_breaks_in_range(join_date_words(args)) {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn check_breaks_in_range(args: Vec<String>) -> Result<(), String> {
+    let config: Config = get_config();
+    let policy: &BreakPolicy = config.break_policy().ok_or(
+        "There's no break policy set. Add 'break_policy' to the config with 'punch edit-config' first."
+            .to_string(),
+    )?;
+    if args.len() > 2 {
+        return Err(
+            "'punch check-breaks' takes a start date and an optional end date.".to_string(),
+        );
+    }
+    let context: DateContext = DateContext::from_config();
+    let (start_date, end_date): (NaiveDate, NaiveDate) = match args.first() {
+        Some(start_expr) => {
+            parse_date_range(start_expr, args.get(1).map(|x| x.as_str()), &context)?
+        }
+        None => (context.today, context.today),
+    };
+
+    let local_now: DateTime<Local> = get_local_now();
+    let days_in_range: DaysInRange = read_days_in_range(
+        start_date,
+        end_date,
+        &local_now,
+        config.split_days_at_midnight_or_default(),
+    )?;
+    let violations: Vec<(NaiveDate, BreakViolation)> =
+        policy.find_violations_in_days(&days_in_range.days);
+    println!(
+        "Checked: {}",
+        render_list_for_user(&days_in_range.get_dates_included(), None)
+    );
+    if days_in_range.days_not_ended.len() > 0 {
+        println!(
+            "Days not ended (only finished blocks are checked): {}",
+            render_list_for_user(&days_in_range.days_not_ended, None)
+        );
+    }
+    println!(
+        "{}",
+        render_violations_by_date(policy, &violations, config.show_times_in_hours_or_default())
+    );
+    return Ok(());
+}
@@ -0,0 +1,67 @@
+use chrono::NaiveDate;
+use std::process::exit;
+
+use crate::commands::day_summaries::{
+    aggregate_date_range, take_task_options_or_exit, DateRangeSummary,
+};
+use crate::units::comparison::Comparison;
+use crate::units::task_table::TaskTableOptions;
+use crate::user_interaction::date_expressions::{
+    join_date_words, parse_date_span, DateContext, DateSpan,
+};
+use crate::utils::config::get_config;
+
+pub fn compare(other_args: Vec<String>) {
+    let (task_options, other_args) = take_task_options_or_exit(other_args);
+    if let Err(err_msg) = compare_periods(join_date_words(other_args), &task_options) {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn render_span(span: &DateSpan) -> String {
+    let render_date = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+    if span.is_single_date() {
+        return render_date(span.start);
+    }
+    return format!("{} to {}", render_date(span.start), render_date(span.end));
+}
+
+fn compare_periods(args: Vec<String>, task_options: &TaskTableOptions) -> Result<(), String> {
+    if args.len() != 2 {
+        return Err(
+            "'punch compare' takes two dates or ranges, e.g. 'this-week last-week' or '2026-09 2026-10'."
+                .to_string(),
+        );
+    }
+    let context: DateContext = DateContext::from_config();
+    let first_span: DateSpan = parse_date_span(&args[0], &context)?;
+    let second_span: DateSpan = parse_date_span(&args[1], &context)?;
+    let first_summary: DateRangeSummary =
+        aggregate_date_range(first_span.start, first_span.end, None)?;
+    let second_summary: DateRangeSummary =
+        aggregate_date_range(second_span.start, second_span.end, None)?;
+    let comparison: Comparison = Comparison {
+        first_label: args[0].clone(),
+        first: &first_summary.aggregated,
+        second_label: args[1].clone(),
+        second: &second_summary.aggregated,
+    };
+
+    let show_times_in_hours: bool = get_config().show_times_in_hours_or_default();
+    println!(
+        "Comparing {} ({}) with {} ({}):",
+        args[0],
+        render_span(&first_span),
+        args[1],
+        render_span(&second_span)
+    );
+    comparison
+        .render_totals_table(show_times_in_hours)
+        .printstd();
+    println!("Tasks:");
+    comparison
+        .render_tasks_table(show_times_in_hours, task_options)
+        .printstd();
+    return Ok(());
+}
@@ -1,26 +1,60 @@
-use crate::commands::day_summaries::print_day_summary;
+use crate::commands::day_summaries::{print_day_summary, print_day_summary_with_time_behind};
+use crate::commands::working_time::warn_if_rest_period_too_short;
 use crate::utils::file_io::SafeFileEdit;
-use chrono::prelude::{DateTime, Local};
+use chrono::prelude::{DateTime, Local, NaiveDate};
 use std::process::exit;
 
-use crate::units::day::{read_day, read_day_from_date_str, write_day, Day};
+use crate::units::day::{get_latest_day, read_day, read_day_from_date_str, write_day, Day};
+use crate::units::task_table::TaskTableOptions;
+use crate::user_interaction::charts::{render_day_timeline, use_colour};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date, DateContext};
 
 use crate::utils::config::{get_config, update_config, Config};
+use crate::utils::dates_and_times::convert_date_to_date_str;
+use crate::utils::event_hooks::{queue_event_hook, StateEvent};
+
+const TIMELINE_WIDTH: usize = 60;
+const RAW_FLAG: &str = "--raw";
 
 pub fn punch_in(now: &DateTime<Local>, other_args: Vec<String>) {
     if let Ok(_) = read_day(now) {
         println!("You've already clocked in for the day!");
     } else {
         let parsed_args: (String, u64) = get_other_args_for_punch_in(other_args);
-        let new_day: Day = Day::new(&now, parsed_args.0, parsed_args.1, None);
-        println!(
-            "Clocking in for the day at '{}'",
-            &new_day.get_day_start_as_str()
-        );
-        write_day(&new_day);
+        match start_day(now, Some(parsed_args.0), parsed_args.1) {
+            Ok(new_day) => println!(
+                "Clocking in for the day at '{}'",
+                &new_day.get_day_start_as_str()
+            ),
+            Err(err_msg) => {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+        }
     }
 }
 
+/// Starts a new day, using the default start task if there isn't one.
+///
+/// This and the other state changes below are shared by the CLI and `punch serve`.
+pub fn start_day(
+    now: &DateTime<Local>,
+    start_task: Option<String>,
+    time_to_do: u64,
+) -> Result<Day, String> {
+    if let Ok(_) = read_day(now) {
+        return Err("You've already clocked in for the day!".to_string());
+    }
+    if let Some(previous_end) = get_latest_day().and_then(|x| x.get_day_end()) {
+        warn_if_rest_period_too_short(&previous_end.as_dt(), now);
+    }
+    let start_task: String = start_task.unwrap_or_else(get_default_punch_in_task);
+    let new_day: Day = Day::new(&now, start_task.clone(), time_to_do, None);
+    write_day(&new_day);
+    queue_event_hook(StateEvent::In, None, Some(start_task), now, &new_day);
+    return Ok(new_day);
+}
+
 fn get_other_args_for_punch_in(other_args: Vec<String>) -> (String, u64) {
     let default_time_to_do: u64 = get_default_day_in_minutes();
     println!(
@@ -41,7 +75,7 @@ fn get_other_args_for_punch_in(other_args: Vec<String>) -> (String, u64) {
     return (punch_in_task, default_time_to_do);
 }
 
-fn get_default_day_in_minutes() -> u64 {
+pub fn get_default_day_in_minutes() -> u64 {
     return get_config().day_in_minutes() as u64;
 }
 
@@ -49,36 +83,53 @@ fn get_default_punch_in_task() -> String {
     return get_config().get_default_punch_in_task().to_owned();
 }
 
-pub fn punch_out(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+pub fn punch_out(now: &DateTime<Local>, day: Day, other_args: Vec<String>) {
     let time_to_do_done_result: Result<bool, String> = parse_args_for_punch_out(other_args);
     if let Err(err_msg) = time_to_do_done_result {
         eprintln!("{}", err_msg);
         exit(1);
     }
+    if day.has_ended() {
+        println!("Can't punch out: Already punched out for the day!");
+        return;
+    }
     let time_to_do_done: bool = time_to_do_done_result.unwrap();
-    if let Ok(_) = day.end_day_at(&now, time_to_do_done) {
-        println!(
-            "Punching out for the day at '{}'",
-            &day.get_day_end_as_str().unwrap().trim()
-        );
-        let summary_result = print_day_summary(&day, true);
-        if let Err(err_msg) = summary_result {
-            eprintln!("{}", err_msg);
-            exit(1);
-        }
-        write_day(&day);
-        match update_time_behind(day) {
-            Ok(()) => (),
-            Err(err_msg) => {
+    // Ending the day adds today's time left to the balance, which the summary adds on itself.
+    let time_behind_before: i64 = get_config().get_seconds_behind();
+    match end_day(now, day, time_to_do_done) {
+        Ok(day) => {
+            println!(
+                "Punching out for the day at '{}'",
+                &day.get_day_end_as_str().unwrap().trim()
+            );
+            let summary_result = print_day_summary_with_time_behind(
+                &day,
+                Some(time_behind_before),
+                &TaskTableOptions::default(),
+            );
+            if let Err(err_msg) = summary_result {
                 eprintln!("{}", err_msg);
                 exit(1);
             }
         }
-    } else {
-        println!("Can't punch out: Already punched out for the day!");
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
     }
 }
 
+pub fn end_day(now: &DateTime<Local>, mut day: Day, time_to_do_done: bool) -> Result<Day, String> {
+    let previous_task: String = day.get_latest_task_name();
+    if let Err(_) = day.end_day_at(&now, time_to_do_done) {
+        return Err("Can't punch out: Already punched out for the day!".to_string());
+    }
+    write_day(&day);
+    update_time_behind(day.clone())?;
+    queue_event_hook(StateEvent::Out, Some(previous_task), None, now, &day);
+    return Ok(day);
+}
+
 fn parse_args_for_punch_out(other_args: Vec<String>) -> Result<bool, String> {
     return match other_args.len() {
         0 => Ok(false),
@@ -93,128 +144,183 @@ fn parse_args_for_punch_out(other_args: Vec<String>) -> Result<bool, String> {
     };
 }
 
-pub fn take_break(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day) {
-    let resolved_break_name: Result<String, &str> = get_name_for_break(other_args);
+pub fn take_break(now: &DateTime<Local>, other_args: Vec<String>, day: Day) {
+    let resolved_break_name: Result<Option<String>, &str> = get_name_for_break(other_args);
     if let Err(msg) = resolved_break_name {
         eprintln!("{}", msg);
         exit(1);
     }
-    let break_result: Result<(), &str> = day.start_break_at(
+    let break_result: Result<Day, String> = start_break(
+        now,
+        day,
         resolved_break_name.expect("break_name error should already have been handled"),
-        &now,
     );
-    if let Ok(_) = break_result {
-        println!("Taking a break at '{}'", &now);
-        write_day(&day);
-
-        if !day.has_ended() {
-            day.end_day_at(&now, false)
-                .expect("We should be able to end the day");
+    match break_result {
+        Ok(day) => {
+            println!("Taking a break at '{}'", &now);
+            print_day_summary_so_far(now, day);
         }
-
-        let summary_result = print_day_summary(&day, true);
-        if let Err(err_msg) = summary_result {
-            eprintln!("{}", err_msg);
+        Err(msg) => {
+            eprintln!("{}", msg);
             exit(1);
         }
-    } else {
-        let msg = break_result.unwrap_err();
-        eprintln!("{}", msg);
+    }
+}
+
+/// Starts a break, using the default break name if there isn't one.
+pub fn start_break(
+    now: &DateTime<Local>,
+    mut day: Day,
+    break_name: Option<String>,
+) -> Result<Day, String> {
+    let break_name: String =
+        break_name.unwrap_or_else(|| get_config().get_default_break_task().to_owned());
+    let previous_task: String = day.get_latest_task_name();
+    day.start_break_at(break_name.clone(), &now)
+        .map_err(|x| x.to_string())?;
+    write_day(&day);
+    queue_event_hook(
+        StateEvent::Pause,
+        Some(previous_task),
+        Some(break_name),
+        now,
+        &day,
+    );
+    return Ok(day);
+}
+
+fn print_day_summary_so_far(now: &DateTime<Local>, mut day: Day) {
+    if !day.has_ended() {
+        day.end_day_at(&now, false)
+            .expect("We should be able to end the day");
+    }
+
+    let summary_result = print_day_summary(&day, true);
+    if let Err(err_msg) = summary_result {
+        eprintln!("{}", err_msg);
         exit(1);
     }
 }
 
-pub fn get_name_for_break(other_args: Vec<String>) -> Result<String, &'static str> {
-    let default_break_name = get_config().get_default_break_task().to_owned();
+pub fn get_name_for_break(other_args: Vec<String>) -> Result<Option<String>, &'static str> {
     return match other_args.len() {
-        0 => Ok(default_break_name),
-        1 => Ok(other_args[0].to_owned()),
+        0 => Ok(None),
+        1 => Ok(Some(other_args[0].to_owned())),
         _ => Err("'punch pause' should have at most one argument!"),
     };
 }
 
-pub fn resume(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day) {
-    let new_block_task_result: Result<String, String> =
-        get_resume_task_from_args(other_args, day.clone());
+pub fn resume(now: &DateTime<Local>, other_args: Vec<String>, day: Day) {
+    let new_block_task_result: Result<Option<String>, String> =
+        get_resume_task_from_args(other_args);
     if let Err(msg) = new_block_task_result {
         eprintln!("{}", msg);
         exit(1);
     }
 
-    let new_block_task: String = new_block_task_result.expect("We've precluded no arguments");
-    let resume_result: Result<(), &str> = day.start_new_block(new_block_task, &now);
-    if let Ok(_) = resume_result {
-        println!("Back to work at '{}'", &now);
-        write_day(&day);
-        if !day.has_ended() {
-            day.end_day_at(&now, false)
-                .expect("We should be able to end the day");
+    let new_block_task: Option<String> = new_block_task_result.expect("Error already handled!");
+    match resume_work(now, day, new_block_task) {
+        Ok(day) => {
+            println!("Back to work at '{}'", &now);
+            print_day_summary_so_far(now, day);
         }
-
-        let summary_result = print_day_summary(&day, true);
-        if let Err(err_msg) = summary_result {
-            eprintln!("{}", err_msg);
+        Err(msg) => {
+            eprintln!("{}", msg);
             exit(1);
         }
-    } else {
-        let msg = resume_result.unwrap_err();
-        eprintln!("{}", msg);
-        exit(1);
     }
 }
 
-fn get_resume_task_from_args(other_args: Vec<String>, day: Day) -> Result<String, String> {
+/// Ends a break, going back to the task from before it if there isn't a new one.
+pub fn resume_work(
+    now: &DateTime<Local>,
+    mut day: Day,
+    new_block_task: Option<String>,
+) -> Result<Day, String> {
+    let new_block_task: String = new_block_task.unwrap_or_else(|| day.get_task_name(-2));
+    let previous_task: String = day.get_latest_task_name();
+    day.start_new_block(new_block_task.clone(), &now)
+        .map_err(|x| x.to_string())?;
+    write_day(&day);
+    queue_event_hook(
+        StateEvent::Resume,
+        Some(previous_task),
+        Some(new_block_task),
+        now,
+        &day,
+    );
+    return Ok(day);
+}
+
+fn get_resume_task_from_args(other_args: Vec<String>) -> Result<Option<String>, String> {
     return match other_args.len() {
-        0 => Ok(day.get_task_name(-2)),
-        1 => Ok(other_args[0].to_owned()),
+        0 => Ok(None),
+        1 => Ok(Some(other_args[0].to_owned())),
         _ => Err("'punch resume' should have at most one argument!".to_string()),
     };
 }
 
-pub fn punch_back_in(now: &DateTime<Local>, other_args: Vec<String>, mut day: Day) {
-    let new_block_task_result: Result<String, String> =
-        get_restart_task_from_args(other_args, day.clone());
+pub fn punch_back_in(now: &DateTime<Local>, other_args: Vec<String>, day: Day) {
+    let new_block_task_result: Result<Option<String>, String> =
+        get_restart_task_from_args(other_args);
     if let Err(msg) = new_block_task_result {
         eprintln!("{}", msg);
         exit(1);
     }
-    let new_block_task: String = new_block_task_result.expect("We've precluded no arguments");
-
-    let default_break_name = get_config().get_default_break_task().to_owned();
-    let restart_result: Result<i64, &str> =
-        day.restart_day(default_break_name, new_block_task, &now);
-    if let Ok(seconds_left_before) = restart_result {
-        println!("Back to work at '{}'", &now);
-        write_day(&day);
-        if !day.has_ended() {
-            day.end_day_at(&now, false)
-                .expect("We should be able to end the day");
+    let new_block_task: Option<String> = new_block_task_result.expect("Error already handled!");
+    match return_to_day(now, day, new_block_task) {
+        Ok(day) => {
+            println!("Back to work at '{}'", &now);
+            print_day_summary_so_far(now, day);
         }
-        let mut config: Config = get_config();
-        config.update_time_behind(-seconds_left_before);
-        update_config(config);
-
-        let summary_result = print_day_summary(&day, true);
-        if let Err(err_msg) = summary_result {
-            eprintln!("{}", err_msg);
+        Err(msg) => {
+            eprintln!("{}", msg);
             exit(1);
         }
-    } else {
-        let msg = restart_result.unwrap_err();
-        eprintln!("{}", msg);
-        exit(1);
     }
 }
 
-fn get_restart_task_from_args(other_args: Vec<String>, day: Day) -> Result<String, String> {
+/// Reopens a day that was punched out of, going back to its last task if there isn't a new one.
+pub fn return_to_day(
+    now: &DateTime<Local>,
+    mut day: Day,
+    new_block_task: Option<String>,
+) -> Result<Day, String> {
+    let new_block_task: String = new_block_task.unwrap_or_else(|| day.get_task_name(-1));
+    // Coming back on a later date than punching out starts a new stretch of work after a rest.
+    if let Some(day_end) = day.get_day_end() {
+        if day_end.as_dt().date_naive() < now.date_naive() {
+            warn_if_rest_period_too_short(&day_end.as_dt(), now);
+        }
+    }
+    let previous_task: String = day.get_latest_task_name();
+    let default_break_name = get_config().get_default_break_task().to_owned();
+    let seconds_left_before: i64 = day
+        .restart_day(default_break_name, new_block_task.clone(), &now)
+        .map_err(|x| x.to_string())?;
+    write_day(&day);
+    let mut config: Config = get_config();
+    config.update_time_behind(-seconds_left_before);
+    update_config(config);
+    queue_event_hook(
+        StateEvent::BackIn,
+        Some(previous_task),
+        Some(new_block_task),
+        now,
+        &day,
+    );
+    return Ok(day);
+}
+
+fn get_restart_task_from_args(other_args: Vec<String>) -> Result<Option<String>, String> {
     return match other_args.len() {
-        0 => Ok(day.get_task_name(-1)),
-        1 => Ok(other_args[0].to_owned()),
+        0 => Ok(None),
+        1 => Ok(Some(other_args[0].to_owned())),
         _ => Err("'punch back-in' should have at most one argument!".to_string()),
     };
 }
 
-pub fn switch_to_new_task(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+pub fn switch_to_new_task(now: &DateTime<Local>, day: Day, other_args: Vec<String>) {
     let new_block_task_result: Result<String, String> = get_new_task_block_from_args(other_args);
     if let Err(msg) = new_block_task_result {
         eprintln!("{}", msg);
@@ -222,27 +328,37 @@ pub fn switch_to_new_task(now: &DateTime<Local>, mut day: Day, other_args: Vec<S
     }
 
     let new_block_task: String = new_block_task_result.expect("We've handled errors");
-    let result: Result<(), &str> = day.start_new_block(new_block_task.to_owned(), &now);
-    if let Ok(_) = result {
-        println!("Now working on '{}' from '{}'", &new_block_task, &now);
-        write_day(&day);
-        if !day.has_ended() {
-            day.end_day_at(&now, false)
-                .expect("We should be able to end the day");
+    match start_task(now, day, new_block_task.clone()) {
+        Ok(day) => {
+            println!("Now working on '{}' from '{}'", &new_block_task, &now);
+            print_day_summary_so_far(now, day);
         }
-
-        let summary_result = print_day_summary(&day, true);
-        if let Err(err_msg) = summary_result {
-            eprintln!("{}", err_msg);
+        Err(msg) => {
+            eprintln!("{}", msg);
             exit(1);
         }
-    } else {
-        let msg = result.unwrap_err();
-        eprintln!("{}", msg);
-        exit(1);
     }
 }
 
+pub fn start_task(
+    now: &DateTime<Local>,
+    mut day: Day,
+    new_block_task: String,
+) -> Result<Day, String> {
+    let previous_task: String = day.get_latest_task_name();
+    day.start_new_block(new_block_task.clone(), &now)
+        .map_err(|x| x.to_string())?;
+    write_day(&day);
+    queue_event_hook(
+        StateEvent::Task,
+        Some(previous_task),
+        Some(new_block_task),
+        now,
+        &day,
+    );
+    return Ok(day);
+}
+
 fn get_new_task_block_from_args(other_args: Vec<String>) -> Result<String, String> {
     return match other_args.len() {
         0 => Err("'punch task' needs a new task name!".to_string()),
@@ -251,31 +367,74 @@ fn get_new_task_block_from_args(other_args: Vec<String>) -> Result<String, Strin
     };
 }
 
-pub fn view_day(day: Day) {
-    println!("Here's the day so far: \n");
-    println!("{}", day.as_string());
+pub fn view_day(now: &DateTime<Local>, day: Day, other_args: Vec<String>) {
+    match other_args
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        [] => {
+            println!("Here's the day so far: \n");
+            print_day_timeline(now, &day);
+        }
+        [RAW_FLAG] => {
+            println!("Here's the day so far: \n");
+            println!("{}", day.as_string());
+        }
+        _ => {
+            eprintln!("'punch view' only takes an optional '{}'.", RAW_FLAG);
+            exit(1);
+        }
+    }
+}
+
+fn print_day_timeline(now: &DateTime<Local>, day: &Day) {
+    println!(
+        "{}",
+        render_day_timeline(
+            day,
+            now,
+            TIMELINE_WIDTH,
+            use_colour(),
+            get_config().show_times_in_hours_or_default()
+        )
+    );
 }
 
-pub fn view_past(other_args: Vec<String>) {
-    let arg_result: Result<String, String> = parse_args_for_view_past(other_args);
+pub fn view_past(now: &DateTime<Local>, other_args: Vec<String>) {
+    let arg_result: Result<(String, bool), String> = parse_args_for_view_past(other_args);
 
     if let Err(msg) = arg_result {
         eprintln!("{}", msg);
         exit(1);
-    } else if let Ok(date_str) = arg_result {
+    } else if let Ok((date_str, raw)) = arg_result {
         if let Ok(day) = read_day_from_date_str(&date_str) {
             println!("Here is {}:\n", date_str);
-            println!("{}", day.as_string());
+            if raw {
+                println!("{}", day.as_string());
+            } else {
+                print_day_timeline(now, &day);
+            }
         } else {
             eprintln!("'{}' does not have a day associated with it!", date_str);
         }
     }
 }
 
-fn parse_args_for_view_past(other_args: Vec<String>) -> Result<String, String> {
-    return match other_args.len() {
-        1 => Ok(other_args[0].to_owned()),
-        _ => Err("'punch view-past' should have exactly one argument!".to_string()),
+fn parse_args_for_view_past(other_args: Vec<String>) -> Result<(String, bool), String> {
+    let other_args: Vec<String> = join_date_words(other_args);
+    let raw: bool = other_args.iter().any(|x| x == RAW_FLAG);
+    let date_args: Vec<&String> = other_args.iter().filter(|x| *x != RAW_FLAG).collect();
+    return match date_args.as_slice() {
+        [date_expr] => {
+            let date: NaiveDate = parse_date(date_expr, &DateContext::from_config())?;
+            Ok((convert_date_to_date_str(date), raw))
+        }
+        _ => Err(format!(
+            "'punch view-past' should have exactly one date argument, optionally with '{}'!",
+            RAW_FLAG
+        )),
     };
 }
 
@@ -311,7 +470,7 @@ pub fn edit_config() {
     config.safe_edit_from_file();
 }
 
-pub fn add_note_to_today(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+pub fn add_note_to_today(now: &DateTime<Local>, day: Day, other_args: Vec<String>) {
     if other_args.len() == 0 {
         eprintln!("'punch note' requires a msg argument!");
         exit(1);
@@ -322,12 +481,25 @@ pub fn add_note_to_today(now: &DateTime<Local>, mut day: Day, other_args: Vec<St
         exit(1);
     } else {
         let msg: String = (&other_args[0]).to_string();
-        day.add_note(now, &msg);
-        write_day(&day);
+        add_note(now, day, &msg);
         println!("New note '{}' added to today at '{}'.", msg, now);
     }
 }
 
+pub fn add_note(now: &DateTime<Local>, mut day: Day, msg: &String) -> Day {
+    day.add_note(now, msg);
+    write_day(&day);
+    let current_task: String = day.get_latest_task_name();
+    queue_event_hook(
+        StateEvent::Note,
+        Some(current_task.clone()),
+        Some(current_task),
+        now,
+        &day,
+    );
+    return day;
+}
+
 pub fn update_current_task_name(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
     let task_name_result: Result<String, String> = get_new_task_name_from_args(other_args);
     if let Err(msg) = task_name_result {
@@ -365,7 +537,7 @@ fn get_new_task_name_from_args(other_args: Vec<String>) -> Result<String, String
     };
 }
 
-fn update_time_behind(day: Day) -> Result<(), String> {
+pub fn update_time_behind(day: Day) -> Result<(), String> {
     if day.has_ended() {
         let mut config: Config = get_config();
         let time_left: i64 = day
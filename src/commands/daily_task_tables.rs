@@ -2,18 +2,31 @@ use chrono::{DateTime, Duration, Local, NaiveDate};
 use prettytable::{row, Table};
 use std::process::exit;
 
-use crate::units::day::{read_day_from_date_str, Day};
+use crate::commands::day_summaries::take_task_options_or_exit;
+use crate::units::aggregate_day::AggregateDay;
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::units::rounding::render_rounded_task_times;
+use crate::units::task_table::TaskTableOptions;
+use crate::user_interaction::date_expressions::{
+    join_date_words, parse_date, parse_date_range, DateContext,
+};
 use crate::user_interaction::human_readable::render_seconds_human_readable;
 use crate::user_interaction::render_list_for_user::render_list_for_user;
 use crate::utils::config::{get_config, Config};
-use crate::utils::dates_and_times::{get_local_now, DateRange};
+use crate::utils::dates_and_times::get_local_now;
 
 pub fn week_in_tasks(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
     let config: Config = get_config();
     let show_times_in_hours: bool = config.show_times_in_hours_or_default();
     match parse_args_for_week_in_tasks(args) {
         Ok((start_date, end_date)) => {
-            print_daily_task_summary_for_date_range(start_date, end_date, show_times_in_hours);
+            print_daily_task_summary_for_date_range(
+                start_date,
+                end_date,
+                show_times_in_hours,
+                &task_options,
+            );
         }
         Err(msg) => {
             eprintln!("{}", msg);
@@ -23,18 +36,15 @@ pub fn week_in_tasks(args: Vec<String>) {
 }
 
 fn parse_args_for_week_in_tasks(args: Vec<String>) -> Result<(NaiveDate, NaiveDate), String> {
+    let args: Vec<String> = join_date_words(args);
     if args.len() > 1 {
         return Err("Too many args found for week-in-tasks".to_owned());
     }
 
-    let current_date = if args.len() == 0 {
-        get_local_now().date_naive()
-    } else {
-        let parse_date_result = NaiveDate::parse_from_str(&args[0], "%Y-%m-%d");
-        if let Err(_) = parse_date_result {
-            return Err(format!("First argument for week-in-tasks must be a date of the form 'YYYY-mm-dd'. Got: '{}'", args[0]));
-        }
-        parse_date_result.expect("Error already handled")
+    let context: DateContext = DateContext::from_config();
+    let current_date = match args.first() {
+        Some(date_expr) => parse_date(date_expr, &context)?,
+        None => context.today,
     };
 
     let week_before: NaiveDate = current_date - Duration::days(6);
@@ -42,12 +52,16 @@ fn parse_args_for_week_in_tasks(args: Vec<String>) -> Result<(NaiveDate, NaiveDa
 }
 
 pub fn daily_tasks(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
     let config: Config = get_config();
     let show_times_in_hours: bool = config.show_times_in_hours_or_default();
     match parse_args_for_daily_tasks(args) {
-        Ok((start_date, end_date)) => {
-            print_daily_task_summary_for_date_range(start_date, end_date, show_times_in_hours)
-        }
+        Ok((start_date, end_date)) => print_daily_task_summary_for_date_range(
+            start_date,
+            end_date,
+            show_times_in_hours,
+            &task_options,
+        ),
         Err(msg) => {
             eprintln!("{}", msg);
             exit(1);
@@ -56,88 +70,59 @@ pub fn daily_tasks(args: Vec<String>) {
 }
 
 fn parse_args_for_daily_tasks(args: Vec<String>) -> Result<(NaiveDate, NaiveDate), String> {
+    let args: Vec<String> = join_date_words(args);
     if args.len() == 0 {
         return Err("daily-tasks must have at least one argument.".to_string());
     }
     if args.len() > 2 {
         return Err("daily-tasks must have at most two arguments.".to_string());
     }
-    let naive_date_result: Result<NaiveDate, chrono::ParseError> =
-        NaiveDate::parse_from_str(&args[0], "%Y-%m-%d");
-    if let Err(_) = naive_date_result {
-        return Err(format!(
-            "First argument for daily-tasks must be a date of the form 'YYYY-mm-dd'. Got: '{}'",
-            args[0]
-        ));
-    }
-    let naive_start_date = naive_date_result.expect("Error for this has already been handled!");
-    let naive_end_date: NaiveDate = if args.len() >= 2 {
-        let naive_end_date_result: Result<NaiveDate, chrono::ParseError> =
-            NaiveDate::parse_from_str(&args[1], "%Y-%m-%d");
-        if let Err(_) = naive_end_date_result {
-            return Err(format!("Second argument for daily-tasks must be a date of the form 'YYYY-mm-dd'. Got: '{}'", args[1]));
-        }
-        naive_end_date_result.expect("Error for this has already been handled!")
-    } else {
-        naive_start_date
-    };
-    return Ok((naive_start_date, naive_end_date));
+    return parse_date_range(
+        &args[0],
+        args.get(1).map(|x| x.as_str()),
+        &DateContext::from_config(),
+    );
 }
 
 pub fn print_daily_task_summary_for_date_range(
     start_date: NaiveDate,
     end_date: NaiveDate,
     show_times_in_hours: bool,
+    task_options: &TaskTableOptions,
 ) {
     let local_now: DateTime<Local> = get_local_now();
-    let todays_date: NaiveDate = local_now.date_naive();
-    let mut days_done: Vec<String> = Vec::new();
-    let mut days_not_there: Vec<String> = Vec::new();
-    let mut days_not_ended: Vec<String> = Vec::new();
+    let split_at_midnight: bool = get_config().split_days_at_midnight_or_default();
+    let days_result: Result<DaysInRange, String> =
+        read_days_in_range(start_date, end_date, &local_now, split_at_midnight);
+    if let Err(err_msg) = days_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let days_in_range: DaysInRange = days_result.expect("Already handled error!");
 
     let mut table = Table::new();
     table.set_titles(row!["Date", "Task", "Time", "Blocks"]);
-    for local_date in DateRange(start_date, end_date) {
+    let mut previous_date: Option<NaiveDate> = None;
+    for (local_date, this_day) in days_in_range.days.iter() {
         let this_date_str: String = local_date.format("%Y-%m-%d").to_string();
-        let this_day_result = read_day_from_date_str(&this_date_str);
-
-        if let Err(_) = this_day_result {
-            days_not_there.push(this_date_str.clone());
-            continue;
-        }
-        let mut this_day: Day = this_day_result.expect("Already handled error!");
-        if !this_day.has_ended() && (local_date == todays_date) {
-            match this_day.end_day_at(&local_now, false) {
-                Ok(()) => (),
-                Err(err_msg) => {
-                    eprintln!("Failed to end today: {err_msg}");
-                    exit(1);
-                }
-            }
-        } else if !this_day.has_ended() {
-            days_not_ended.push(this_date_str.clone());
-            continue;
-        }
-        let mut first_for_date: bool = true;
-        let task_summaries = this_day.get_task_times_secs_and_num_blocks();
-        for task_name in this_day.get_tasks_in_chronological_order() {
-            let (time, blocks) = task_summaries.get(&task_name).unwrap();
-            let date_col = if first_for_date {
+        for task_row in task_options.select(this_day.get_task_rows()) {
+            let date_col = if previous_date != Some(*local_date) {
                 this_date_str.clone()
             } else {
                 "".to_owned()
             };
             table.add_row(row![
                 &date_col,
-                &task_name,
-                render_seconds_human_readable(*time, show_times_in_hours),
-                &blocks
+                &task_row.task_name,
+                render_seconds_human_readable(task_row.secs, show_times_in_hours),
+                &task_row.blocks
             ]);
-            first_for_date = false;
+            previous_date = Some(*local_date);
         }
-
-        days_done.push(this_date_str.clone());
     }
+    let days_done: Vec<String> = days_in_range.get_dates_included();
+    let days_not_there: Vec<String> = days_in_range.days_not_there;
+    let days_not_ended: Vec<String> = days_in_range.days_not_ended;
     println!("Days included: {}", render_list_for_user(&days_done, None));
     if days_not_there.len() > 0 {
         println!(
@@ -152,4 +137,59 @@ pub fn print_daily_task_summary_for_date_range(
         );
     }
     table.printstd();
+
+    if let Some(policy) = get_config().rounding() {
+        let mut aggregated: AggregateDay = AggregateDay::new(0);
+        aggregated.set_rounding(Some(policy.clone()));
+        for (_, this_day) in days_in_range.days.iter() {
+            if let Err(err_msg) = aggregated.add_day(this_day.clone()) {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+        }
+        let task_order: Vec<String> = task_options
+            .select(aggregated.get_task_rows())
+            .into_iter()
+            .map(|x| x.task_name)
+            .collect();
+        println!(
+            "{}",
+            render_rounded_task_times(
+                policy,
+                &task_order,
+                &aggregated.get_task_times_secs(),
+                &aggregated
+                    .get_rounded_task_times_secs()
+                    .expect("Rounding was just set!"),
+                show_times_in_hours
+            )
+        );
+    }
+
+    let mut secondary_table = Table::new();
+    secondary_table.set_titles(row!["Date", "Task", "Time", "Blocks"]);
+    let mut previous_date: Option<NaiveDate> = None;
+    for (local_date, this_day) in days_in_range.days.iter() {
+        let this_date_str: String = local_date.format("%Y-%m-%d").to_string();
+        let task_summaries = this_day.get_secondary_task_times_secs_and_num_blocks();
+        for task_name in this_day.get_secondary_tasks_in_chronological_order() {
+            let (time, blocks) = task_summaries.get(&task_name).unwrap();
+            let date_col = if previous_date != Some(*local_date) {
+                this_date_str.clone()
+            } else {
+                "".to_owned()
+            };
+            secondary_table.add_row(row![
+                &date_col,
+                &task_name,
+                render_seconds_human_readable(*time, show_times_in_hours),
+                &blocks
+            ]);
+            previous_date = Some(*local_date);
+        }
+    }
+    if !secondary_table.is_empty() {
+        println!("Secondary track (not counted in time done):");
+        secondary_table.printstd();
+    }
 }
@@ -1,14 +1,38 @@
-use chrono::{DateTime, Duration, Local, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use prettytable::{row, Table};
 use std::process::exit;
 
+use crate::commands::balance::render_balance_adjustments;
+use crate::commands::working_time::print_working_time_violations;
 use crate::units::aggregate_day::AggregateDay;
+use crate::units::break_policy::{render_break_check, render_violations_by_date};
 use crate::units::day::{read_day_from_date_str, Day};
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::units::rounding::render_rounded_task_times;
+use crate::units::task_table::{take_task_table_options, TaskTableOptions};
 use crate::user_interaction::convert_input::convert_input_to_seconds;
+use crate::user_interaction::date_expressions::{
+    join_date_words, parse_date, parse_date_range, parse_date_span, DateContext,
+};
+use crate::user_interaction::human_readable::render_seconds_human_readable;
 use crate::user_interaction::render_list_for_user::render_list_for_user;
+use crate::utils::balance::{get_balance_ledger, BalanceAdjustment};
 use crate::utils::config::{get_config, Config};
-use crate::utils::dates_and_times::{get_local_now, DateRange};
+use crate::utils::dates_and_times::{get_local_now, get_period_range, split_into_periods, Period};
+
+/// Takes the task table flags like '--sort' and '--top' out of a command's args.
+pub fn take_task_options_or_exit(args: Vec<String>) -> (TaskTableOptions, Vec<String>) {
+    return match take_task_table_options(args) {
+        Ok(options_and_args) => options_and_args,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+}
 
 pub fn summarise_week(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
     let config: Config = get_config();
     let show_times_in_hours: bool = config.show_times_in_hours_or_default();
     match parse_args_for_summarise_week(args) {
@@ -18,6 +42,7 @@ pub fn summarise_week(args: Vec<String>) {
                 end_date,
                 initial_time_behind_opt,
                 show_times_in_hours,
+                &task_options,
             );
         }
         Err(msg) => {
@@ -30,18 +55,15 @@ pub fn summarise_week(args: Vec<String>) {
 fn parse_args_for_summarise_week(
     args: Vec<String>,
 ) -> Result<(NaiveDate, NaiveDate, Option<i64>), String> {
+    let args: Vec<String> = join_date_words(args);
     if args.len() > 2 {
         return Err("Too many args found for summarise_week".to_owned());
     }
 
-    let current_date = if args.len() == 0 {
-        get_local_now().date_naive()
-    } else {
-        let parse_date_result = NaiveDate::parse_from_str(&args[0], "%Y-%m-%d");
-        if let Err(_) = parse_date_result {
-            return Err(format!("First argument for summarise-week must be a date of the form 'YYYY-mm-dd'. Got: '{}'", args[0]));
-        }
-        parse_date_result.expect("Error already handled")
+    let context: DateContext = DateContext::from_config();
+    let current_date = match args.first() {
+        Some(date_expr) => parse_date(date_expr, &context)?,
+        None => context.today,
     };
 
     let week_before: NaiveDate = current_date - Duration::days(6);
@@ -60,6 +82,7 @@ fn parse_args_for_summarise_week(
 }
 
 pub fn summarise_days(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
     let config: Config = get_config();
     let show_times_in_hours: bool = config.show_times_in_hours_or_default();
     match parse_args_for_summarise_days(args) {
@@ -68,6 +91,7 @@ pub fn summarise_days(args: Vec<String>) {
             end_date,
             initial_time_behind_opt,
             show_times_in_hours,
+            &task_options,
         ),
         Err(msg) => {
             eprintln!("{}", msg);
@@ -79,85 +103,255 @@ pub fn summarise_days(args: Vec<String>) {
 fn parse_args_for_summarise_days(
     args: Vec<String>,
 ) -> Result<(NaiveDate, NaiveDate, Option<i64>), String> {
+    let args: Vec<String> = join_date_words(args);
     if args.len() == 0 {
         return Err("summarise-days must have at least one argument.".to_string());
     }
     if args.len() > 3 {
         return Err("summarise-days must have at most three arguments.".to_string());
     }
-    let naive_date_result: Result<NaiveDate, chrono::ParseError> =
-        NaiveDate::parse_from_str(&args[0], "%Y-%m-%d");
-    if let Err(_) = naive_date_result {
-        return Err(format!(
-            "First argument for summarise-days must be a date of the form 'YYYY-mm-dd'. Got: '{}'",
-            args[0]
-        ));
-    }
-    let naive_start_date = naive_date_result.expect("Error for this has already been handled!");
-    let naive_end_date: NaiveDate = if args.len() >= 2 {
-        let naive_end_date_result: Result<NaiveDate, chrono::ParseError> =
-            NaiveDate::parse_from_str(&args[1], "%Y-%m-%d");
-        if let Err(_) = naive_end_date_result {
-            return Err(format!("Second argument for summarise-days must be a date of the form 'YYYY-mm-dd'. Got: '{}'", args[1]));
-        }
-        naive_end_date_result.expect("Error for this has already been handled!")
-    } else {
-        naive_start_date
+    let context: DateContext = DateContext::from_config();
+    // With a range like 'last-month', the time behind can come straight after it.
+    let end_expr: Option<&str> = match args.get(1) {
+        Some(arg) if args.len() == 3 || parse_date_span(arg, &context).is_ok() => Some(arg),
+        _ => None,
     };
-    let initial_time_behind_opt = if args.len() == 3 {
-        let parse_result: Result<i64, String> = convert_input_to_seconds(&args[2]);
-        if let Err(err_msg) = parse_result {
-            return Err(err_msg);
-        }
-        Some(parse_result.expect("Error for this has already been handled!"))
-    } else {
-        None
+    let (naive_start_date, naive_end_date) = parse_date_range(&args[0], end_expr, &context)?;
+    let time_behind_arg: Option<&String> = match end_expr {
+        Some(_) => args.get(2),
+        None => args.get(1),
+    };
+    let initial_time_behind_opt = match time_behind_arg {
+        Some(time_behind_str) => Some(convert_input_to_seconds(time_behind_str)?),
+        None => None,
     };
 
     return Ok((naive_start_date, naive_end_date, initial_time_behind_opt));
 }
 
-pub fn summarise_date_range(
+pub fn summarise(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
+    let config: Config = get_config();
+    match parse_args_for_summarise(args, config.week_start_or_default()) {
+        Ok((period, start_date, end_date)) => summarise_by_period(
+            period,
+            start_date,
+            end_date,
+            config.week_start_or_default(),
+            config.show_times_in_hours_or_default(),
+            &task_options,
+        ),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            exit(1);
+        }
+    }
+}
+
+fn parse_args_for_summarise(
+    args: Vec<String>,
+    week_start: Weekday,
+) -> Result<(Period, NaiveDate, NaiveDate), String> {
+    let usage: &str =
+        "'punch summarise' takes '--period week|month|quarter|year' and optionally which dates to cover, e.g. 'last-month' or a start and end date.";
+    let mut period: Period = Period::Week;
+    let mut date_exprs: Vec<String> = Vec::new();
+    let mut args_iter = join_date_words(args).into_iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--period" | "-p" => period = Period::from_string(&args_iter.next().ok_or(usage)?)?,
+            _ => date_exprs.push(arg),
+        }
+    }
+    if date_exprs.len() > 2 {
+        return Err(usage.to_string());
+    }
+    let (start_date, end_date) = parse_date_range(
+        date_exprs.first().map(|x| x.as_str()).unwrap_or("today"),
+        date_exprs.get(1).map(|x| x.as_str()),
+        &DateContext::from_config(),
+    )?;
+    // Partial periods at either end are widened so that every period is complete.
+    return Ok((
+        period,
+        get_period_range(start_date, period, week_start).0,
+        get_period_range(end_date, period, week_start).1,
+    ));
+}
+
+fn render_period_label(start_date: NaiveDate, period: Period, week_start: Weekday) -> String {
+    return match period {
+        Period::Week if week_start == Weekday::Mon => start_date.format("%G-W%V").to_string(),
+        Period::Week => format!("Week of {}", start_date.format("%Y-%m-%d")),
+        Period::Month => start_date.format("%Y-%m").to_string(),
+        Period::Quarter => format!("{}-Q{}", start_date.year(), start_date.month0() / 3 + 1),
+        Period::Year => start_date.format("%Y").to_string(),
+    };
+}
+
+pub fn summarise_by_period(
+    period: Period,
     start_date: NaiveDate,
     end_date: NaiveDate,
-    initial_time_behind_opt: Option<i64>,
+    week_start: Weekday,
     show_times_in_hours: bool,
+    task_options: &TaskTableOptions,
 ) {
+    let summary_result: Result<DateRangeSummary, String> =
+        aggregate_date_range(start_date, end_date, None);
+    if let Err(err_msg) = summary_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let date_range_summary: DateRangeSummary = summary_result.expect("Already handled error!");
+
+    let mut buckets: Vec<(NaiveDate, NaiveDate, AggregateDay)> =
+        split_into_periods(start_date, end_date, period, week_start)
+            .into_iter()
+            .map(|(period_start, period_end)| (period_start, period_end, AggregateDay::new(0)))
+            .collect();
+    let find_bucket = |date: NaiveDate| -> Option<usize> {
+        return buckets.iter().position(|(period_start, period_end, _)| {
+            (*period_start <= date) && (date <= *period_end)
+        });
+    };
+    let day_buckets: Vec<Option<usize>> = date_range_summary
+        .days_in_range
+        .days
+        .iter()
+        .map(|(date, _)| find_bucket(*date))
+        .collect();
+    let adjustment_buckets: Vec<Option<usize>> = date_range_summary
+        .adjustments
+        .iter()
+        .map(|x| find_bucket(x.get_date()))
+        .collect();
+    for ((_, this_day), bucket_opt) in date_range_summary
+        .days_in_range
+        .days
+        .iter()
+        .zip(day_buckets)
+    {
+        if let Some(bucket) = bucket_opt {
+            if let Err(err_msg) = buckets[bucket].2.add_day(this_day.clone()) {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+        }
+    }
+    for (adjustment, bucket_opt) in date_range_summary
+        .adjustments
+        .iter()
+        .zip(adjustment_buckets)
+    {
+        if let Some(bucket) = bucket_opt {
+            buckets[bucket].2.add_balance_adjustment(adjustment);
+        }
+    }
+
+    let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+    let mut table = Table::new();
+    table.set_titles(row![
+        "Period",
+        "Days",
+        "Time done",
+        "Time to do",
+        "Time behind",
+        "Breaks",
+        "Break time"
+    ]);
+    for (period_start, _, aggregated) in buckets.iter() {
+        table.add_row(row![
+            render_period_label(*period_start, period, week_start),
+            aggregated.num_days,
+            render(aggregated.get_total_time_done() as i64),
+            render(aggregated.total_time_to_do as i64),
+            render(aggregated.get_time_behind_overall()),
+            aggregated.num_breaks,
+            render(aggregated.total_break_time as i64)
+        ]);
+    }
+    println!(
+        "From {} to {}:",
+        start_date.format("%Y-%m-%d"),
+        end_date.format("%Y-%m-%d")
+    );
+    table.printstd();
+
+    let days_not_ended: &Vec<String> = &date_range_summary.days_in_range.days_not_ended;
+    if days_not_ended.len() > 0 {
+        println!(
+            "Days not ended: {}",
+            render_list_for_user(days_not_ended, None)
+        );
+    }
+    let print_result: Result<(), String> = print_aggregated_day_summary(
+        &date_range_summary.aggregated,
+        false,
+        show_times_in_hours,
+        task_options,
+    );
+    if let Err(err_msg) = print_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    print_break_policy_violations(&date_range_summary.days_in_range.days, show_times_in_hours);
+    print_working_time_violations(start_date, end_date, show_times_in_hours);
+}
+
+pub struct DateRangeSummary {
+    pub days_in_range: DaysInRange,
+    pub aggregated: AggregateDay,
+    pub adjustments: Vec<BalanceAdjustment>,
+}
+
+/// Reads and aggregates the days in a range, shared by summaries and reports of that range.
+pub fn aggregate_date_range(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    initial_time_behind_opt: Option<i64>,
+) -> Result<DateRangeSummary, String> {
     let seed_time: i64 = initial_time_behind_opt.unwrap_or(0);
     let mut aggregated: AggregateDay = AggregateDay::new(seed_time);
+    aggregated.set_rounding(get_config().rounding().cloned());
 
     let local_now: DateTime<Local> = get_local_now();
-    let todays_date: NaiveDate = local_now.date_naive();
-    let mut days_aggregated: Vec<String> = Vec::new();
-    let mut days_not_there: Vec<String> = Vec::new();
-    let mut days_not_ended: Vec<String> = Vec::new();
-    for local_date in DateRange(start_date, end_date) {
-        let this_date_str: String = local_date.format("%Y-%m-%d").to_string();
-        let this_day_result = read_day_from_date_str(&this_date_str);
-
-        if let Err(_) = this_day_result {
-            days_not_there.push(this_date_str.clone());
-            continue;
-        }
-        let mut this_day: Day = this_day_result.expect("Already handled error!");
-        if !this_day.has_ended() && (local_date == todays_date) {
-            match this_day.end_day_at(&local_now, false) {
-                Ok(()) => (),
-                Err(err_msg) => {
-                    eprintln!("Failed to end today: {err_msg}");
-                    exit(1);
-                }
-            }
-        } else if !this_day.has_ended() {
-            days_not_ended.push(this_date_str.clone());
-            continue;
-        }
-        if let Err(err_msg) = aggregated.add_day(this_day) {
-            eprintln!("{}", err_msg);
-            exit(1);
-        };
-        days_aggregated.push(this_date_str.clone());
+    let split_at_midnight: bool = get_config().split_days_at_midnight_or_default();
+    let days_in_range: DaysInRange =
+        read_days_in_range(start_date, end_date, &local_now, split_at_midnight)?;
+    for (_, this_day) in days_in_range.days.iter() {
+        aggregated.add_day(this_day.clone())?;
+    }
+    let adjustments: Vec<BalanceAdjustment> =
+        get_balance_ledger().get_adjustments_between(start_date, end_date);
+    for adjustment in adjustments.iter() {
+        aggregated.add_balance_adjustment(adjustment);
+    }
+    return Ok(DateRangeSummary {
+        days_in_range: days_in_range,
+        aggregated: aggregated,
+        adjustments: adjustments,
+    });
+}
+
+pub fn summarise_date_range(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    initial_time_behind_opt: Option<i64>,
+    show_times_in_hours: bool,
+    task_options: &TaskTableOptions,
+) {
+    let summary_result: Result<DateRangeSummary, String> =
+        aggregate_date_range(start_date, end_date, initial_time_behind_opt);
+    if let Err(err_msg) = summary_result {
+        eprintln!("{}", err_msg);
+        exit(1);
     }
+    let date_range_summary: DateRangeSummary = summary_result.expect("Already handled error!");
+    let days_in_range: DaysInRange = date_range_summary.days_in_range;
+    let days_aggregated: Vec<String> = days_in_range.get_dates_included();
+    let days_not_there: &Vec<String> = &days_in_range.days_not_there;
+    let days_not_ended: &Vec<String> = &days_in_range.days_not_ended;
     println!(
         "Days aggregated: {}",
         render_list_for_user(&days_aggregated, None)
@@ -165,33 +359,61 @@ pub fn summarise_date_range(
     if days_not_there.len() > 0 {
         println!(
             "Days not there: {}",
-            render_list_for_user(&days_not_there, None)
+            render_list_for_user(days_not_there, None)
         );
     }
     if days_not_ended.len() > 0 {
         println!(
             "Days not ended: {}",
-            render_list_for_user(&days_not_ended, None)
+            render_list_for_user(days_not_ended, None)
         );
     }
+    let adjustments: Vec<BalanceAdjustment> = date_range_summary.adjustments;
     let print_result: Result<(), String> = print_aggregated_day_summary(
-        &aggregated,
+        &date_range_summary.aggregated,
         initial_time_behind_opt.is_some(),
         show_times_in_hours,
+        task_options,
     );
     if let Err(err_msg) = print_result {
         eprintln!("{}", err_msg);
         exit(1);
     }
+    if adjustments.len() > 0 {
+        println!(
+            "Balance adjustments:\n{}",
+            render_balance_adjustments(&adjustments, show_times_in_hours)
+        );
+    }
+    print_break_policy_violations(&days_in_range.days, show_times_in_hours);
+    print_working_time_violations(start_date, end_date, show_times_in_hours);
+}
+
+/// Lists the break policy violations in a range of days, if there's a break policy set.
+pub fn print_break_policy_violations(days: &[(NaiveDate, Day)], show_times_in_hours: bool) {
+    if let Some(policy) = get_config().break_policy() {
+        println!(
+            "\n{}",
+            render_violations_by_date(
+                policy,
+                &policy.find_violations_in_days(days),
+                show_times_in_hours
+            )
+        );
+    }
 }
 
 pub fn print_aggregated_day_summary(
     aggregate_day: &AggregateDay,
     include_overall_time_behind: bool,
     show_times_in_hours: bool,
+    task_options: &TaskTableOptions,
 ) -> Result<(), String> {
-    let summary_result: Result<String, String> = aggregate_day
-        .render_human_readable_summary(include_overall_time_behind, show_times_in_hours);
+    let summary_result: Result<String, String> = aggregate_day.render_human_readable_summary(
+        include_overall_time_behind,
+        show_times_in_hours,
+        task_options,
+    );
     return match summary_result {
         Ok(summary_str) => {
             println!("{}", summary_str);
@@ -202,6 +424,7 @@ pub fn print_aggregated_day_summary(
 }
 
 pub fn summary_past(args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
     let parse_result: Result<NaiveDate, String> = parse_args_for_summary_past(args);
     if let Err(err_msg) = parse_result {
         eprintln!("{}", err_msg);
@@ -215,23 +438,17 @@ pub fn summary_past(args: Vec<String>) {
         exit(1);
     }
     let day: Day = day_result.expect("Already handled the error case!");
-    if let Err(err_msg) = print_day_summary(&day, false) {
+    if let Err(err_msg) = print_day_summary_with_task_options(&day, false, &task_options) {
         eprintln!("{}", err_msg);
         exit(1);
     }
 }
 
 fn parse_args_for_summary_past(args: Vec<String>) -> Result<NaiveDate, String> {
+    let args: Vec<String> = join_date_words(args);
     return match args.len() {
         0 => Err("'punch summary-past' takes a single argument. None were given.".to_string()),
-        1 => {
-            let parse_result: Result<NaiveDate, chrono::ParseError> =
-                NaiveDate::parse_from_str(&args[0], "%Y-%m-%d");
-            if let Err(err_contents) = parse_result {
-                return Err(err_contents.to_string());
-            }
-            Ok(parse_result.expect("Error for this has already been processed."))
-        }
+        1 => parse_date(&args[0], &DateContext::from_config()),
         a => Err(format!(
             "'punch summary-past' takes a single argument. {} were given.",
             a
@@ -239,7 +456,12 @@ fn parse_args_for_summary_past(args: Vec<String>) -> Result<NaiveDate, String> {
     };
 }
 
-pub fn summary(now: &DateTime<Local>, mut day: Day) {
+pub fn summary(now: &DateTime<Local>, mut day: Day, args: Vec<String>) {
+    let (task_options, args) = take_task_options_or_exit(args);
+    if args.len() > 0 {
+        eprintln!("'punch summary' only takes '--sort', '--top', '--min', '--task' and '--exclude-breaks'.");
+        exit(1);
+    }
     if !day.has_ended() {
         let end_result: Result<(), &str> = day.end_day_at(&now, false);
         match end_result {
@@ -250,27 +472,80 @@ pub fn summary(now: &DateTime<Local>, mut day: Day) {
             }
         }
     }
-    if let Err(err_msg) = print_day_summary(&day, true) {
+    if let Err(err_msg) = print_day_summary_with_task_options(&day, true, &task_options) {
         eprintln!("{}", err_msg);
         exit(1);
     }
 }
 
 pub fn print_day_summary(day: &Day, use_config_for_time_behind: bool) -> Result<(), String> {
-    let config: Config = get_config();
-    let show_times_in_hours = config.show_times_in_hours_or_default();
+    return print_day_summary_with_task_options(
+        day,
+        use_config_for_time_behind,
+        &TaskTableOptions::default(),
+    );
+}
+
+pub fn print_day_summary_with_task_options(
+    day: &Day,
+    use_config_for_time_behind: bool,
+    task_options: &TaskTableOptions,
+) -> Result<(), String> {
     let time_behind_opt: Option<i64> = match use_config_for_time_behind {
-        true => Some(config.get_seconds_behind()),
+        true => Some(get_config().get_seconds_behind()),
         false => None,
     };
+    return print_day_summary_with_time_behind(day, time_behind_opt, task_options);
+}
+
+/// Prints the summary with the time behind from before the day, for when the stored balance already includes it.
+pub fn print_day_summary_with_time_behind(
+    day: &Day,
+    time_behind_opt: Option<i64>,
+    task_options: &TaskTableOptions,
+) -> Result<(), String> {
+    let config: Config = get_config();
+    let show_times_in_hours = config.show_times_in_hours_or_default();
     let summary_result: Result<String, String> =
-        day.render_human_readable_summary(time_behind_opt, show_times_in_hours);
+        day.render_human_readable_summary(time_behind_opt, show_times_in_hours, task_options);
 
-    return match summary_result {
-        Ok(summary_str) => {
-            println!("{}", summary_str);
-            Ok(())
-        }
-        Err(err_msg) => Err(err_msg),
-    };
+    if let Err(err_msg) = summary_result {
+        return Err(err_msg);
+    }
+    println!("{}", summary_result.expect("Error already handled!"));
+
+    if let Some(policy) = config.rounding() {
+        println!(
+            "\n{}",
+            render_rounded_task_times(
+                policy,
+                &task_options
+                    .select(day.get_task_rows())
+                    .into_iter()
+                    .map(|x| x.task_name)
+                    .collect(),
+                &day.get_task_times_secs(),
+                &day.get_rounded_task_times_secs(policy),
+                show_times_in_hours
+            )
+        );
+    }
+
+    if let Some(policy) = config.break_policy() {
+        println!(
+            "\n{}",
+            render_break_check(policy, &policy.check_day(day), show_times_in_hours)
+        );
+    }
+
+    let day_date: NaiveDate = day.get_day_start().as_dt().date_naive();
+    let adjustments: Vec<BalanceAdjustment> =
+        get_balance_ledger().get_adjustments_between(day_date, day_date);
+    if adjustments.len() > 0 {
+        println!(
+            "Balance adjustments on this day:\n{}",
+            render_balance_adjustments(&adjustments, show_times_in_hours)
+        );
+    }
+    return Ok(());
 }
@@ -0,0 +1,188 @@
+use std::env::var;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::process::exit;
+
+use crate::storage::sqlite::{SqliteRow, SqliteStorage};
+use crate::storage::{get_storage_kind, StorageKind};
+use crate::utils::encryption::{
+    ask_for_new_passphrase, check_key_decrypts, generate_key_hex, get_encryptable_paths,
+    get_encryption_settings, is_encrypted, set_encryption_settings, use_passphrase,
+    EncryptionSettings, DEFAULT_KEYFILE, PASSPHRASE_VAR,
+};
+use crate::utils::file_io::{expand_path, read_file, write_file};
+use crate::utils::history::has_history;
+use crate::utils::profiles::get_data_dir;
+
+pub fn encrypt_all(other_args: Vec<String>) {
+    let requested_settings: Option<EncryptionSettings> =
+        match parse_args_for_encrypt_all(other_args) {
+            Ok(settings) => settings,
+            Err(err_msg) => {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+        };
+    let current_settings: Option<EncryptionSettings> = get_encryption_settings();
+    let is_new_passphrase: bool = current_settings.is_none()
+        && requested_settings
+            .as_ref()
+            .is_some_and(|x| x.get_keyfile().is_none());
+    let settings: EncryptionSettings = match (current_settings, requested_settings) {
+        (Some(current), Some(requested)) if current.get_keyfile() != requested.get_keyfile() => {
+            eprintln!(
+                "The data is already encrypted with {}. Run 'punch decrypt-all' first to change how it's encrypted.",
+                current.describe()
+            );
+            exit(1);
+        }
+        (Some(current), _) => current,
+        (None, Some(requested)) => requested,
+        (None, None) => EncryptionSettings::with_keyfile(DEFAULT_KEYFILE.to_string()),
+    };
+    if let Some(keyfile) = settings.get_keyfile() {
+        create_keyfile_if_not_exists(keyfile);
+    }
+    if is_new_passphrase && var(PASSPHRASE_VAR).is_err() {
+        if let Err(err_msg) = ask_for_new_passphrase().and_then(|x| use_passphrase(&settings, &x)) {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+    check_key_or_exit(&settings);
+
+    // Everything is read before the settings change so plain and encrypted data are both handled.
+    let contents: Vec<(String, String)> = read_all_encryptable_files();
+    let rows: Vec<SqliteRow> = read_all_database_rows();
+    set_encryption_settings(Some(&settings));
+    rewrite_all(&contents, &rows);
+    println!(
+        "Encrypted {} with {}.",
+        describe_amount(&contents, &rows),
+        settings.describe()
+    );
+    if has_history() {
+        eprintln!(
+            "Warning: Earlier versions of the data in the git history at '{}' are still unencrypted. Deleting its '.git' directory removes them, along with everything 'punch history' and 'punch restore' can go back to.",
+            get_data_dir()
+        );
+    }
+    println!("While a day or the config is open in an editor, it's kept unencrypted in a '-temp' file next to it.");
+}
+
+fn parse_args_for_encrypt_all(args: Vec<String>) -> Result<Option<EncryptionSettings>, String> {
+    return match args.iter().map(|x| x.as_str()).collect::<Vec<&str>>()[..] {
+        [] => Ok(None),
+        ["--keyfile", keyfile] | ["-k", keyfile] => {
+            Ok(Some(EncryptionSettings::with_keyfile(keyfile.to_string())))
+        }
+        ["--passphrase"] | ["-p"] => Ok(Some(EncryptionSettings::with_new_passphrase_salt())),
+        _ => Err(
+            "'punch encrypt-all' takes either '--keyfile <path>' or '--passphrase'.".to_string(),
+        ),
+    };
+}
+
+fn create_keyfile_if_not_exists(keyfile: &str) {
+    let keyfile_path: String = expand_path(keyfile);
+    if Path::new(&keyfile_path).exists() {
+        return;
+    }
+    std::fs::write(&keyfile_path, generate_key_hex() + "\n").expect("Couldn't write the keyfile!");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&keyfile_path, std::fs::Permissions::from_mode(0o600))
+            .expect("Couldn't restrict the keyfile's permissions!");
+    }
+    println!(
+        "Created a new key at '{}'. Keep a copy of it somewhere safe: without it the data can't be read.",
+        keyfile
+    );
+}
+
+pub fn decrypt_all(other_args: Vec<String>) {
+    if other_args.len() > 0 {
+        eprintln!("'punch decrypt-all' doesn't take any arguments.");
+        exit(1);
+    }
+    let settings: EncryptionSettings = match get_encryption_settings() {
+        Some(settings) => settings,
+        None => {
+            eprintln!("The data isn't encrypted.");
+            exit(1);
+        }
+    };
+    check_key_or_exit(&settings);
+    let contents: Vec<(String, String)> = read_all_encryptable_files();
+    let rows: Vec<SqliteRow> = read_all_database_rows();
+    set_encryption_settings(None);
+    rewrite_all(&contents, &rows);
+    println!("Decrypted {}.", describe_amount(&contents, &rows));
+}
+
+/// Stops before anything is rewritten if the key doesn't open data that's already encrypted.
+fn check_key_or_exit(settings: &EncryptionSettings) {
+    let encrypted_sample: Option<String> = find_encrypted_sample();
+    if let Some(sample) = encrypted_sample {
+        if let Err(err_msg) = check_key_decrypts(settings, &sample) {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+}
+
+fn find_encrypted_sample() -> Option<String> {
+    let file_sample: Option<String> = get_encryptable_paths()
+        .iter()
+        .filter_map(|x| read_to_string(x).ok())
+        .find(|x| is_encrypted(x));
+    if file_sample.is_some() || get_storage_kind() != StorageKind::Sqlite {
+        return file_sample;
+    }
+    return SqliteStorage::open_default()
+        .read_raw_rows()
+        .into_iter()
+        .map(|x| x.get_contents().clone())
+        .find(|x| is_encrypted(x));
+}
+
+fn read_all_database_rows() -> Vec<SqliteRow> {
+    if get_storage_kind() != StorageKind::Sqlite {
+        return Vec::new();
+    }
+    return SqliteStorage::open_default().read_all_rows();
+}
+
+fn rewrite_all(contents: &[(String, String)], rows: &[SqliteRow]) {
+    for (path, file_contents) in contents.iter() {
+        write_file(path, file_contents.clone());
+    }
+    if rows.len() > 0 {
+        SqliteStorage::open_default().write_rows(rows);
+    }
+}
+
+fn describe_amount(contents: &[(String, String)], rows: &[SqliteRow]) -> String {
+    if get_storage_kind() != StorageKind::Sqlite {
+        return format!("{} files", contents.len());
+    }
+    return format!(
+        "{} files and {} rows of the SQLite database",
+        contents.len(),
+        rows.len()
+    );
+}
+
+fn read_all_encryptable_files() -> Vec<(String, String)> {
+    return get_encryptable_paths()
+        .into_iter()
+        .map(|path| match read_file(&path) {
+            Ok(file_contents) => (path, file_contents),
+            Err(err) => {
+                eprintln!("Couldn't read '{}': {}", path, err);
+                exit(1);
+            }
+        })
+        .collect();
+}
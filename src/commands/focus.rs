@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Local, Timelike};
+use std::process::exit;
+use std::thread::sleep;
+
+use crate::units::day::{read_day_from_date_str, write_day, Day};
+use crate::user_interaction::convert_input::convert_input_to_seconds;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::config::{get_config, Config};
+use crate::utils::dates_and_times::convert_date_to_date_str;
+use crate::utils::hooks::run_hook_command;
+use crate::utils::lock::without_data_lock;
+
+pub fn focus(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let parsed_args: Result<(i64, Option<String>), String> = parse_args_for_focus(other_args);
+    if let Err(err_msg) = parsed_args {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let (focus_secs, task_opt) = parsed_args.expect("Error already handled!");
+    let task_name: String = match task_opt {
+        Some(task_name) => task_name,
+        None if day.on_break => day.get_task_name(-2),
+        None => day.get_latest_task_name(),
+    };
+
+    if let Err(err_msg) = day.start_new_block(task_name.clone(), now) {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    // Days are stored to the second, so this matches the block once it's read back after the session.
+    let focus_block_start: DateTime<Local> = day
+        .get_latest_block_start()
+        .as_dt()
+        .with_nanosecond(0)
+        .expect("Zero nanoseconds is always valid");
+    write_day(&day);
+
+    let config: Config = get_config();
+    let show_times_in_hours: bool = config.show_times_in_hours_or_default();
+    let focus_end: DateTime<Local> = *now + Duration::seconds(focus_secs);
+    println!(
+        "Focusing on '{}' for {} until '{}'. Press Ctrl+C to stop the timer early.",
+        &task_name,
+        render_seconds_human_readable(focus_secs, show_times_in_hours),
+        focus_end.format("%H:%M:%S")
+    );
+    without_data_lock(|| {
+        sleep(
+            (focus_end - Local::now())
+                .to_std()
+                .unwrap_or(std::time::Duration::ZERO),
+        )
+    });
+
+    // Other commands may have changed the day while the timer was running.
+    let day_result: Result<Day, std::io::Error> =
+        read_day_from_date_str(&convert_date_to_date_str(day.get_date()));
+    if let Err(err_msg) = day_result {
+        eprintln!("Couldn't read the day after the focus session: {}", err_msg);
+        exit(1);
+    }
+    let mut day: Day = day_result.expect("Error already handled!");
+    let break_name: String = config.get_default_break_task().to_owned();
+    if let Err(err_msg) = day.complete_focus_block(&focus_block_start, break_name, &focus_end) {
+        println!("Focus session on '{}' ended: {}", &task_name, err_msg);
+        return;
+    }
+    write_day(&day);
+
+    // The bell character gets the terminal's attention if it's in the background.
+    println!(
+        "\x07Focus session on '{}' complete! Taking a break from '{}'. Use `punch resume` to get back to work.",
+        &task_name, &task_name
+    );
+    if let Some(hook) = config.focus_hook() {
+        let env_vars: Vec<(String, String)> = vec![
+            ("PUNCH_EVENT".to_string(), "focus-end".to_string()),
+            ("PUNCH_TASK".to_string(), task_name.clone()),
+            ("PUNCH_FOCUS_SECONDS".to_string(), focus_secs.to_string()),
+        ];
+        if let Err(err_msg) = without_data_lock(|| run_hook_command(hook, env_vars)) {
+            eprintln!("{}", err_msg);
+        }
+    }
+}
+
+fn parse_args_for_focus(other_args: Vec<String>) -> Result<(i64, Option<String>), String> {
+    if other_args.len() == 0 {
+        return Err("'punch focus' needs a length for the session, e.g. '25m'.".to_string());
+    }
+    let focus_secs: i64 = convert_input_to_seconds(&other_args[0])?;
+    if focus_secs <= 0 {
+        return Err("The length of a focus session must be positive!".to_string());
+    }
+    return match other_args.len() {
+        1 => Ok((focus_secs, None)),
+        3 => {
+            let flag: &String = &other_args[1];
+            if (flag != "--task") & (flag != "-t") {
+                return Err(format!("Unrecognised argument or flag: {}", flag));
+            }
+            Ok((focus_secs, Some(other_args[2].to_owned())))
+        }
+        _ => Err("'punch focus' takes a length and optionally '--task <task>'.".to_string()),
+    };
+}
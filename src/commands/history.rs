@@ -0,0 +1,95 @@
+use std::process::exit;
+
+use crate::storage::{get_storage_kind, StorageKind};
+use crate::units::day::{write_day, Day, DAILY_DIR};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date, DateContext};
+use crate::utils::config::{update_config, Config, CONFIG_FILE};
+use crate::utils::dates_and_times::convert_date_to_date_str;
+use crate::utils::encryption::decrypt_if_needed;
+use crate::utils::file_io::FromString;
+use crate::utils::history::run_git;
+
+pub fn history(other_args: Vec<String>) {
+    let other_args: Vec<String> = join_date_words(other_args);
+    if other_args.len() > 1 {
+        eprintln!("'punch history' takes at most one argument: a date.");
+        exit(1);
+    }
+    let mut git_args: Vec<String> = vec![
+        "log".to_string(),
+        "--format=%h %ad %s".to_string(),
+        "--date=format:%Y-%m-%d %H:%M".to_string(),
+    ];
+    if let Some(date_expr) = other_args.first() {
+        git_args.push("--".to_string());
+        git_args.push(DAILY_DIR.to_string() + &parse_date_str_or_exit(date_expr));
+    }
+    let git_args_as_str: Vec<&str> = git_args.iter().map(|x| x.as_str()).collect();
+    match run_git(&git_args_as_str) {
+        Ok(log) if log.trim().len() == 0 => println!("No history found."),
+        Ok(log) => print!("{}", log),
+        Err(err_msg) => {
+            eprintln!(
+                "Couldn't read the history. Is 'git_history' enabled in the config? {}",
+                err_msg
+            );
+            exit(1);
+        }
+    }
+}
+
+fn parse_date_str_or_exit(date_expr: &str) -> String {
+    return match parse_date(date_expr, &DateContext::from_config()) {
+        Ok(date) => convert_date_to_date_str(date),
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+}
+
+pub fn restore(other_args: Vec<String>) {
+    let other_args: Vec<String> = join_date_words(other_args);
+    if (other_args.len() == 0) || (other_args.len() > 2) {
+        eprintln!("'punch restore' takes a revision and optionally a date.");
+        exit(1);
+    }
+    if get_storage_kind() != StorageKind::Yaml {
+        eprintln!("'punch restore' only works with the yaml storage backend.");
+        exit(1);
+    }
+    let revision: &String = &other_args[0];
+    let file_in_history: String = match other_args.get(1) {
+        Some(date_expr) => DAILY_DIR.to_string() + &parse_date_str_or_exit(date_expr),
+        None => CONFIG_FILE.to_string(),
+    };
+    let contents: String = match run_git(&["show", &format!("{}:{}", revision, file_in_history)])
+        .and_then(decrypt_if_needed)
+    {
+        Ok(contents) => contents,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+
+    // Restoring goes through the usual writes so the restore is recorded in the history too.
+    if other_args.len() == 2 {
+        match Day::try_from_string(&contents) {
+            Ok(day) => write_day(&day),
+            Err(err) => {
+                eprintln!("The day at '{}' is malformed: {}", revision, err);
+                exit(1);
+            }
+        }
+    } else {
+        match Config::try_from_string(&contents) {
+            Ok(config) => update_config(config),
+            Err(err) => {
+                eprintln!("The config at '{}' is malformed: {}", revision, err);
+                exit(1);
+            }
+        }
+    }
+    println!("Restored '{}' from '{}'.", file_in_history, revision);
+}
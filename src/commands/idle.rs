@@ -0,0 +1,114 @@
+use chrono::{DateTime, Local};
+use std::process::exit;
+
+use crate::commands::day_summaries::print_day_summary;
+use crate::units::day::{write_day, Day};
+use crate::user_interaction::convert_input::convert_input_to_seconds;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::user_interaction::prompt::ask_for_confirmation;
+use crate::utils::activity::{
+    create_heartbeat_dir_if_not_exists, read_heartbeats_between, record_heartbeat,
+};
+use crate::utils::config::{get_config, Config};
+use crate::utils::file_io::ToFile;
+
+pub fn heartbeat(now: &DateTime<Local>) {
+    // This is meant to be called often from editors and shell hooks, so it stays quiet.
+    create_heartbeat_dir_if_not_exists();
+    record_heartbeat(now);
+}
+
+pub fn idle_fix(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    let config: Config = get_config();
+    let show_times_in_hours: bool = config.show_times_in_hours_or_default();
+    let default_threshold_secs: i64 = (config.idle_threshold_minutes_or_default() * 60) as i64;
+    let parsed_args: Result<(i64, bool), String> =
+        parse_args_for_idle_fix(other_args, default_threshold_secs);
+    if let Err(err_msg) = parsed_args {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let (threshold_secs, skip_confirmation) = parsed_args.expect("Error already handled!");
+
+    let until: DateTime<Local> = match day.get_day_end() {
+        Some(end) => end.as_dt(),
+        None => *now,
+    };
+    let activity: Vec<DateTime<Local>> =
+        read_heartbeats_between(day.get_day_start().as_dt().date_naive(), until.date_naive());
+    let day_start: DateTime<Local> = day.get_day_start().as_dt();
+    if !activity.iter().any(|x| (*x >= day_start) && (*x <= until)) {
+        eprintln!("No activity has been recorded for this day. Set up your editor or shell to call `punch heartbeat` first.");
+        exit(1);
+    }
+
+    let gaps: Vec<(DateTime<Local>, DateTime<Local>)> =
+        day.find_idle_gaps(&activity, threshold_secs, &until);
+    if gaps.len() == 0 {
+        println!(
+            "No idle gaps longer than {} found.",
+            render_seconds_human_readable(threshold_secs, show_times_in_hours)
+        );
+        return;
+    }
+    println!("Found the following idle gaps outside of breaks:");
+    for (gap_start, gap_end) in gaps.iter() {
+        println!(
+            "\t{} - {} ({})",
+            gap_start.format("%H:%M:%S"),
+            gap_end.format("%H:%M:%S"),
+            render_seconds_human_readable(
+                (*gap_end - *gap_start).num_seconds(),
+                show_times_in_hours
+            )
+        );
+    }
+    if !skip_confirmation && !ask_for_confirmation("Insert these as breaks?") {
+        println!("No breaks inserted.");
+        return;
+    }
+    if day.read_stored_string() != Some(day.as_string()) {
+        eprintln!("The day was changed by another punch command in the meantime. Run 'punch idle-fix' again.");
+        exit(1);
+    }
+
+    let break_name: String = config.get_default_break_task().to_owned();
+    for (gap_start, gap_end) in gaps.iter() {
+        if let Err(err_msg) = day.insert_break_between(break_name.clone(), gap_start, gap_end) {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+    write_day(&day);
+    println!("Inserted {} breaks.", gaps.len());
+
+    if !day.has_ended() {
+        day.end_day_at(&now, false)
+            .expect("We should be able to end the day");
+    }
+    let summary_result = print_day_summary(&day, true);
+    if let Err(err_msg) = summary_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn parse_args_for_idle_fix(
+    other_args: Vec<String>,
+    default_threshold_secs: i64,
+) -> Result<(i64, bool), String> {
+    let mut threshold_secs: i64 = default_threshold_secs;
+    let mut skip_confirmation: bool = false;
+    let mut args_iter = other_args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--yes" | "-y" => skip_confirmation = true,
+            "--threshold" | "-t" => match args_iter.next() {
+                Some(threshold_str) => threshold_secs = convert_input_to_seconds(threshold_str)?,
+                None => return Err("'--threshold' needs a value, e.g. '20m'.".to_string()),
+            },
+            other => return Err(format!("Unrecognised argument or flag: {}", other)),
+        }
+    }
+    return Ok((threshold_secs, skip_confirmation));
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::HashMap;
+use std::process::exit;
+
+use crate::units::aggregate_day::AggregateDay;
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::units::invoice::{Billing, Invoice, InvoiceFormat};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date_range, DateContext};
+use crate::user_interaction::render_list_for_user::render_list_for_user;
+use crate::utils::config::{get_config, Config};
+use crate::utils::dates_and_times::get_local_now;
+use crate::utils::profiles::{get_active_profile, profile_exists, set_active_profile};
+
+struct InvoiceArgs {
+    // Parsed once the client's profile is in use, as it has its own week start.
+    date_exprs: Vec<String>,
+    client: Option<String>,
+    format: InvoiceFormat,
+}
+
+pub fn invoice(other_args: Vec<String>) {
+    let invoice_args: InvoiceArgs = match parse_args_for_invoice(other_args) {
+        Ok(invoice_args) => invoice_args,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+    // Clients are profiles, each with its own days and rates.
+    let client: String = invoice_args.client.unwrap_or(get_active_profile());
+    if !profile_exists(&client) {
+        eprintln!(
+            "There's no profile for the client '{}'. Create it with 'punch profile create {}'.",
+            client, client
+        );
+        exit(1);
+    }
+    set_active_profile(&client);
+    let (start_date, end_date): (NaiveDate, NaiveDate) = match parse_date_range(
+        &invoice_args.date_exprs[0],
+        invoice_args.date_exprs.get(1).map(|x| x.as_str()),
+        &DateContext::from_config(),
+    ) {
+        Ok(date_range) => date_range,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+    let config: Config = get_config();
+    let billing: Billing = match config.billing() {
+        Some(billing) => billing.clone(),
+        None => {
+            eprintln!(
+                "No rates are set up for '{}'. Add a 'billing' section to its config first.",
+                client
+            );
+            exit(1);
+        }
+    };
+
+    let local_now: DateTime<Local> = get_local_now();
+    let days_result: Result<DaysInRange, String> = read_days_in_range(
+        start_date,
+        end_date,
+        &local_now,
+        config.split_days_at_midnight_or_default(),
+    );
+    if let Err(err_msg) = days_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let days_in_range: DaysInRange = days_result.expect("Already handled error!");
+    let mut aggregated: AggregateDay = AggregateDay::new(0);
+    aggregated.set_rounding(config.rounding().cloned());
+    for (_, this_day) in days_in_range.days.iter() {
+        if let Err(err_msg) = aggregated.add_day(this_day.clone()) {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+
+    let task_times_secs: HashMap<String, i64> = aggregated
+        .get_rounded_task_times_secs()
+        .unwrap_or(aggregated.get_task_times_secs())
+        .into_iter()
+        .filter(|(task_name, _)| !aggregated.is_break_task(task_name))
+        .collect();
+    let invoice: Invoice = Invoice::new(client, start_date, end_date, &billing, &task_times_secs);
+    print!("{}", invoice.render(invoice_args.format));
+    if invoice_args.format == InvoiceFormat::Text {
+        println!();
+    }
+
+    // Warnings go to stderr so they don't end up in exported files.
+    if days_in_range.days_not_ended.len() > 0 {
+        eprintln!(
+            "Days not ended (not included): {}",
+            render_list_for_user(&days_in_range.days_not_ended, None)
+        );
+    }
+    if invoice.unbilled_tasks.len() > 0 {
+        eprintln!(
+            "Tasks without a rate (not included): {}",
+            render_list_for_user(&invoice.unbilled_tasks, None)
+        );
+    }
+}
+
+fn parse_args_for_invoice(args: Vec<String>) -> Result<InvoiceArgs, String> {
+    let usage: &str = "'punch invoice' takes a start and end date (or a single range like 'last-month'), and optionally '--client <name>' and '--format text|csv|markdown|html'.";
+    let mut date_exprs: Vec<String> = Vec::new();
+    let mut client: Option<String> = None;
+    let mut format: InvoiceFormat = InvoiceFormat::Text;
+    let mut args_iter = join_date_words(args).into_iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--client" | "-c" => client = Some(args_iter.next().ok_or(usage)?),
+            "--format" | "-f" => {
+                format = InvoiceFormat::from_string(&args_iter.next().ok_or(usage)?)?
+            }
+            _ => date_exprs.push(arg),
+        }
+    }
+    if (date_exprs.len() == 0) || (date_exprs.len() > 2) {
+        return Err(usage.to_string());
+    }
+    return Ok(InvoiceArgs {
+        date_exprs: date_exprs,
+        client: client,
+        format: format,
+    });
+}
@@ -1,3 +1,22 @@
+pub mod balance;
+pub mod chart;
+pub mod check;
+pub mod check_breaks;
+pub mod compare;
 pub mod core;
 pub mod daily_task_tables;
 pub mod day_summaries;
+pub mod encryption;
+pub mod focus;
+pub mod history;
+pub mod idle;
+pub mod invoice;
+pub mod profile;
+pub mod report;
+pub mod secondary_track;
+pub mod serve;
+pub mod stale_days;
+pub mod storage;
+pub mod timezone;
+pub mod undo;
+pub mod working_time;
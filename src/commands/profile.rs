@@ -0,0 +1,171 @@
+use chrono::{DateTime, Local, NaiveDate};
+use prettytable::{Cell, Row, Table};
+use std::process::exit;
+
+use crate::units::day::create_daily_dir_if_not_exists;
+use crate::units::days_in_range::{read_days_in_range, DaysInRange};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date_range, DateContext};
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::config::{create_default_config_if_not_exists, get_config};
+use crate::utils::dates_and_times::{convert_date_to_date_str, get_local_now, DateRange};
+use crate::utils::profiles::{
+    find_cross_profile_overlaps, get_active_profile, get_profile_settings, list_profiles,
+    profile_exists, set_active_profile, validate_profile_name, write_profile_settings,
+    ProfileOverlap, ProfileSettings, WorkIntervals,
+};
+
+pub fn profile(other_args: Vec<String>) {
+    let result: Result<(), String> = match other_args.first().map(|x| x.as_str()) {
+        None | Some("list") => list(),
+        Some("create") => create(&other_args[1..]),
+        Some("switch") => switch(&other_args[1..]),
+        Some("report") => report(&other_args[1..]),
+        Some(other) => Err(format!(
+            "'{}' isn't a profile command. Try one of: list, create, switch, report.",
+            other
+        )),
+    };
+    if let Err(err_msg) = result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn get_single_name_arg(args: &[String], command: &str) -> Result<String, String> {
+    if args.len() != 1 {
+        return Err(format!("'punch profile {}' takes a profile name.", command));
+    }
+    validate_profile_name(&args[0])?;
+    return Ok(args[0].clone());
+}
+
+fn list() -> Result<(), String> {
+    let active_profile: String = get_active_profile();
+    let default_profile: String = get_profile_settings().get_default_profile();
+    for name in list_profiles() {
+        let marker: &str = if name == active_profile { "*" } else { " " };
+        let suffix: &str = if name == default_profile {
+            " (default)"
+        } else {
+            ""
+        };
+        println!("{} {}{}", marker, name, suffix);
+    }
+    return Ok(());
+}
+
+fn create(args: &[String]) -> Result<(), String> {
+    let name: String = get_single_name_arg(args, "create")?;
+    if profile_exists(&name) {
+        return Err(format!("The profile '{}' already exists.", name));
+    }
+    let active_profile: String = get_active_profile();
+    set_active_profile(&name);
+    create_daily_dir_if_not_exists();
+    create_default_config_if_not_exists();
+    set_active_profile(&active_profile);
+    println!(
+        "Created the profile '{}'. Use 'punch --profile {} edit-config' to set its day length and default tasks.",
+        name, name
+    );
+    return Ok(());
+}
+
+fn switch(args: &[String]) -> Result<(), String> {
+    let name: String = get_single_name_arg(args, "switch")?;
+    if !profile_exists(&name) {
+        return Err(format!(
+            "The profile '{}' doesn't exist. Create it with 'punch profile create {}'.",
+            name, name
+        ));
+    }
+    let mut settings: ProfileSettings = get_profile_settings();
+    settings.set_default_profile(&name);
+    write_profile_settings(&settings);
+    println!("Now using the profile '{}' by default.", name);
+    return Ok(());
+}
+
+fn parse_args_for_report(args: &[String]) -> Result<(NaiveDate, NaiveDate), String> {
+    let args: Vec<String> = join_date_words(args.to_vec());
+    if (args.len() == 0) || (args.len() > 2) {
+        return Err(
+            "'punch profile report' takes a start date and optionally an end date.".to_string(),
+        );
+    }
+    return parse_date_range(
+        &args[0],
+        args.get(1).map(|x| x.as_str()),
+        &DateContext::from_config(),
+    );
+}
+
+fn report(args: &[String]) -> Result<(), String> {
+    let (start_date, end_date) = parse_args_for_report(args)?;
+    let local_now: DateTime<Local> = get_local_now();
+    let show_times_in_hours: bool = get_config().show_times_in_hours_or_default();
+
+    // Each profile is read in turn with its own storage and config.
+    let active_profile: String = get_active_profile();
+    let mut days_per_profile: Vec<(String, DaysInRange)> = Vec::new();
+    for name in list_profiles() {
+        set_active_profile(&name);
+        let split_at_midnight: bool = get_config().split_days_at_midnight_or_default();
+        let days_result: Result<DaysInRange, String> =
+            read_days_in_range(start_date, end_date, &local_now, split_at_midnight);
+        set_active_profile(&active_profile);
+        days_per_profile.push((name, days_result?));
+    }
+
+    let mut table: Table = Table::new();
+    let mut titles: Vec<Cell> = vec![Cell::new("Date")];
+    for (name, _) in days_per_profile.iter() {
+        titles.push(Cell::new(name));
+    }
+    titles.push(Cell::new("Total"));
+    table.set_titles(Row::new(titles));
+
+    let mut overlaps: Vec<ProfileOverlap> = Vec::new();
+    for date in DateRange(start_date, end_date) {
+        let mut cells: Vec<Cell> = vec![Cell::new(&convert_date_to_date_str(date))];
+        let mut total_secs: i64 = 0;
+        let mut work_per_profile: Vec<(String, WorkIntervals)> = Vec::new();
+        for (name, days_in_range) in days_per_profile.iter() {
+            let days_on_date = days_in_range.days.iter().filter(|(x, _)| *x == date);
+            let mut secs: i64 = 0;
+            let mut intervals: WorkIntervals = Vec::new();
+            for (_, day) in days_on_date {
+                secs += day.get_time_done_secs().unwrap_or(0);
+                intervals.append(&mut day.get_work_intervals());
+            }
+            total_secs += secs;
+            cells.push(Cell::new(&render_seconds_human_readable(
+                secs,
+                show_times_in_hours,
+            )));
+            work_per_profile.push((name.clone(), intervals));
+        }
+        if total_secs == 0 {
+            continue;
+        }
+        cells.push(Cell::new(&render_seconds_human_readable(
+            total_secs,
+            show_times_in_hours,
+        )));
+        table.add_row(Row::new(cells));
+        overlaps.append(&mut find_cross_profile_overlaps(&work_per_profile));
+    }
+    table.printstd();
+
+    for overlap in overlaps.iter() {
+        println!(
+            "Double-booked on {}: '{}' and '{}' both from {} to {}.",
+            overlap.start.format("%Y-%m-%d"),
+            overlap.first_profile,
+            overlap.second_profile,
+            overlap.start.format("%H:%M"),
+            overlap.end.format("%H:%M")
+        );
+    }
+    return Ok(());
+}
@@ -0,0 +1,97 @@
+use chrono::NaiveDate;
+use std::fs;
+use std::process::exit;
+
+use crate::commands::day_summaries::{aggregate_date_range, DateRangeSummary};
+use crate::commands::working_time::find_working_time_violations;
+use crate::units::working_time_limits::WorkingTimeViolation;
+use crate::user_interaction::date_expressions::{join_date_words, parse_date_range, DateContext};
+use crate::user_interaction::html_report::{render_html_report, ReportPolicies};
+use crate::utils::config::{get_config, Config};
+
+struct ReportArgs {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    output_path: Option<String>,
+}
+
+pub fn report(other_args: Vec<String>) {
+    let result: Result<(), String> = match other_args.first().map(|x| x.as_str()) {
+        Some("html") => html_report(&other_args[1..]),
+        _ => Err("'punch report' takes 'html <start> [end] [-o <file>]'.".to_string()),
+    };
+    if let Err(err_msg) = result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn html_report(args: &[String]) -> Result<(), String> {
+    let report_args: ReportArgs = parse_args_for_report(args)?;
+    let date_range_summary: DateRangeSummary =
+        aggregate_date_range(report_args.start_date, report_args.end_date, None)?;
+    let config: Config = get_config();
+    let working_time_violations: Option<Vec<WorkingTimeViolation>> =
+        match config.working_time_limits() {
+            Some(limits) => Some(find_working_time_violations(
+                limits,
+                report_args.start_date,
+                report_args.end_date,
+                config.week_start_or_default(),
+            )?),
+            None => None,
+        };
+    let html_str: String = render_html_report(
+        report_args.start_date,
+        report_args.end_date,
+        &date_range_summary.days_in_range,
+        &date_range_summary.aggregated,
+        false,
+        config.show_times_in_hours_or_default(),
+        &ReportPolicies {
+            break_policy: config.break_policy(),
+            working_time: config
+                .working_time_limits()
+                .zip(working_time_violations.as_deref()),
+        },
+    );
+    // Reports are meant to be shared, so they're written out in plain text even when the data is encrypted.
+    match report_args.output_path {
+        Some(output_path) => {
+            fs::write(&output_path, html_str)
+                .map_err(|err| format!("Couldn't write report to '{}': {}", output_path, err))?;
+            println!("Report written to '{}'.", output_path);
+        }
+        None => print!("{}", html_str),
+    }
+    return Ok(());
+}
+
+fn parse_args_for_report(args: &[String]) -> Result<ReportArgs, String> {
+    let mut date_exprs: Vec<String> = Vec::new();
+    let mut output_path: Option<String> = None;
+    let args: Vec<String> = join_date_words(args.to_vec());
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args_iter.next() {
+                Some(path) => output_path = Some(path.clone()),
+                None => return Err(format!("'{}' needs a file to write to.", arg)),
+            },
+            date_expr => date_exprs.push(date_expr.to_string()),
+        }
+    }
+    if (date_exprs.len() == 0) || (date_exprs.len() > 2) {
+        return Err("'punch report html' takes a start date and an optional end date.".to_string());
+    }
+    let (start_date, end_date) = parse_date_range(
+        &date_exprs[0],
+        date_exprs.get(1).map(|x| x.as_str()),
+        &DateContext::from_config(),
+    )?;
+    return Ok(ReportArgs {
+        start_date: start_date,
+        end_date: end_date,
+        output_path: output_path,
+    });
+}
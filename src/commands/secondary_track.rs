@@ -0,0 +1,55 @@
+use chrono::prelude::{DateTime, Local};
+use std::process::exit;
+
+use crate::units::day::{write_day, Day};
+
+pub fn track(now: &DateTime<Local>, mut day: Day, other_args: Vec<String>) {
+    match other_args.first().map(|x| x.as_str()) {
+        None => match day.get_current_secondary_task() {
+            Some(task_name) => println!("On the secondary track: '{}'.", task_name),
+            None => println!("Nothing is running on the secondary track."),
+        },
+        Some("start") => {
+            if other_args.len() != 2 {
+                eprintln!("'punch track start' takes a single task name argument.");
+                exit(1);
+            }
+            let task_name: String = other_args[1].clone();
+            if let Err(err_msg) = day.start_secondary_block(task_name.clone(), now) {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+            write_day(&day);
+            println!(
+                "Now tracking '{}' on the secondary track from '{}'",
+                task_name, now
+            );
+        }
+        Some("stop") => {
+            if other_args.len() > 1 {
+                eprintln!("'punch track stop' doesn't take any more arguments.");
+                exit(1);
+            }
+            match day.stop_secondary_block_at(now) {
+                Ok(task_name) => {
+                    write_day(&day);
+                    println!(
+                        "Stopped '{}' on the secondary track at '{}'",
+                        task_name, now
+                    );
+                }
+                Err(err_msg) => {
+                    eprintln!("{}", err_msg);
+                    exit(1);
+                }
+            }
+        }
+        Some(other) => {
+            eprintln!(
+                "'{}' isn't a track command. Use 'punch track start <task>' or 'punch track stop'.",
+                other
+            );
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde_json::{json, Value};
+use std::any::Any;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::process::exit;
+use std::time::Duration;
+
+use crate::commands::core::{
+    add_note, end_day, get_default_day_in_minutes, resume_work, return_to_day, start_break,
+    start_day, start_task,
+};
+use crate::commands::day_summaries::{aggregate_date_range, DateRangeSummary};
+use crate::commands::stale_days::close_stale_day_if_needed;
+use crate::units::day::{get_current_day, read_day_from_date_str, Day};
+use crate::user_interaction::date_expressions::{parse_date, parse_date_range, DateContext};
+use crate::utils::config::get_config;
+use crate::utils::dates_and_times::convert_date_to_date_str;
+use crate::utils::encryption::derive_key_for_unattended_use;
+use crate::utils::event_hooks::run_queued_event_hooks;
+use crate::utils::fatal::unwind_on_error;
+use crate::utils::history::{describe_command, set_command_description};
+use crate::utils::http::{read_request, HttpRequest, HttpResponse};
+use crate::utils::journal::start_journal_entry;
+use crate::utils::lock::{lock_data_within, DataLock};
+use crate::utils::profiles::{get_data_dir, with_data_dir};
+
+const LISTEN_FLAG: &str = "--listen";
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:7421";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const ALLOWED_HOST_NAMES: [&str; 3] = ["localhost", "127.0.0.1", "[::1]"];
+
+pub fn serve(other_args: Vec<String>) {
+    let address: SocketAddr = match parse_args_for_serve(other_args) {
+        Ok(address) => address,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
+    // Anything that would need a prompt or stop punch has to be sorted out before serving requests.
+    let startup_result: Result<(), String> =
+        derive_key_for_unattended_use().and(get_config().get_week_start().map(|_| ()));
+    if let Err(err_msg) = startup_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    unwind_on_error();
+    let listener: TcpListener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Couldn't listen on '{}': {}", address, err);
+            exit(1);
+        }
+    };
+    let data_dir: String = get_data_dir();
+    println!("Serving the punch API on http://{}", address);
+    // Requests are handled one at a time, as each one holds the data lock anyway.
+    for stream_result in listener.incoming() {
+        match stream_result {
+            Ok(stream) => handle_connection(stream, &data_dir),
+            Err(err) => eprintln!("Couldn't accept a connection: {}", err),
+        }
+    }
+}
+
+fn parse_args_for_serve(other_args: Vec<String>) -> Result<SocketAddr, String> {
+    let address_str: &str = match other_args
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<&str>>()
+        .as_slice()
+    {
+        [] => DEFAULT_LISTEN_ADDRESS,
+        [LISTEN_FLAG, address_str] => address_str,
+        _ => {
+            return Err(format!(
+                "'punch serve' only takes an optional '{} 127.0.0.1:PORT'.",
+                LISTEN_FLAG
+            ))
+        }
+    };
+    let address: SocketAddr = address_str
+        .parse()
+        .map_err(|_| format!("'{}' isn't an address like 127.0.0.1:PORT.", address_str))?;
+    // There's no authentication, so the API is only for programs on this machine.
+    if !address.ip().is_loopback() {
+        return Err(format!(
+            "'punch serve' only listens on this machine (e.g. 127.0.0.1), not '{}'.",
+            address.ip()
+        ));
+    }
+    return Ok(address);
+}
+
+fn handle_connection(stream: TcpStream, data_dir: &str) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response: HttpResponse = match read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => handle_request(&request, data_dir),
+        Err(err_msg) => HttpResponse::error(400, &err_msg),
+    };
+    if let Err(err_msg) = response.write_to(&mut &stream) {
+        eprintln!("{}", err_msg);
+    }
+}
+
+fn handle_request(request: &HttpRequest, data_dir: &str) -> HttpResponse {
+    if let Err(response) = check_request_is_local(request) {
+        return response;
+    }
+    return with_data_dir(data_dir, || {
+        // The lock is held for the whole request, so CLI commands run before or after it.
+        let lock: DataLock = match lock_data_within(LOCK_TIMEOUT) {
+            Ok(lock) => lock,
+            Err(err_msg) => return HttpResponse::error(503, &err_msg),
+        };
+        let now: DateTime<Local> = Local::now();
+        // A request that fails part way (e.g. on data that can't be read) mustn't take the server down.
+        let response: HttpResponse = match catch_unwind(AssertUnwindSafe(|| route(request, &now))) {
+            Ok(Ok(body)) => HttpResponse::ok(body),
+            Ok(Err(response)) => response,
+            Err(panic) => HttpResponse::error(500, &describe_panic(panic.as_ref())),
+        };
+        drop(lock);
+        run_queued_event_hooks();
+        return response;
+    });
+}
+
+/// Turns away requests a web page could make from the browser. Pages on another host name
+/// (including one pointed at 127.0.0.1) are refused, and a JSON body can't be sent cross-origin
+/// without the browser asking first, which this server never allows.
+fn check_request_is_local(request: &HttpRequest) -> Result<(), HttpResponse> {
+    let host_name: Option<&str> = request.get_host_name();
+    if !host_name.is_some_and(|x| ALLOWED_HOST_NAMES.contains(&x.to_lowercase().as_str())) {
+        return Err(HttpResponse::error(
+            403,
+            "Requests need a Host of localhost or 127.0.0.1.",
+        ));
+    }
+    if (request.method == "POST") && !request.has_json_content_type() {
+        return Err(HttpResponse::error(
+            415,
+            "POST requests need 'Content-Type: application/json'.",
+        ));
+    }
+    return Ok(());
+}
+
+fn describe_panic(panic: &(dyn Any + Send)) -> String {
+    return match (panic.downcast_ref::<String>(), panic.downcast_ref::<&str>()) {
+        (Some(err_msg), _) => err_msg.clone(),
+        (_, Some(err_msg)) => err_msg.to_string(),
+        _ => "Something went wrong handling the request.".to_string(),
+    };
+}
+
+fn route(request: &HttpRequest, now: &DateTime<Local>) -> Result<Value, HttpResponse> {
+    let segments: Vec<&str> = request.get_path_segments();
+    return match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["status"]) => Ok(get_status(now)),
+        ("GET", ["days", date_expr]) => get_day(date_expr),
+        ("GET", ["summary"]) => get_summary(request),
+        ("POST", [command]) if is_state_change(command) => {
+            let body: Value = request.get_json_body().map_err(bad_request)?;
+            change_state(command, &body, now)
+        }
+        (_, ["status"]) | (_, ["days", _]) | (_, ["summary"]) => {
+            Err(HttpResponse::error(405, "Use GET for this endpoint."))
+        }
+        (_, [command]) if is_state_change(command) => {
+            Err(HttpResponse::error(405, "Use POST for this endpoint."))
+        }
+        _ => Err(HttpResponse::error(
+            404,
+            &format!("There's no endpoint at '{}'.", request.path),
+        )),
+    };
+}
+
+fn bad_request(err_msg: String) -> HttpResponse {
+    return HttpResponse::error(400, &err_msg);
+}
+
+fn conflict(err_msg: String) -> HttpResponse {
+    return HttpResponse::error(409, &err_msg);
+}
+
+fn is_state_change(command: &str) -> bool {
+    return ["in", "out", "pause", "resume", "task", "back-in", "note"].contains(&command);
+}
+
+/// An optional string field of a request body, e.g. the `task` in `{"task": "review"}`.
+fn get_string_field(body: &Value, field: &str) -> Result<Option<String>, HttpResponse> {
+    return match body.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(bad_request(format!("'{}' should be a string.", field))),
+    };
+}
+
+fn change_state(command: &str, body: &Value, now: &DateTime<Local>) -> Result<Value, HttpResponse> {
+    let field: &str = match command {
+        "pause" => "name",
+        "note" => "message",
+        _ => "task",
+    };
+    let arg_opt: Option<String> = get_string_field(body, field)?;
+    let time_to_do_done: bool = match body.get("time_to_do_done") {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(done)) => *done,
+        Some(_) => {
+            return Err(bad_request(
+                "'time_to_do_done' should be true or false.".to_string(),
+            ))
+        }
+    };
+
+    // Each request is its own entry in the journal and history, just like a CLI command.
+    let command_args: Vec<String> = [Some(command.to_string()), arg_opt.clone()]
+        .into_iter()
+        .flatten()
+        .collect();
+    let description: String = describe_command(&command_args, &now.format("%H:%M").to_string());
+    start_journal_entry(description.clone(), now);
+    set_command_description(description);
+
+    // Closing a stale day would need a prompt without a cutoff in the config.
+    if get_config().close_stale_days_at().is_some() {
+        close_stale_day_if_needed(now);
+    }
+    let day: Day = if command == "in" {
+        start_day(now, arg_opt, get_default_day_in_minutes()).map_err(conflict)?
+    } else {
+        let current_day: Day = get_current_day(now).map_err(conflict)?;
+        match command {
+            "out" => end_day(now, current_day, time_to_do_done).map_err(conflict)?,
+            "pause" => start_break(now, current_day, arg_opt).map_err(conflict)?,
+            "resume" => resume_work(now, current_day, arg_opt).map_err(conflict)?,
+            "back-in" => return_to_day(now, current_day, arg_opt).map_err(conflict)?,
+            "task" => match arg_opt {
+                Some(task) => start_task(now, current_day, task).map_err(conflict)?,
+                None => return Err(bad_request("'task' needs a task name.".to_string())),
+            },
+            "note" => match arg_opt {
+                Some(msg) => add_note(now, current_day, &msg),
+                None => return Err(bad_request("'note' needs a message.".to_string())),
+            },
+            _ => unreachable!("'{}' should have been routed elsewhere.", command),
+        }
+    };
+    return Ok(render_status(&day, now));
+}
+
+fn get_status(now: &DateTime<Local>) -> Value {
+    return match get_current_day(now) {
+        Ok(day) => render_status(&day, now),
+        Err(_) => json!({ "punched_in": false }),
+    };
+}
+
+fn render_status(day: &Day, now: &DateTime<Local>) -> Value {
+    let mut day_so_far: Day = day.clone();
+    if !day_so_far.has_ended() {
+        day_so_far
+            .end_day_at(now, false)
+            .expect("We should be able to end the day");
+    }
+    return json!({
+        "punched_in": !day.has_ended(),
+        "on_break": day.on_break,
+        "task": day.get_latest_task_name(),
+        "date": convert_date_to_date_str(day.get_date()),
+        "day_start": day.get_day_start_as_str(),
+        "day_end": day.get_day_end_as_str(),
+        "time_to_do_secs": day.get_time_to_do_secs(),
+        "time_done_secs": day_so_far.get_time_done_secs(),
+        "time_left_secs": day_so_far.get_time_left_secs(),
+        "time_behind_overall_secs": get_config().get_seconds_behind(),
+    });
+}
+
+fn get_day(date_expr: &str) -> Result<Value, HttpResponse> {
+    let date: NaiveDate =
+        parse_date(date_expr, &DateContext::from_config()).map_err(bad_request)?;
+    let date_str: String = convert_date_to_date_str(date);
+    return match read_day_from_date_str(&date_str) {
+        Ok(day) => Ok(json!({ "date": date_str, "day": day })),
+        Err(_) => Err(HttpResponse::error(
+            404,
+            &format!("'{}' does not have a day associated with it!", date_str),
+        )),
+    };
+}
+
+fn get_summary(request: &HttpRequest) -> Result<Value, HttpResponse> {
+    let start_expr: &String = request.query.get("start").ok_or(bad_request(
+        "A summary needs a 'start' date, e.g. ?start=this-week".to_string(),
+    ))?;
+    let end_expr: Option<&str> = request.query.get("end").map(|x| x.as_str());
+    let (start_date, end_date) =
+        parse_date_range(start_expr, end_expr, &DateContext::from_config()).map_err(bad_request)?;
+    let summary: DateRangeSummary =
+        aggregate_date_range(start_date, end_date, None).map_err(conflict)?;
+    let tasks: Vec<Value> = summary
+        .aggregated
+        .get_task_rows()
+        .iter()
+        .map(|x| {
+            json!({
+                "task": x.task_name,
+                "secs": x.secs,
+                "blocks": x.blocks,
+                "is_break": x.is_break,
+            })
+        })
+        .collect();
+    let secondary_tasks: Vec<Value> = summary
+        .aggregated
+        .get_secondary_task_rows()
+        .iter()
+        .map(|x| json!({ "task": x.task_name, "secs": x.secs, "blocks": x.blocks }))
+        .collect();
+    return Ok(json!({
+        "start": convert_date_to_date_str(start_date),
+        "end": convert_date_to_date_str(end_date),
+        "days_included": summary.days_in_range.get_dates_included(),
+        "days_not_there": summary.days_in_range.days_not_there,
+        "days_not_ended": summary.days_in_range.days_not_ended,
+        "time_done_secs": summary.aggregated.get_total_time_done(),
+        "time_behind_over_period_secs": summary.aggregated.get_time_behind_over_period(),
+        "average_day_length_secs": summary.aggregated.get_average_day_length_secs(),
+        "tasks": tasks,
+        "secondary_tasks": secondary_tasks,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use std::env;
+    use std::panic::resume_unwind;
+    use std::process;
+
+    use crate::units::day::{create_daily_dir_if_not_exists, get_day_file_path_from_date_str};
+    use crate::utils::config::create_default_config_if_not_exists;
+
+    /// Runs `test` with its own data dir, which it's given for calls to `handle_request`.
+    fn with_temp_data_dir(name: &str, test: impl FnOnce(&str)) {
+        let dir = env::temp_dir().join(format!("punch-serve-{}-{}", name, process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data_dir: String = dir.to_string_lossy().to_string() + "/";
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            with_data_dir(&data_dir, || {
+                create_daily_dir_if_not_exists();
+                create_default_config_if_not_exists();
+                test(&data_dir);
+            })
+        }));
+        std::fs::remove_dir_all(&dir).unwrap();
+        if let Err(panic) = result {
+            resume_unwind(panic);
+        }
+    }
+
+    fn request_with_headers(method: &str, target: &str, headers: &str, body: &str) -> HttpRequest {
+        let raw: String = format!(
+            "{} {} HTTP/1.1\r\n{}Content-Length: {}\r\n\r\n{}",
+            method,
+            target,
+            headers,
+            body.len(),
+            body
+        );
+        return read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+    }
+
+    fn request(method: &str, target: &str, body: &str) -> HttpRequest {
+        return request_with_headers(
+            method,
+            target,
+            "Host: 127.0.0.1:7421\r\nContent-Type: application/json\r\n",
+            body,
+        );
+    }
+
+    fn get_status_code(result: Result<Value, HttpResponse>) -> u16 {
+        return match result {
+            Ok(_) => 200,
+            Err(response) => response.status,
+        };
+    }
+
+    #[test]
+    fn test_state_changes() {
+        with_temp_data_dir("state", |_| {
+            let start: DateTime<Local> = Local::now() - ChronoDuration::hours(3);
+            let at = |hours: i64| start + ChronoDuration::hours(hours);
+
+            let status: Value = route(&request("GET", "/status", ""), &start).unwrap();
+            assert_eq!(status["punched_in"], false);
+            let status: Value =
+                route(&request("POST", "/in", "{\"task\": \"coding\"}"), &start).unwrap();
+            assert_eq!(status["punched_in"], true);
+            assert_eq!(status["task"], "coding");
+            assert_eq!(
+                get_status_code(route(&request("POST", "/in", ""), &start)),
+                409
+            );
+
+            let status: Value =
+                route(&request("POST", "/task", "{\"task\": \"review\"}"), &at(1)).unwrap();
+            assert_eq!(status["task"], "review");
+            let status: Value =
+                route(&request("POST", "/pause", "{\"name\": \"Lunch\"}"), &at(2)).unwrap();
+            assert_eq!(status["on_break"], true);
+            route(&request("POST", "/resume", ""), &at(2)).unwrap();
+            route(
+                &request("POST", "/note", "{\"message\": \"shipped\"}"),
+                &at(2),
+            )
+            .unwrap();
+
+            let status: Value = route(&request("POST", "/out", ""), &at(3)).unwrap();
+            assert_eq!(status["punched_in"], false);
+            assert_eq!(status["time_done_secs"], 3 * 3600);
+            assert_eq!(
+                get_status_code(route(&request("POST", "/out", ""), &at(3))),
+                409
+            );
+        });
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        with_temp_data_dir("invalid", |_| {
+            let now: DateTime<Local> = Local::now();
+            let status_of = |method: &str, target: &str, body: &str| {
+                get_status_code(route(&request(method, target, body), &now))
+            };
+            assert_eq!(status_of("GET", "/nothing", ""), 404);
+            assert_eq!(status_of("POST", "/status", ""), 405);
+            assert_eq!(status_of("GET", "/in", ""), 405);
+            assert_eq!(status_of("POST", "/in", "[]"), 400);
+            assert_eq!(status_of("POST", "/in", "{\"task\": 3}"), 400);
+            assert_eq!(status_of("POST", "/task", ""), 409);
+            route(&request("POST", "/in", ""), &now).unwrap();
+            assert_eq!(status_of("POST", "/task", ""), 400);
+            assert_eq!(status_of("GET", "/days/not-a-date", ""), 400);
+            assert_eq!(status_of("GET", "/summary", ""), 400);
+        });
+    }
+
+    #[test]
+    fn test_get_day_and_summary() {
+        with_temp_data_dir("summary", |_| {
+            let start: DateTime<Local> = Local::now() - ChronoDuration::hours(2);
+            let date_str: String = convert_date_to_date_str(start.date_naive());
+            route(&request("POST", "/in", "{\"task\": \"coding\"}"), &start).unwrap();
+            route(
+                &request("POST", "/out", ""),
+                &(start + ChronoDuration::hours(2)),
+            )
+            .unwrap();
+
+            let day: Value =
+                route(&request("GET", &format!("/days/{}", date_str), ""), &start).unwrap();
+            assert_eq!(day["date"], date_str);
+            assert_eq!(day["day"]["timeblocks"][0]["task_name"], "coding");
+
+            let summary: Value = route(
+                &request("GET", &format!("/summary?start={}", date_str), ""),
+                &start,
+            )
+            .unwrap();
+            assert_eq!(summary["days_included"], json!([date_str]));
+            assert_eq!(summary["time_done_secs"], 2 * 3600);
+            assert_eq!(summary["tasks"][0]["task"], "coding");
+            assert_eq!(summary["tasks"][0]["secs"], 2 * 3600);
+        });
+    }
+
+    #[test]
+    fn test_unreadable_data_gives_a_server_error() {
+        with_temp_data_dir("unreadable", |data_dir| {
+            let now: DateTime<Local> = Local::now();
+            let date_str: String = convert_date_to_date_str(now.date_naive());
+            std::fs::write(get_day_file_path_from_date_str(&date_str), "not: [a day").unwrap();
+            let day_request: HttpRequest = request("GET", &format!("/days/{}", date_str), "");
+            assert_eq!(handle_request(&day_request, data_dir).status, 500);
+
+            // Data that can't be decrypted would stop a CLI command, but only fails the request here.
+            unwind_on_error();
+            std::fs::write(
+                get_day_file_path_from_date_str(&date_str),
+                "punch-encrypted-v1\n00\n",
+            )
+            .unwrap();
+            let response: HttpResponse = handle_request(&day_request, data_dir);
+            assert_eq!(response.status, 500);
+            assert!(response.body["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Couldn't read"));
+            assert_eq!(
+                handle_request(&request("GET", "/status", ""), data_dir).status,
+                500
+            );
+        });
+    }
+
+    #[test]
+    fn test_requests_a_web_page_could_make_are_refused() {
+        with_temp_data_dir("cross-origin", |data_dir| {
+            let status_of = |headers: &str| {
+                let in_request: HttpRequest =
+                    request_with_headers("POST", "/in", headers, "{\"task\": \"coding\"}");
+                return handle_request(&in_request, data_dir).status;
+            };
+            assert_eq!(status_of("Content-Type: application/json\r\n"), 403);
+            assert_eq!(
+                status_of("Host: attacker.example:7421\r\nContent-Type: application/json\r\n"),
+                403
+            );
+            assert_eq!(status_of("Host: localhost:7421\r\n"), 415);
+            assert_eq!(
+                status_of("Host: localhost:7421\r\nContent-Type: text/plain\r\n"),
+                415
+            );
+            let status: Value = route(&request("GET", "/status", ""), &Local::now()).unwrap();
+            assert_eq!(status["punched_in"], false);
+
+            assert_eq!(
+                status_of("Host: LOCALHOST:7421\r\nContent-Type: application/json\r\n"),
+                200
+            );
+        });
+    }
+}
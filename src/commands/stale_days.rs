@@ -0,0 +1,122 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime};
+
+use crate::commands::core::update_time_behind;
+use crate::units::day::{get_latest_day, write_day, Day};
+use crate::user_interaction::convert_input::convert_input_to_naive_datetime;
+use crate::user_interaction::prompt::{can_prompt, prompt_for_input};
+use crate::utils::config::{get_config, Config};
+use crate::utils::fatal::exit_with_error;
+use crate::utils::file_io::ToFile;
+
+pub fn close_stale_day_if_needed(now: &DateTime<Local>) {
+    let config: Config = get_config();
+    let day: Day = match get_latest_day() {
+        Some(day) if !day.has_ended() => day,
+        _ => return,
+    };
+    let day_start: DateTime<Local> = day.get_day_start().as_dt();
+    let stale_after: Duration = Duration::hours(config.stale_day_hours_or_default() as i64);
+    if *now - day_start <= stale_after {
+        return;
+    }
+    let latest_block_start: DateTime<Local> = day.get_latest_block_start().as_dt();
+
+    let close_at_result: Result<Option<DateTime<Local>>, String> = match config
+        .close_stale_days_at()
+    {
+        Some(cutoff) => {
+            get_cutoff_for_stale_day(cutoff, &day_start, &latest_block_start, now).map(Some)
+        }
+        None if can_prompt() => prompt_for_close_time(&day_start, &latest_block_start, now),
+        None => {
+            // Scripts can't answer the prompt, so the day is left open for later.
+            eprintln!(
+                    "The day started at '{}' is still open. Run punch in a terminal to close it, or set 'close_stale_days_at' in the config.",
+                    day_start.format("%d/%m/%Y %H:%M:%S")
+                );
+            Ok(None)
+        }
+    };
+    let close_at: DateTime<Local> = match close_at_result {
+        Ok(Some(close_at)) => close_at,
+        Ok(None) => return,
+        Err(err_msg) => exit_with_error(&err_msg),
+    };
+    // The prompt doesn't hold the lock, so another command may have closed the day meanwhile.
+    if day.read_stored_string() != Some(day.as_string()) {
+        eprintln!(
+            "The day was changed by another punch command in the meantime, so it wasn't closed."
+        );
+        return;
+    }
+    close_stale_day(day, &close_at);
+}
+
+fn get_cutoff_for_stale_day(
+    cutoff: &str,
+    day_start: &DateTime<Local>,
+    latest_block_start: &DateTime<Local>,
+    now: &DateTime<Local>,
+) -> Result<DateTime<Local>, String> {
+    // The cutoff is a time of day: use its first occurrence after the day started.
+    let start_date: NaiveDate = day_start.date_naive();
+    let mut close_at: DateTime<Local> = convert_to_local(
+        convert_input_to_naive_datetime(cutoff, start_date)
+            .map_err(|x| format!("Invalid 'close_stale_days_at' in the config: {}", x))?,
+    )?;
+    if close_at <= *day_start {
+        close_at += Duration::days(1);
+    }
+    return Ok(close_at.max(*latest_block_start).min(*now));
+}
+
+fn prompt_for_close_time(
+    day_start: &DateTime<Local>,
+    latest_block_start: &DateTime<Local>,
+    now: &DateTime<Local>,
+) -> Result<Option<DateTime<Local>>, String> {
+    println!(
+        "The day started at '{}' is still open. The latest block started at '{}'.",
+        day_start.format("%d/%m/%Y %H:%M:%S"),
+        latest_block_start.format("%d/%m/%Y %H:%M:%S")
+    );
+    let input: String = prompt_for_input(
+        "Enter a time to punch out at ('HH:MM' or 'YYYY-mm-dd HH:MM'), or leave it empty to keep the day open:",
+    );
+    if input.len() == 0 {
+        return Ok(None);
+    }
+    let close_at: DateTime<Local> = convert_to_local(convert_input_to_naive_datetime(
+        &input,
+        latest_block_start.date_naive(),
+    )?)?;
+    if (close_at < *latest_block_start) || (close_at > *now) {
+        return Err(
+            "Can't punch out before the latest block started or after the current time!"
+                .to_string(),
+        );
+    }
+    return Ok(Some(close_at));
+}
+
+fn convert_to_local(naive: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    return naive
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or(format!("'{}' doesn't exist in the local timezone!", naive));
+}
+
+fn close_stale_day(mut day: Day, close_at: &DateTime<Local>) {
+    if let Err(err_msg) = day.end_day_at(close_at, false) {
+        exit_with_error(&err_msg);
+    }
+    write_day(&day);
+    println!(
+        "Punched out of the day started at '{}' at '{}'",
+        day.get_day_start().0.format("%d/%m/%Y %H:%M:%S"),
+        close_at.format("%d/%m/%Y %H:%M:%S")
+    );
+    if let Err(err_msg) = update_time_behind(day) {
+        exit_with_error(&err_msg);
+    }
+}
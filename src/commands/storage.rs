@@ -0,0 +1,82 @@
+use std::process::exit;
+
+use crate::storage::{get_storage_kind, open_storage, set_storage_kind, Storage, StorageKind};
+use crate::units::day::Day;
+use crate::utils::balance::BalanceLedger;
+use crate::utils::config::Config;
+
+pub fn storage(other_args: Vec<String>) {
+    if other_args.len() == 0 {
+        println!(
+            "Current storage backend: {}",
+            get_storage_kind().to_string()
+        );
+        return;
+    }
+    match other_args[0].as_str() {
+        "migrate" => migrate_storage(other_args[1..].to_vec()),
+        other => {
+            eprintln!(
+                "'{}' is not a valid subcommand for 'punch storage'. Try 'migrate'.",
+                other
+            );
+            exit(1);
+        }
+    }
+}
+
+fn migrate_storage(other_args: Vec<String>) {
+    let target_result: Result<StorageKind, String> = parse_args_for_migrate(other_args);
+    if let Err(err_msg) = target_result {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+    let target_kind: StorageKind = target_result.expect("Error already handled!");
+    let source_kind: StorageKind = get_storage_kind();
+    if source_kind == target_kind {
+        println!(
+            "Already using the {} storage backend.",
+            target_kind.to_string()
+        );
+        return;
+    }
+
+    let source: Box<dyn Storage> = open_storage(source_kind);
+    // Everything is read before anything is written, so a day that can't be read leaves the target untouched.
+    let mut days: Vec<Day> = Vec::new();
+    for date_str in source.list_day_date_strs().iter() {
+        match source.read_day_from_date_str(date_str) {
+            Ok(day) => days.push(day),
+            Err(err) => {
+                eprintln!(
+                    "Couldn't read '{}' so nothing was migrated: {}",
+                    date_str, err
+                );
+                exit(1);
+            }
+        }
+    }
+    let config: Config = source.read_config();
+    let balance_ledger: BalanceLedger = source.read_balance_ledger();
+    let target: Box<dyn Storage> = open_storage(target_kind);
+    for day in days.iter() {
+        target.write_day(day);
+    }
+    target.write_config(&config);
+    target.write_balance_ledger(&balance_ledger);
+    set_storage_kind(target_kind);
+    println!(
+        "Migrated {} days, the config and the balance from {} to {}. The {} data has been left in place.",
+        days.len(),
+        source_kind.to_string(),
+        target_kind.to_string(),
+        source_kind.to_string()
+    );
+}
+
+fn parse_args_for_migrate(other_args: Vec<String>) -> Result<StorageKind, String> {
+    if (other_args.len() != 2) || (other_args[0] != "--to") {
+        return Err("'punch storage migrate' takes '--to yaml' or '--to sqlite'.".to_string());
+    }
+    return StorageKind::from_string(&other_args[1]);
+}
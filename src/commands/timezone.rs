@@ -0,0 +1,49 @@
+use std::process::exit;
+
+use crate::utils::config::{get_config, update_config, Config};
+use crate::utils::dates_and_times::{
+    get_system_timezone_name, parse_timezone, set_working_timezone,
+};
+
+pub fn use_configured_timezone() {
+    if let Some(timezone) = get_config().timezone() {
+        if parse_timezone(timezone).is_ok() {
+            set_working_timezone(timezone);
+        } else {
+            eprintln!(
+                "Ignoring unknown timezone '{}' in the config. Use `punch tz` to fix it.",
+                timezone
+            );
+        }
+    }
+}
+
+pub fn timezone(other_args: Vec<String>) {
+    let mut config: Config = get_config();
+    let system_timezone: String = get_system_timezone_name().unwrap_or("unknown".to_string());
+    match other_args.len() {
+        0 => match config.timezone() {
+            Some(timezone) => println!("Working timezone: {}", timezone),
+            None => println!("Working timezone: {} (system)", system_timezone),
+        },
+        1 if (other_args[0] == "--reset") | (other_args[0] == "system") => {
+            config.set_timezone(None);
+            update_config(config);
+            println!("Now using the system timezone: {}", system_timezone);
+        }
+        1 => {
+            let timezone_name: &String = &other_args[0];
+            if let Err(err_msg) = parse_timezone(timezone_name) {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+            config.set_timezone(Some(timezone_name.to_owned()));
+            update_config(config);
+            println!("Now using the working timezone: {}", timezone_name);
+        }
+        _ => {
+            eprintln!("'punch tz' takes at most one argument: an IANA timezone or '--reset'.");
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::process::exit;
+
+use crate::utils::journal::{redo_next_entry, undo_last_entry};
+
+const FORCE_FLAG: &str = "--force";
+
+fn parse_force_flag(other_args: &[String], command: &str) -> bool {
+    return match other_args {
+        [] => false,
+        [flag] if flag == FORCE_FLAG => true,
+        _ => {
+            eprintln!(
+                "'punch {}' only takes an optional '{}'.",
+                command, FORCE_FLAG
+            );
+            exit(1);
+        }
+    };
+}
+
+pub fn undo(other_args: Vec<String>) {
+    let force: bool = parse_force_flag(&other_args, "undo");
+    match undo_last_entry(force) {
+        Ok(Some(entry)) => println!("Undid '{}'.", entry.get_command()),
+        Ok(None) => {
+            eprintln!("Nothing to undo.");
+            exit(1);
+        }
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+}
+
+pub fn redo(other_args: Vec<String>) {
+    let force: bool = parse_force_flag(&other_args, "redo");
+    match redo_next_entry(force) {
+        Ok(Some(entry)) => println!("Redid '{}'.", entry.get_command()),
+        Ok(None) => {
+            eprintln!("Nothing to redo.");
+            exit(1);
+        }
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, Weekday};
+use std::process::exit;
+
+use crate::storage::get_storage;
+use crate::units::day::Day;
+use crate::units::working_time_limits::{
+    render_working_time_violations, WorkingTimeLimits, WorkingTimeViolation,
+};
+use crate::user_interaction::date_expressions::{join_date_words, parse_date_range, DateContext};
+use crate::utils::config::{get_config, Config};
+use crate::utils::dates_and_times::get_week_range;
+
+pub fn check_limits(args: Vec<String>) {
+    if let Err(err_msg) = check_limits_in_range(join_date_words(args)) {
+        eprintln!("{}", err_msg);
+        exit(1);
+    }
+}
+
+fn check_limits_in_range(args: Vec<String>) -> Result<(), String> {
+    let config: Config = get_config();
+    let limits: &WorkingTimeLimits = config.working_time_limits().ok_or(
+        "There are no working time limits set. Add 'working_time_limits' to the config with 'punch edit-config' first."
+            .to_string(),
+    )?;
+    if args.len() > 2 {
+        return Err(
+            "'punch check-limits' takes a start date and an optional end date.".to_string(),
+        );
+    }
+    let context: DateContext = DateContext::from_config();
+    let (start_date, end_date): (NaiveDate, NaiveDate) = match args.first() {
+        Some(start_expr) => {
+            parse_date_range(start_expr, args.get(1).map(|x| x.as_str()), &context)?
+        }
+        None => (context.today, context.today),
+    };
+    let violations: Vec<WorkingTimeViolation> =
+        find_working_time_violations(limits, start_date, end_date, context.week_start)?;
+    println!(
+        "{}",
+        render_working_time_violations(
+            limits,
+            &violations,
+            config.show_times_in_hours_or_default()
+        )
+    );
+    return Ok(());
+}
+
+/// Checks the day files in a range against the limits.
+///
+/// Day files are used as they are, even when splitting days at midnight, since the limits are about
+/// each stretch from punching in to punching out. The day before the range and the rest of the first
+/// week are read too, for the rest period and weekly total going into the range.
+pub fn find_working_time_violations(
+    limits: &WorkingTimeLimits,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    week_start: Weekday,
+) -> Result<Vec<WorkingTimeViolation>, String> {
+    let (first_week_start, _) = get_week_range(start_date, week_start);
+    let first_date: NaiveDate = first_week_start.min(start_date - Duration::days(1));
+    let days: Vec<(NaiveDate, Day)> = get_storage().read_days_between(first_date, end_date);
+    return limits.check_days(&days, start_date, week_start);
+}
+
+/// Lists the working time violations in a range, if there are limits set.
+pub fn print_working_time_violations(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    show_times_in_hours: bool,
+) {
+    let config: Config = get_config();
+    if let Some(limits) = config.working_time_limits() {
+        match find_working_time_violations(
+            limits,
+            start_date,
+            end_date,
+            config.week_start_or_default(),
+        ) {
+            Ok(violations) => println!(
+                "\n{}",
+                render_working_time_violations(limits, &violations, show_times_in_hours)
+            ),
+            Err(err_msg) => {
+                eprintln!("{}", err_msg);
+                exit(1);
+            }
+        }
+    }
+}
+
+/// Warns, without stopping anything, when starting work now cuts the rest since punching out short.
+pub fn warn_if_rest_period_too_short(previous_end: &DateTime<Local>, now: &DateTime<Local>) {
+    let config: Config = get_config();
+    let violation_opt: Option<WorkingTimeViolation> = config
+        .working_time_limits()
+        .and_then(|x| x.check_rest(previous_end, now));
+    if let Some(violation) = violation_opt {
+        eprintln!(
+            "Warning: {}",
+            violation.render(config.show_times_in_hours_or_default())
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod commands;
 pub mod random_generators;
+pub mod storage;
 pub mod units;
 pub mod user_interaction;
 pub mod utils;
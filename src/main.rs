@@ -4,19 +4,47 @@ use std::process::exit;
 
 mod commands;
 mod random_generators;
+mod storage;
 mod units;
 mod user_interaction;
 mod utils;
+use crate::commands::balance::balance;
+use crate::commands::chart::chart;
+use crate::commands::check::check;
+use crate::commands::check_breaks::check_breaks;
+use crate::commands::compare::compare;
 use crate::commands::core::{
     add_note_to_today, add_summary_to_today, edit_config, edit_day, punch_back_in, punch_in,
     punch_out, resume, switch_to_new_task, take_break, update_current_task_name, view_config,
     view_day, view_past,
 };
 use crate::commands::daily_task_tables::{daily_tasks, week_in_tasks};
-use crate::commands::day_summaries::{summarise_days, summarise_week, summary, summary_past};
+use crate::commands::day_summaries::{
+    summarise, summarise_days, summarise_week, summary, summary_past,
+};
+use crate::commands::encryption::{decrypt_all, encrypt_all};
+use crate::commands::focus::focus;
+use crate::commands::history::{history, restore};
+use crate::commands::idle::{heartbeat, idle_fix};
+use crate::commands::invoice::invoice;
+use crate::commands::profile::profile;
+use crate::commands::report::report;
+use crate::commands::secondary_track::track;
+use crate::commands::serve::serve;
+use crate::commands::stale_days::close_stale_day_if_needed;
+use crate::commands::storage::storage;
+use crate::commands::timezone::{timezone, use_configured_timezone};
+use crate::commands::undo::{redo, undo};
+use crate::commands::working_time::check_limits;
 use crate::units::day::{create_daily_dir_if_not_exists, get_current_day, Day};
+use crate::utils::activity::create_heartbeat_dir_if_not_exists;
 use crate::utils::config::create_default_config_if_not_exists;
+use crate::utils::event_hooks::run_queued_event_hooks;
 use crate::utils::file_io::create_base_dir_if_not_exists;
+use crate::utils::history::{describe_command, set_command_description};
+use crate::utils::journal::start_journal_entry;
+use crate::utils::lock::{hold_data_lock, release_data_lock};
+use crate::utils::profiles::{profile_exists, set_active_profile, take_profile_arg};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -31,6 +59,7 @@ enum SubCommand {
     SummaryPast(Vec<String>),
     SummariseWeek(Vec<String>),
     SummariseDays(Vec<String>),
+    Summarise(Vec<String>),
     DailyTasks(Vec<String>),
     WeekInTasks(Vec<String>),
     View(Vec<String>),
@@ -42,6 +71,28 @@ enum SubCommand {
     ViewConfig(Vec<String>),
     AddSummary(Vec<String>),
     UpdateTask(Vec<String>),
+    Balance(Vec<String>),
+    Focus(Vec<String>),
+    Heartbeat(Vec<String>),
+    IdleFix(Vec<String>),
+    Timezone(Vec<String>),
+    Storage(Vec<String>),
+    History(Vec<String>),
+    Restore(Vec<String>),
+    Undo(Vec<String>),
+    Redo(Vec<String>),
+    EncryptAll(Vec<String>),
+    DecryptAll(Vec<String>),
+    Profile(Vec<String>),
+    Track(Vec<String>),
+    Invoice(Vec<String>),
+    Chart(Vec<String>),
+    Report(Vec<String>),
+    Compare(Vec<String>),
+    CheckBreaks(Vec<String>),
+    CheckLimits(Vec<String>),
+    Check(Vec<String>),
+    Serve(Vec<String>),
     Version(Vec<String>),
     Invalid(String),
 }
@@ -58,6 +109,7 @@ impl SubCommand {
             "summary-past" => Self::SummaryPast(other_args),
             "summarise-week" => Self::SummariseWeek(other_args),
             "summarise-days" => Self::SummariseDays(other_args),
+            "summarise" => Self::Summarise(other_args),
             "daily-tasks" => Self::DailyTasks(other_args),
             "week-in-tasks" => Self::WeekInTasks(other_args),
             "view" => Self::View(other_args),
@@ -69,6 +121,28 @@ impl SubCommand {
             "view-config" => Self::ViewConfig(other_args),
             "add-summary" => Self::AddSummary(other_args),
             "update-task" => Self::UpdateTask(other_args),
+            "balance" => Self::Balance(other_args),
+            "focus" => Self::Focus(other_args),
+            "heartbeat" => Self::Heartbeat(other_args),
+            "idle-fix" => Self::IdleFix(other_args),
+            "tz" => Self::Timezone(other_args),
+            "storage" => Self::Storage(other_args),
+            "history" => Self::History(other_args),
+            "restore" => Self::Restore(other_args),
+            "undo" => Self::Undo(other_args),
+            "redo" => Self::Redo(other_args),
+            "encrypt-all" => Self::EncryptAll(other_args),
+            "decrypt-all" => Self::DecryptAll(other_args),
+            "profile" => Self::Profile(other_args),
+            "track" => Self::Track(other_args),
+            "invoice" => Self::Invoice(other_args),
+            "chart" => Self::Chart(other_args),
+            "report" => Self::Report(other_args),
+            "compare" => Self::Compare(other_args),
+            "check-breaks" => Self::CheckBreaks(other_args),
+            "check-limits" => Self::CheckLimits(other_args),
+            "check" => Self::Check(other_args),
+            "serve" => Self::Serve(other_args),
             "version" | "-v" | "--version" => Self::Version(other_args),
             other => Self::Invalid(other.to_string()),
         };
@@ -85,6 +159,7 @@ impl SubCommand {
             Self::SummaryPast(_) => "summary-past",
             Self::SummariseWeek(_) => "summarise-week",
             Self::SummariseDays(_) => "summarise-days",
+            Self::Summarise(_) => "summarise",
             Self::DailyTasks(_) => "daily-tasks",
             Self::WeekInTasks(_) => "week-in-tasks",
             Self::View(_) => "view",
@@ -96,6 +171,28 @@ impl SubCommand {
             Self::ViewConfig(_) => "view-config",
             Self::AddSummary(_) => "add-summary",
             Self::UpdateTask(_) => "update-task",
+            Self::Balance(_) => "balance",
+            Self::Focus(_) => "focus",
+            Self::Heartbeat(_) => "heartbeat",
+            Self::IdleFix(_) => "idle-fix",
+            Self::Timezone(_) => "tz",
+            Self::Storage(_) => "storage",
+            Self::History(_) => "history",
+            Self::Restore(_) => "restore",
+            Self::Undo(_) => "undo",
+            Self::Redo(_) => "redo",
+            Self::EncryptAll(_) => "encrypt-all",
+            Self::DecryptAll(_) => "decrypt-all",
+            Self::Profile(_) => "profile",
+            Self::Track(_) => "track",
+            Self::Invoice(_) => "invoice",
+            Self::Chart(_) => "chart",
+            Self::Report(_) => "report",
+            Self::Compare(_) => "compare",
+            Self::CheckBreaks(_) => "check-breaks",
+            Self::CheckLimits(_) => "check-limits",
+            Self::Check(_) => "check",
+            Self::Serve(_) => "serve",
             Self::Version(_) => "version",
             Self::Invalid(_) => "invalid",
         }
@@ -114,6 +211,7 @@ impl SubCommand {
                 "summary-past",
                 "summarise-week",
                 "summarise-days",
+                "summarise",
                 "daily-tasks",
                 "week-in-tasks",
                 "view",
@@ -124,6 +222,28 @@ impl SubCommand {
                 "edit-config",
                 "add-summary",
                 "update-task",
+                "balance",
+                "focus",
+                "heartbeat",
+                "idle-fix",
+                "tz",
+                "storage",
+                "history",
+                "restore",
+                "undo",
+                "redo",
+                "encrypt-all",
+                "decrypt-all",
+                "profile",
+                "track",
+                "invoice",
+                "chart",
+                "report",
+                "compare",
+                "check-breaks",
+                "check-limits",
+                "check",
+                "serve",
                 "version",
                 "-v",
                 "--version",
@@ -134,7 +254,24 @@ impl SubCommand {
 }
 
 fn main() {
-    let env_args: Vec<String> = args().collect();
+    let env_args: Vec<String> = match take_profile_arg(args().collect()) {
+        Ok((Some(profile_name), remaining_args)) => {
+            if !profile_exists(&profile_name) {
+                eprintln!(
+                    "The profile '{}' doesn't exist. Create it with 'punch profile create {}'.",
+                    profile_name, profile_name
+                );
+                exit(1);
+            }
+            set_active_profile(&profile_name);
+            remaining_args
+        }
+        Ok((None, remaining_args)) => remaining_args,
+        Err(err_msg) => {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    };
     let command_name: &String;
 
     if let Some(name) = env_args.get(1) {
@@ -146,16 +283,40 @@ fn main() {
     let other_args: Vec<String> = env_args[2..].to_vec();
     let command: SubCommand = SubCommand::from_string(command_name, other_args);
 
+    // Heartbeats come in very often and only append to their own file, so they skip the setup,
+    // the lock and the journal.
+    if let SubCommand::Heartbeat(_) = command {
+        heartbeat(&Local::now());
+        return;
+    }
+
     setup();
+    // Held while the command reads and writes, so it never sees another command's half-done changes.
+    // It's let go while waiting on the user or a timer, and `punch serve` locks each request instead.
+    if !matches!(command, SubCommand::Serve(_)) {
+        if let Err(err_msg) = hold_data_lock() {
+            eprintln!("{}", err_msg);
+            exit(1);
+        }
+    }
 
     let now: DateTime<Local> = Local::now();
+    let description: String = describe_command(&env_args[1..], &now.format("%H:%M").to_string());
+    if !matches!(command, SubCommand::Undo(_) | SubCommand::Redo(_)) {
+        start_journal_entry(description.clone(), &now);
+    }
+    set_command_description(description);
     run_command(command, now);
+    release_data_lock();
+    run_queued_event_hooks();
 }
 
 fn setup() {
     create_base_dir_if_not_exists();
     create_daily_dir_if_not_exists();
     create_default_config_if_not_exists();
+    create_heartbeat_dir_if_not_exists();
+    use_configured_timezone();
 }
 
 fn run_command(command: SubCommand, now: DateTime<Local>) {
@@ -163,21 +324,44 @@ fn run_command(command: SubCommand, now: DateTime<Local>) {
     match command.clone() {
         SubCommand::Version(_) => println!("Current punch-card version: {}", VERSION),
         SubCommand::Invalid(original) => handle_invalid_cmd(&original),
-        SubCommand::In(other_args) => punch_in(&now, other_args),
-        SubCommand::ViewPast(other_args) => view_past(other_args),
+        SubCommand::In(other_args) => {
+            close_stale_day_if_needed(&now);
+            punch_in(&now, other_args)
+        }
+        SubCommand::ViewPast(other_args) => view_past(&now, other_args),
         SubCommand::SummaryPast(other_args) => summary_past(other_args),
         SubCommand::EditConfig(_) => edit_config(),
         SubCommand::ViewConfig(_) => view_config(),
         SubCommand::SummariseWeek(other_args) => summarise_week(other_args),
         SubCommand::SummariseDays(other_args) => summarise_days(other_args),
+        SubCommand::Summarise(other_args) => summarise(other_args),
         SubCommand::DailyTasks(other_args) => daily_tasks(other_args),
         SubCommand::WeekInTasks(other_args) => week_in_tasks(other_args),
+        SubCommand::Balance(other_args) => balance(&now, other_args),
+        SubCommand::Timezone(other_args) => timezone(other_args),
+        SubCommand::Storage(other_args) => storage(other_args),
+        SubCommand::History(other_args) => history(other_args),
+        SubCommand::Restore(other_args) => restore(other_args),
+        SubCommand::Undo(other_args) => undo(other_args),
+        SubCommand::Redo(other_args) => redo(other_args),
+        SubCommand::EncryptAll(other_args) => encrypt_all(other_args),
+        SubCommand::DecryptAll(other_args) => decrypt_all(other_args),
+        SubCommand::Profile(other_args) => profile(other_args),
+        SubCommand::Invoice(other_args) => invoice(other_args),
+        SubCommand::Chart(other_args) => chart(other_args),
+        SubCommand::Report(other_args) => report(other_args),
+        SubCommand::Compare(other_args) => compare(other_args),
+        SubCommand::CheckBreaks(other_args) => check_breaks(other_args),
+        SubCommand::CheckLimits(other_args) => check_limits(other_args),
+        SubCommand::Check(other_args) => check(&now, other_args),
+        SubCommand::Serve(other_args) => serve(other_args),
         _ => processed = false,
     }
     if processed {
-        exit(0);
+        return;
     }
 
+    close_stale_day_if_needed(&now);
     let possible_day: Result<Day, String> = get_current_day(&now);
     if let Err(msg) = possible_day {
         eprintln!("{}", msg);
@@ -190,13 +374,16 @@ fn run_command(command: SubCommand, now: DateTime<Local>) {
         SubCommand::BackIn(other_args) => punch_back_in(&now, other_args, day),
         SubCommand::Pause(other_args) => take_break(&now, other_args, day),
         SubCommand::Resume(other_args) => resume(&now, other_args, day),
-        SubCommand::Summary(_) => summary(&now, day),
-        SubCommand::View(_) => view_day(day),
+        SubCommand::Summary(other_args) => summary(&now, day, other_args),
+        SubCommand::View(other_args) => view_day(&now, day, other_args),
         SubCommand::Edit(_) => edit_day(day),
         SubCommand::Task(other_args) => switch_to_new_task(&now, day, other_args),
         SubCommand::Note(other_args) => add_note_to_today(&now, day, other_args),
         SubCommand::AddSummary(other_args) => add_summary_to_today(day, other_args),
         SubCommand::UpdateTask(other_args) => update_current_task_name(&now, day, other_args),
+        SubCommand::Focus(other_args) => focus(&now, day, other_args),
+        SubCommand::IdleFix(other_args) => idle_fix(&now, day, other_args),
+        SubCommand::Track(other_args) => track(&now, day, other_args),
         SubCommand::Version(_) => {
             unreachable!("`punch version/--version/-v` commands should already be processed.")
         }
@@ -1,5 +1,6 @@
 use rand::{distr::Alphanumeric, Rng};
 
+#[allow(dead_code)]
 pub fn generate_random_string(num_chars: usize) -> String {
     return rand::rng()
         .sample_iter(&Alphanumeric)
@@ -0,0 +1,97 @@
+pub mod sqlite;
+pub mod yaml;
+
+use chrono::NaiveDate;
+use std::path::Path;
+
+use crate::storage::sqlite::SqliteStorage;
+use crate::storage::yaml::YamlStorage;
+use crate::units::day::Day;
+use crate::utils::balance::BalanceLedger;
+use crate::utils::config::Config;
+use crate::utils::file_io::{expand_path, read_file, write_file};
+use crate::utils::profiles::get_data_dir;
+
+pub const STORAGE_FILE: &str = "storage";
+
+pub trait Storage {
+    fn read_day_from_date_str(&self, date_str: &str) -> Result<Day, std::io::Error>;
+
+    fn read_days_between(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Vec<(NaiveDate, Day)>;
+
+    fn list_day_date_strs(&self) -> Vec<String>;
+
+    fn write_day(&self, day: &Day);
+
+    fn delete_day(&self, date_str: &str);
+
+    fn has_config(&self) -> bool;
+
+    fn read_config(&self) -> Config;
+
+    fn write_config(&self, config: &Config);
+
+    fn read_balance_ledger(&self) -> BalanceLedger;
+
+    fn write_balance_ledger(&self, ledger: &BalanceLedger);
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StorageKind {
+    Yaml,
+    Sqlite,
+}
+
+impl StorageKind {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        return match name.trim().to_lowercase().as_str() {
+            "yaml" => Ok(Self::Yaml),
+            "sqlite" => Ok(Self::Sqlite),
+            other => Err(format!(
+                "'{}' isn't a storage backend. Try 'yaml' or 'sqlite'.",
+                other
+            )),
+        };
+    }
+
+    pub fn to_string(self) -> String {
+        return match self {
+            Self::Yaml => "yaml",
+            Self::Sqlite => "sqlite",
+        }
+        .to_string();
+    }
+}
+
+pub fn get_storage_kind_path() -> String {
+    return expand_path(&(get_data_dir() + STORAGE_FILE));
+}
+
+pub fn get_storage_kind() -> StorageKind {
+    // Without a storage file, the data is in the original YAML layout.
+    let storage_kind_path: String = get_storage_kind_path();
+    if !Path::new(&storage_kind_path).exists() {
+        return StorageKind::Yaml;
+    }
+    let storage_kind_str: String = read_file(&storage_kind_path).unwrap();
+    return StorageKind::from_string(&storage_kind_str).expect("Storage file is malformed!");
+}
+
+pub fn set_storage_kind(kind: StorageKind) {
+    write_file(&get_storage_kind_path(), kind.to_string() + "\n");
+}
+
+pub fn open_storage(kind: StorageKind) -> Box<dyn Storage> {
+    return match kind {
+        StorageKind::Yaml => Box::new(YamlStorage {}),
+        StorageKind::Sqlite => Box::new(SqliteStorage::open_default()),
+    };
+}
+
+pub fn get_storage() -> Box<dyn Storage> {
+    return open_storage(get_storage_kind());
+}
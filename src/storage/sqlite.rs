@@ -0,0 +1,336 @@
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::storage::Storage;
+use crate::units::day::Day;
+use crate::units::interval::DATE_FMT;
+use crate::utils::balance::BalanceLedger;
+use crate::utils::config::Config;
+use crate::utils::dates_and_times::convert_date_to_date_str;
+use crate::utils::encryption::{decrypt_if_needed, encrypt_contents_if_enabled};
+use crate::utils::fatal::exit_with_error;
+use crate::utils::file_io::{expand_path, FromString};
+use crate::utils::history::record_change;
+use crate::utils::profiles::get_data_dir;
+
+pub const SQLITE_FILE: &str = "punch.db";
+const CONFIG_DOCUMENT: &str = "config";
+const BALANCE_DOCUMENT: &str = "balance";
+
+/// A stored day or document, keyed by its table and date or name.
+pub struct SqliteRow {
+    table: &'static str,
+    key: String,
+    contents: String,
+}
+
+pub struct SqliteStorage {
+    connection: Connection,
+    path: Option<String>,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> Result<Self, rusqlite::Error> {
+        return Self::from_connection(Connection::open(path)?, Some(path.to_string()));
+    }
+
+    pub fn open_default() -> Self {
+        let path: String = get_sqlite_path();
+        return Self::open(&path).expect(&format!("Couldn't open the database at '{}'", path));
+    }
+
+    #[allow(dead_code)]
+    pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
+        return Self::from_connection(Connection::open_in_memory()?, None);
+    }
+
+    fn from_connection(
+        connection: Connection,
+        path: Option<String>,
+    ) -> Result<Self, rusqlite::Error> {
+        // Days are keyed by their date, so the primary key index serves range queries.
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS days (date TEXT PRIMARY KEY, contents TEXT NOT NULL);
+            CREATE TABLE IF NOT EXISTS documents (name TEXT PRIMARY KEY, contents TEXT NOT NULL);",
+        )?;
+        return Ok(Self {
+            connection: connection,
+            path: path,
+        });
+    }
+
+    fn record_change(&self) {
+        // The database is tracked as a whole, so history can't be restored per day.
+        if let Some(path) = &self.path {
+            record_change(path);
+        }
+    }
+
+    fn read_document(&self, name: &str) -> Option<String> {
+        return self
+            .connection
+            .query_row(
+                "SELECT contents FROM documents WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .expect("Couldn't read from the database!")
+            .map(decrypt_contents);
+    }
+
+    fn write_document(&self, name: &str, contents: String) {
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO documents (name, contents) VALUES (?1, ?2)",
+                params![name, encrypt_contents_if_enabled(contents)],
+            )
+            .expect("Couldn't write to the database!");
+        self.record_change();
+    }
+
+    /// Every day and document as it's stored, i.e. still encrypted if it was.
+    pub fn read_raw_rows(&self) -> Vec<SqliteRow> {
+        let mut rows: Vec<SqliteRow> = Vec::new();
+        for (table, query) in [
+            ("days", "SELECT date, contents FROM days ORDER BY date"),
+            (
+                "documents",
+                "SELECT name, contents FROM documents ORDER BY name",
+            ),
+        ] {
+            let mut statement = self
+                .connection
+                .prepare(query)
+                .expect("Couldn't read from the database!");
+            let table_rows = statement
+                .query_map([], |row| {
+                    Ok(SqliteRow {
+                        table: table,
+                        key: row.get(0)?,
+                        contents: row.get(1)?,
+                    })
+                })
+                .expect("Couldn't read from the database!");
+            rows.extend(table_rows.filter_map(|x| x.ok()));
+        }
+        return rows;
+    }
+
+    /// Reads every row, so they can be rewritten with `write_rows` after the encryption changes.
+    pub fn read_all_rows(&self) -> Vec<SqliteRow> {
+        return self
+            .read_raw_rows()
+            .into_iter()
+            .map(|x| SqliteRow {
+                contents: decrypt_contents(x.contents),
+                ..x
+            })
+            .collect();
+    }
+
+    pub fn write_rows(&self, rows: &[SqliteRow]) {
+        for row in rows.iter() {
+            let query: &str = match row.table {
+                "days" => "INSERT OR REPLACE INTO days (date, contents) VALUES (?1, ?2)",
+                _ => "INSERT OR REPLACE INTO documents (name, contents) VALUES (?1, ?2)",
+            };
+            self.connection
+                .execute(
+                    query,
+                    params![row.key, encrypt_contents_if_enabled(row.contents.clone())],
+                )
+                .expect("Couldn't write to the database!");
+        }
+        self.record_change();
+    }
+}
+
+impl SqliteRow {
+    pub fn get_contents(&self) -> &String {
+        return &self.contents;
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn read_day_from_date_str(&self, date_str: &str) -> Result<Day, std::io::Error> {
+        let contents_result: Result<Option<String>, rusqlite::Error> = self
+            .connection
+            .query_row(
+                "SELECT contents FROM days WHERE date = ?1",
+                params![date_str],
+                |row| row.get(0),
+            )
+            .optional();
+        return match contents_result {
+            Ok(Some(contents)) => Ok(Day::from_string(&decrypt_contents(contents))),
+            Ok(None) => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("No day stored for '{}'", date_str),
+            )),
+            Err(err) => Err(std::io::Error::other(err)),
+        };
+    }
+
+    fn read_days_between(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Vec<(NaiveDate, Day)> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT date, contents FROM days WHERE date BETWEEN ?1 AND ?2 ORDER BY date")
+            .expect("Couldn't read from the database!");
+        let rows = statement
+            .query_map(
+                params![
+                    convert_date_to_date_str(start_date),
+                    convert_date_to_date_str(end_date)
+                ],
+                |row| Ok((row.get::<usize, String>(0)?, row.get::<usize, String>(1)?)),
+            )
+            .expect("Couldn't read from the database!");
+        return rows
+            .filter_map(|x| x.ok())
+            .filter_map(|(date_str, contents)| {
+                NaiveDate::parse_from_str(&date_str, DATE_FMT)
+                    .ok()
+                    .map(|x| (x, Day::from_string(&decrypt_contents(contents))))
+            })
+            .collect();
+    }
+
+    fn list_day_date_strs(&self) -> Vec<String> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT date FROM days ORDER BY date")
+            .expect("Couldn't read from the database!");
+        let rows = statement
+            .query_map([], |row| row.get::<usize, String>(0))
+            .expect("Couldn't read from the database!");
+        return rows.filter_map(|x| x.ok()).collect();
+    }
+
+    fn write_day(&self, day: &Day) {
+        let date_str: String = day.get_date().format(DATE_FMT).to_string();
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO days (date, contents) VALUES (?1, ?2)",
+                params![date_str, encrypt_contents_if_enabled(day.as_string())],
+            )
+            .expect("Couldn't write to the database!");
+        self.record_change();
+    }
+
+    fn delete_day(&self, date_str: &str) {
+        self.connection
+            .execute("DELETE FROM days WHERE date = ?1", params![date_str])
+            .expect("Couldn't write to the database!");
+        self.record_change();
+    }
+
+    fn has_config(&self) -> bool {
+        return self.read_document(CONFIG_DOCUMENT).is_some();
+    }
+
+    fn read_config(&self) -> Config {
+        let contents: String = self
+            .read_document(CONFIG_DOCUMENT)
+            .expect("There's no config in the database!");
+        return Config::from_string(&contents);
+    }
+
+    fn write_config(&self, config: &Config) {
+        self.write_document(CONFIG_DOCUMENT, config.as_string());
+    }
+
+    fn read_balance_ledger(&self) -> BalanceLedger {
+        return match self.read_document(BALANCE_DOCUMENT) {
+            Some(contents) => {
+                serde_yaml::from_str(&contents).expect("Balance in the database is malformed!")
+            }
+            None => BalanceLedger::default(),
+        };
+    }
+
+    fn write_balance_ledger(&self, ledger: &BalanceLedger) {
+        self.write_document(BALANCE_DOCUMENT, ledger.as_string());
+    }
+}
+
+// Like `read_file`, there's nothing a caller could do with data it can't decrypt.
+fn decrypt_contents(contents: String) -> String {
+    return match decrypt_if_needed(contents) {
+        Ok(decrypted) => decrypted,
+        Err(err_msg) => exit_with_error(&format!("Couldn't read from the database: {}", err_msg)),
+    };
+}
+
+pub fn get_sqlite_path() -> String {
+    return expand_path(&(get_data_dir() + SQLITE_FILE));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Local, TimeZone};
+
+    fn example_day(day_of_month: u32) -> Day {
+        let start: DateTime<Local> = Local
+            .with_ymd_and_hms(2026, 10, day_of_month, 9, 0, 0)
+            .unwrap();
+        return Day::new(&start, "coding".to_string(), 480, None);
+    }
+
+    #[test]
+    fn test_day_round_trip() {
+        let storage: SqliteStorage = SqliteStorage::open_in_memory().unwrap();
+        storage.write_day(&example_day(5));
+        let day: Day = storage.read_day_from_date_str("2026-10-05").unwrap();
+        assert_eq!(day.get_latest_task_name(), "coding");
+        assert!(storage.read_day_from_date_str("2026-10-06").is_err());
+    }
+
+    #[test]
+    fn test_read_days_between_only_returns_days_in_range() {
+        let storage: SqliteStorage = SqliteStorage::open_in_memory().unwrap();
+        for day_of_month in [1, 5, 7, 12] {
+            storage.write_day(&example_day(day_of_month));
+        }
+        let days: Vec<(NaiveDate, Day)> = storage.read_days_between(
+            NaiveDate::from_ymd_opt(2026, 10, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 10, 7).unwrap(),
+        );
+        let dates: Vec<String> = days
+            .iter()
+            .map(|(x, _)| convert_date_to_date_str(*x))
+            .collect();
+        assert_eq!(dates, vec!["2026-10-05", "2026-10-07"]);
+        assert_eq!(
+            storage.list_day_date_strs(),
+            vec!["2026-10-01", "2026-10-05", "2026-10-07", "2026-10-12"]
+        );
+    }
+
+    #[test]
+    fn test_balance_defaults_to_empty() {
+        let storage: SqliteStorage = SqliteStorage::open_in_memory().unwrap();
+        assert_eq!(storage.read_balance_ledger().get_adjustments().len(), 0);
+        assert!(!storage.has_config());
+    }
+
+    #[test]
+    fn test_rows_round_trip() {
+        let storage: SqliteStorage = SqliteStorage::open_in_memory().unwrap();
+        storage.write_day(&example_day(5));
+        storage.write_document(CONFIG_DOCUMENT, "minutes_behind: 0\n".to_string());
+        let rows: Vec<SqliteRow> = storage.read_all_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get_contents(), "minutes_behind: 0\n");
+
+        let other_storage: SqliteStorage = SqliteStorage::open_in_memory().unwrap();
+        other_storage.write_rows(&rows);
+        assert!(other_storage.read_day_from_date_str("2026-10-05").is_ok());
+        assert!(other_storage.has_config());
+    }
+}
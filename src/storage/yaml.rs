@@ -0,0 +1,99 @@
+use chrono::NaiveDate;
+use std::fs::{read_dir, remove_file, ReadDir};
+use std::path::Path;
+
+use crate::storage::Storage;
+use crate::units::day::{get_day_file_path_from_date_str, Day, DAILY_DIR};
+use crate::units::interval::DATE_FMT;
+use crate::utils::balance::{get_balance_path, BalanceLedger};
+use crate::utils::config::{get_config_path, read_config, write_config, Config};
+use crate::utils::dates_and_times::{convert_date_to_date_str, DateRange};
+use crate::utils::file_io::{expand_path, read_file, write_file, FromString, ToFile};
+use crate::utils::history::record_change;
+use crate::utils::profiles::get_data_dir;
+
+pub struct YamlStorage {}
+
+impl Storage for YamlStorage {
+    fn read_day_from_date_str(&self, date_str: &str) -> Result<Day, std::io::Error> {
+        let path: &String = &get_day_file_path_from_date_str(date_str);
+        let read_result: Result<String, std::io::Error> = read_file(path);
+        return match read_result {
+            Ok(string) => Ok(Day::from_string(&string)),
+            Err(err) => Err(err),
+        };
+    }
+
+    fn read_days_between(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Vec<(NaiveDate, Day)> {
+        return DateRange(start_date, end_date)
+            .filter_map(|x| {
+                self.read_day_from_date_str(&convert_date_to_date_str(x))
+                    .ok()
+                    .map(|y| (x, y))
+            })
+            .collect();
+    }
+
+    fn list_day_date_strs(&self) -> Vec<String> {
+        let daily_dir: String = expand_path(&get_data_dir()) + DAILY_DIR;
+        let entries_result: Result<ReadDir, std::io::Error> = read_dir(daily_dir);
+        if let Err(_) = entries_result {
+            return Vec::new();
+        }
+        let mut date_strs: Vec<String> = entries_result
+            .expect("Error already handled!")
+            .filter_map(|x| x.ok())
+            .filter_map(|x| x.file_name().into_string().ok())
+            .filter(|x| NaiveDate::parse_from_str(x, DATE_FMT).is_ok())
+            .collect();
+        date_strs.sort();
+        return date_strs;
+    }
+
+    fn write_day(&self, day: &Day) {
+        let path: String = day.get_path();
+        write_file(&path, day.as_string());
+        record_change(&path);
+    }
+
+    fn delete_day(&self, date_str: &str) {
+        let path: String = get_day_file_path_from_date_str(date_str);
+        if Path::new(&path).exists() {
+            remove_file(&path).expect("Couldn't delete day file!");
+            record_change(&path);
+        }
+    }
+
+    fn has_config(&self) -> bool {
+        return Path::new(&get_config_path()).exists();
+    }
+
+    fn read_config(&self) -> Config {
+        return read_config(&get_config_path());
+    }
+
+    fn write_config(&self, config: &Config) {
+        let path: String = get_config_path();
+        write_config(&path, config);
+        record_change(&path);
+    }
+
+    fn read_balance_ledger(&self) -> BalanceLedger {
+        let balance_path: String = get_balance_path();
+        if !Path::new(&balance_path).exists() {
+            return BalanceLedger::default();
+        }
+        let yaml_str: String = read_file(&balance_path).unwrap();
+        return serde_yaml::from_str(&yaml_str).expect("Balance file is malformed!");
+    }
+
+    fn write_balance_ledger(&self, ledger: &BalanceLedger) {
+        let path: String = get_balance_path();
+        write_file(&path, ledger.as_string());
+        record_change(&path);
+    }
+}
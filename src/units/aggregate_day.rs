@@ -1,7 +1,11 @@
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
 
 use crate::units::day::Day;
+use crate::units::rounding::{render_rounded_task_times, RoundingPolicy, RoundingScope};
+use crate::units::task_table::{TaskRow, TaskTableOptions};
 use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::balance::BalanceAdjustment;
 
 #[derive(Debug, Clone)]
 pub struct AggregateDay {
@@ -11,7 +15,16 @@ pub struct AggregateDay {
     pub total_time_to_do: u64,
     pub num_days: u64,
     task_totals: HashMap<String, (u64, u64)>,
+    task_order: Vec<String>,
+    focus_sessions: HashMap<String, u64>,
+    dates_seen: HashSet<NaiveDate>,
     pub starting_time_behind: i64,
+    pub balance_adjustments: i64,
+    rounding: Option<RoundingPolicy>,
+    rounded_task_totals: HashMap<String, i64>,
+    break_task_names: HashSet<String>,
+    secondary_totals: HashMap<String, (u64, u64)>,
+    secondary_order: Vec<String>,
 }
 
 impl AggregateDay {
@@ -23,10 +36,23 @@ impl AggregateDay {
             total_time_to_do: 0,
             num_days: 0,
             task_totals: HashMap::new(),
+            task_order: Vec::new(),
+            focus_sessions: HashMap::new(),
+            dates_seen: HashSet::new(),
             starting_time_behind: starting_time_behind,
+            balance_adjustments: 0,
+            rounding: None,
+            rounded_task_totals: HashMap::new(),
+            break_task_names: HashSet::new(),
+            secondary_totals: HashMap::new(),
+            secondary_order: Vec::new(),
         };
     }
 
+    pub fn set_rounding(&mut self, rounding: Option<RoundingPolicy>) {
+        self.rounding = rounding;
+    }
+
     pub fn add_day(&mut self, day: Day) -> Result<(), &str> {
         if !day.has_ended() {
             return Err("Can't aggregate a day that hasn't ended!");
@@ -41,7 +67,13 @@ impl AggregateDay {
             .get_number_of_breaks()
             .expect("Day has ended so day length should be known.");
         self.total_time_to_do += day.get_time_to_do_secs();
-        self.num_days += 1;
+        // Days split at midnight can contribute more than one part to the same date.
+        if self
+            .dates_seen
+            .insert(day.get_day_start().as_dt().date_naive())
+        {
+            self.num_days += 1;
+        }
 
         let task_summaries: HashMap<String, (i64, u64)> = day.get_task_times_secs_and_num_blocks();
         let old_task_totals: HashMap<String, (u64, u64)> = self.task_totals.clone();
@@ -49,15 +81,102 @@ impl AggregateDay {
             let (time, blocks) = task_summaries.get(&task_name).unwrap();
             let (curr_time, curr_blocks): (u64, u64) =
                 *old_task_totals.get(&task_name).unwrap_or(&(0, 0));
+            if !self.task_order.contains(&task_name) {
+                self.task_order.push(task_name.clone());
+            }
 
             self.task_totals.insert(
                 task_name,
                 (curr_time + (*time as u64), curr_blocks + blocks),
             );
         }
+        self.break_task_names.extend(day.get_break_task_names());
+        if let Some(policy) = &self.rounding {
+            for (task_name, secs) in day.get_rounded_task_times_secs(policy) {
+                *self.rounded_task_totals.entry(task_name).or_insert(0) += secs;
+            }
+        }
+        for (task_name, num_sessions) in day.get_completed_focus_sessions_per_task() {
+            *self.focus_sessions.entry(task_name).or_insert(0) += num_sessions;
+        }
+
+        let secondary_summaries: HashMap<String, (i64, u64)> =
+            day.get_secondary_task_times_secs_and_num_blocks();
+        for task_name in day.get_secondary_tasks_in_chronological_order() {
+            let (time, blocks) = secondary_summaries[&task_name];
+            if !self.secondary_order.contains(&task_name) {
+                self.secondary_order.push(task_name.clone());
+            }
+            let (curr_time, curr_blocks) = self.secondary_totals.entry(task_name).or_default();
+            *curr_time += time as u64;
+            *curr_blocks += blocks;
+        }
         return Ok(());
     }
 
+    pub fn add_balance_adjustment(&mut self, adjustment: &BalanceAdjustment) {
+        self.balance_adjustments += adjustment.get_seconds();
+    }
+
+    pub fn get_task_times_secs(&self) -> HashMap<String, i64> {
+        return self
+            .task_totals
+            .iter()
+            .map(|(task_name, (time, _))| (task_name.clone(), *time as i64))
+            .collect();
+    }
+
+    /// Totals for each task, in the order they were first seen.
+    pub fn get_task_rows(&self) -> Vec<TaskRow> {
+        return self
+            .task_order
+            .iter()
+            .map(|task_name| {
+                let (secs, blocks) = self.task_totals[task_name];
+                TaskRow {
+                    task_name: task_name.clone(),
+                    secs: secs as i64,
+                    blocks: blocks,
+                    is_break: self.is_break_task(task_name),
+                }
+            })
+            .collect();
+    }
+
+    /// Totals for each task on the secondary track, which aren't part of the time done.
+    pub fn get_secondary_task_rows(&self) -> Vec<TaskRow> {
+        return self
+            .secondary_order
+            .iter()
+            .map(|task_name| {
+                let (secs, blocks) = self.secondary_totals[task_name];
+                TaskRow {
+                    task_name: task_name.clone(),
+                    secs: secs as i64,
+                    blocks: blocks,
+                    is_break: false,
+                }
+            })
+            .collect();
+    }
+
+    pub fn get_rounded_task_times_secs(&self) -> Option<HashMap<String, i64>> {
+        let policy: &RoundingPolicy = self.rounding.as_ref()?;
+        if policy.get_scope() != RoundingScope::Invoice {
+            return Some(self.rounded_task_totals.clone());
+        }
+        return Some(
+            self.get_task_times_secs()
+                .into_iter()
+                .map(|(task_name, secs)| (task_name, policy.round_secs(secs)))
+                .collect(),
+        );
+    }
+
+    pub fn is_break_task(&self, task_name: &str) -> bool {
+        return self.break_task_names.contains(task_name);
+    }
+
     pub fn get_total_time_done(&self) -> u64 {
         return self.total_time - self.total_break_time;
     }
@@ -67,7 +186,15 @@ impl AggregateDay {
     }
 
     pub fn get_time_behind_overall(&self) -> i64 {
-        return self.get_time_behind_over_period() + self.starting_time_behind;
+        return self.get_time_behind_over_period() + self.starting_time_behind
+            - self.balance_adjustments;
+    }
+
+    pub fn get_average_day_length_secs(&self) -> i64 {
+        if self.num_days == 0 {
+            return 0;
+        }
+        return (self.total_time / self.num_days) as i64;
     }
 
     pub fn get_total_blocks(&self) -> u64 {
@@ -87,6 +214,7 @@ impl AggregateDay {
         &self,
         include_overall_time_behind: bool,
         show_times_in_hours: bool,
+        task_options: &TaskTableOptions,
     ) -> Result<String, String> {
         let mut summary_str: String = format!("Num days summarised: {}", self.num_days);
         summary_str += &format!(
@@ -114,16 +242,56 @@ impl AggregateDay {
         summary_str += &format!("\nTotal breaks: {}", self.num_breaks);
         summary_str += "\n";
         summary_str += &"\nTask times, blocks:";
-        for (task_name, (time, blocks)) in self.task_totals.clone().into_iter() {
+        let task_rows: Vec<TaskRow> = task_options.select(self.get_task_rows());
+        for task_row in task_rows.iter() {
             summary_str += &format!(
                 "\n\t{}: {}, {} blocks",
-                task_name,
-                render_seconds_human_readable(time as i64, show_times_in_hours),
-                blocks
+                task_row.task_name,
+                render_seconds_human_readable(task_row.secs, show_times_in_hours),
+                task_row.blocks
             );
         }
         summary_str += "\n";
 
+        if self.focus_sessions.len() > 0 {
+            summary_str += &"\nCompleted focus sessions:";
+            for task_name in self.task_order.iter() {
+                if let Some(num_sessions) = self.focus_sessions.get(task_name) {
+                    summary_str += &format!("\n\t{}: {}", task_name, num_sessions);
+                }
+            }
+            summary_str += "\n";
+        }
+
+        let secondary_rows: Vec<TaskRow> = self.get_secondary_task_rows();
+        if secondary_rows.len() > 0 {
+            summary_str += "\nSecondary track (not counted in time done):";
+            for task_row in secondary_rows.iter() {
+                summary_str += &format!(
+                    "\n\t{}: {}, {} blocks",
+                    task_row.task_name,
+                    render_seconds_human_readable(task_row.secs, show_times_in_hours),
+                    task_row.blocks
+                );
+            }
+            summary_str += "\n";
+        }
+
+        if let (Some(policy), Some(rounded_times)) =
+            (&self.rounding, self.get_rounded_task_times_secs())
+        {
+            let task_order: Vec<String> = task_rows.into_iter().map(|x| x.task_name).collect();
+            summary_str += "\n";
+            summary_str += &render_rounded_task_times(
+                policy,
+                &task_order,
+                &self.get_task_times_secs(),
+                &rounded_times,
+                show_times_in_hours,
+            );
+            summary_str += "\n";
+        }
+
         summary_str += &format!(
             "\nTime to do over period: {}",
             render_seconds_human_readable(self.total_time_to_do as i64, show_times_in_hours)
@@ -132,6 +300,12 @@ impl AggregateDay {
             "\nTime behind over period: {}",
             render_seconds_human_readable(self.get_time_behind_over_period(), show_times_in_hours),
         );
+        if self.balance_adjustments != 0 {
+            summary_str += &format!(
+                "\nBalance adjustments over period: {}",
+                render_seconds_human_readable(self.balance_adjustments, show_times_in_hours),
+            );
+        }
         if include_overall_time_behind {
             summary_str += &format!(
                 "\nTime behind overall: {}",
@@ -141,3 +315,40 @@ impl AggregateDay {
         return Ok(summary_str);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Local, TimeZone};
+
+    fn example_day(day_of_month: u32, on_call_hours: i64) -> Day {
+        let start: DateTime<Local> = Local
+            .with_ymd_and_hms(2026, 10, day_of_month, 9, 0, 0)
+            .unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        day.start_secondary_block("on-call".to_string(), &start)
+            .unwrap();
+        day.stop_secondary_block_at(&(start + chrono::Duration::hours(on_call_hours)))
+            .unwrap();
+        day.end_day_at(&(start + chrono::Duration::hours(8)), false)
+            .unwrap();
+        return day;
+    }
+
+    #[test]
+    fn test_secondary_track_is_aggregated_separately() {
+        let mut aggregated: AggregateDay = AggregateDay::new(0);
+        aggregated.add_day(example_day(5, 2)).unwrap();
+        aggregated.add_day(example_day(6, 3)).unwrap();
+        assert_eq!(aggregated.get_total_time_done(), 16 * 3600);
+        let secondary_rows: Vec<TaskRow> = aggregated.get_secondary_task_rows();
+        assert_eq!(secondary_rows.len(), 1);
+        assert_eq!(secondary_rows[0].task_name, "on-call");
+        assert_eq!(secondary_rows[0].secs, 5 * 3600);
+        assert_eq!(secondary_rows[0].blocks, 2);
+        let summary: String = aggregated
+            .render_human_readable_summary(false, true, &TaskTableOptions::default())
+            .unwrap();
+        assert!(summary.contains("Secondary track (not counted in time done):\n\ton-call: "));
+    }
+}
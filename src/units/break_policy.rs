@@ -0,0 +1,315 @@
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::units::day::Day;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+/// Once more than `after_minutes` have been worked in a day, at least `break_minutes` of breaks are needed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RequiredBreak {
+    after_minutes: u64,
+    break_minutes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BreakPolicy {
+    #[serde(default)]
+    required_breaks: Vec<RequiredBreak>,
+    max_minutes_without_break: Option<u64>,
+    min_break_minutes: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreakViolation {
+    NotEnoughBreak {
+        work_secs: i64,
+        break_secs: i64,
+        required_break_secs: i64,
+        after_secs: i64,
+    },
+    TooLongWithoutBreak {
+        start: DateTime<Local>,
+        secs: i64,
+        max_secs: i64,
+    },
+}
+
+impl BreakViolation {
+    pub fn render(&self, show_times_in_hours: bool) -> String {
+        let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+        return match self {
+            Self::NotEnoughBreak {
+                work_secs,
+                break_secs,
+                required_break_secs,
+                after_secs,
+            } => format!(
+                "Worked {} with {} of breaks, but {} of breaks are needed after working {}.",
+                render(*work_secs),
+                render(*break_secs),
+                render(*required_break_secs),
+                render(*after_secs)
+            ),
+            Self::TooLongWithoutBreak {
+                start,
+                secs,
+                max_secs,
+            } => format!(
+                "Worked {} without a break from {}, but the most allowed is {}.",
+                render(*secs),
+                start.format("%H:%M"),
+                render(*max_secs)
+            ),
+        };
+    }
+}
+
+/// How a day's work and breaks measure up against a break policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreakCheck {
+    pub work_secs: i64,
+    pub counted_break_secs: i64,
+    pub longest_stretch_secs: i64,
+    pub violations: Vec<BreakViolation>,
+}
+
+impl BreakPolicy {
+    pub fn get_max_minutes_without_break(&self) -> Option<u64> {
+        return self.max_minutes_without_break;
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = self
+            .required_breaks
+            .iter()
+            .map(|x| format!("{}m after {}m", x.break_minutes, x.after_minutes))
+            .collect();
+        if let Some(max_minutes) = self.max_minutes_without_break {
+            parts.push(format!("at most {}m without a break", max_minutes));
+        }
+        if let Some(min_minutes) = self.min_break_minutes {
+            parts.push(format!("breaks under {}m don't count", min_minutes));
+        }
+        return parts.join(", ");
+    }
+
+    /// Checks the finished blocks of a day, so an ongoing block isn't counted until it ends.
+    pub fn check_day(&self, day: &Day) -> BreakCheck {
+        let min_break_secs: i64 = (self.min_break_minutes.unwrap_or(0) * 60) as i64;
+        let max_stretch_secs_opt: Option<i64> =
+            self.max_minutes_without_break.map(|x| (x * 60) as i64);
+        let mut violations: Vec<BreakViolation> = Vec::new();
+        let (mut work_secs, mut counted_break_secs): (i64, i64) = (0, 0);
+        let mut longest_stretch_secs: i64 = 0;
+        // Consecutive break blocks count as one break, and a stretch is the work between counted breaks.
+        let mut current_break_secs: i64 = 0;
+        let mut stretch_opt: Option<(DateTime<Local>, i64)> = None;
+        let mut close_stretch = |stretch: (DateTime<Local>, i64), violations: &mut Vec<_>| {
+            let (start, secs) = stretch;
+            longest_stretch_secs = longest_stretch_secs.max(secs);
+            if let Some(max_secs) = max_stretch_secs_opt {
+                if secs > max_secs {
+                    violations.push(BreakViolation::TooLongWithoutBreak {
+                        start: start,
+                        secs: secs,
+                        max_secs: max_secs,
+                    });
+                }
+            }
+        };
+        for (ind, block) in day.timeblocks.iter().enumerate() {
+            let length: i64 = match block.get_length_secs() {
+                Some(length) => length,
+                None => continue,
+            };
+            if day.is_break(ind) {
+                current_break_secs += length;
+                continue;
+            }
+            if (current_break_secs > 0) && (current_break_secs >= min_break_secs) {
+                counted_break_secs += current_break_secs;
+                if let Some(stretch) = stretch_opt.take() {
+                    close_stretch(stretch, &mut violations);
+                }
+            }
+            current_break_secs = 0;
+            let (start, secs) = stretch_opt.unwrap_or((block.get_start().as_dt(), 0));
+            stretch_opt = Some((start, secs + length));
+            work_secs += length;
+        }
+        if (current_break_secs > 0) && (current_break_secs >= min_break_secs) {
+            counted_break_secs += current_break_secs;
+        }
+        if let Some(stretch) = stretch_opt {
+            close_stretch(stretch, &mut violations);
+        }
+
+        // Only the strictest rule that applies matters, as it needs the most break time.
+        let strictest_rule_opt: Option<&RequiredBreak> = self
+            .required_breaks
+            .iter()
+            .filter(|x| work_secs > (x.after_minutes * 60) as i64)
+            .max_by_key(|x| x.break_minutes);
+        if let Some(rule) = strictest_rule_opt {
+            let required_break_secs: i64 = (rule.break_minutes * 60) as i64;
+            if counted_break_secs < required_break_secs {
+                violations.insert(
+                    0,
+                    BreakViolation::NotEnoughBreak {
+                        work_secs: work_secs,
+                        break_secs: counted_break_secs,
+                        required_break_secs: required_break_secs,
+                        after_secs: (rule.after_minutes * 60) as i64,
+                    },
+                );
+            }
+        }
+        return BreakCheck {
+            work_secs: work_secs,
+            counted_break_secs: counted_break_secs,
+            longest_stretch_secs: longest_stretch_secs,
+            violations: violations,
+        };
+    }
+
+    pub fn find_violations_in_days(
+        &self,
+        days: &[(NaiveDate, Day)],
+    ) -> Vec<(NaiveDate, BreakViolation)> {
+        return days
+            .iter()
+            .flat_map(|(date, day)| {
+                self.check_day(day)
+                    .violations
+                    .into_iter()
+                    .map(|violation| (*date, violation))
+            })
+            .collect();
+    }
+}
+
+pub fn render_break_check(
+    policy: &BreakPolicy,
+    check: &BreakCheck,
+    show_times_in_hours: bool,
+) -> String {
+    let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+    let mut summary_str: String = format!("Break policy ({}):", policy.describe());
+    summary_str += &format!(
+        "\n\tBreaks counted: {}\n\tLongest time worked without a break: {}",
+        render(check.counted_break_secs),
+        render(check.longest_stretch_secs)
+    );
+    if check.violations.len() == 0 {
+        summary_str += "\n\tNo violations.";
+    }
+    for violation in check.violations.iter() {
+        summary_str += &format!("\n\tViolation: {}", violation.render(show_times_in_hours));
+    }
+    return summary_str;
+}
+
+pub fn render_violations_by_date(
+    policy: &BreakPolicy,
+    violations: &[(NaiveDate, BreakViolation)],
+    show_times_in_hours: bool,
+) -> String {
+    let mut summary_str: String = format!("Break policy ({}):", policy.describe());
+    if violations.len() == 0 {
+        summary_str += "\n\tNo violations.";
+    }
+    for (date, violation) in violations.iter() {
+        summary_str += &format!(
+            "\n\t{}: {}",
+            date.format("%Y-%m-%d"),
+            violation.render(show_times_in_hours)
+        );
+    }
+    return summary_str;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn policy() -> BreakPolicy {
+        return BreakPolicy {
+            required_breaks: vec![
+                RequiredBreak {
+                    after_minutes: 360,
+                    break_minutes: 30,
+                },
+                RequiredBreak {
+                    after_minutes: 540,
+                    break_minutes: 45,
+                },
+            ],
+            max_minutes_without_break: Some(360),
+            min_break_minutes: Some(15),
+        };
+    }
+
+    /// A day of alternating work and breaks, lengths given in minutes and starting with work.
+    fn day_of(minutes: &[i64]) -> Day {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 8, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        let mut at: DateTime<Local> = start;
+        for (i, length) in minutes.iter().enumerate() {
+            at += Duration::minutes(*length);
+            if i + 1 == minutes.len() {
+                break;
+            }
+            if i % 2 == 0 {
+                day.start_break_at("Break".to_string(), &at).unwrap();
+            } else {
+                day.start_new_block("coding".to_string(), &at).unwrap();
+            }
+        }
+        day.end_day_at(&at, false).unwrap();
+        return day;
+    }
+
+    #[test]
+    fn test_compliant_day() {
+        let check: BreakCheck = policy().check_day(&day_of(&[240, 30, 240]));
+        assert_eq!(check.work_secs, 480 * 60);
+        assert_eq!(check.counted_break_secs, 30 * 60);
+        assert_eq!(check.longest_stretch_secs, 240 * 60);
+        assert_eq!(check.violations, vec![]);
+    }
+
+    #[test]
+    fn test_strictest_rule_is_used() {
+        let check: BreakCheck = policy().check_day(&day_of(&[300, 30, 300]));
+        assert_eq!(
+            check.violations,
+            vec![BreakViolation::NotEnoughBreak {
+                work_secs: 600 * 60,
+                break_secs: 30 * 60,
+                required_break_secs: 45 * 60,
+                after_secs: 540 * 60,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_short_breaks_dont_count() {
+        let check: BreakCheck = policy().check_day(&day_of(&[200, 10, 200]));
+        assert_eq!(check.counted_break_secs, 0);
+        assert_eq!(check.longest_stretch_secs, 400 * 60);
+        assert_eq!(check.violations.len(), 2);
+        assert_eq!(
+            check.violations[1].render(true),
+            "Worked 6h 40m 0s without a break from 08:00, but the most allowed is 6h 0m 0s."
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            policy().describe(),
+            "30m after 360m, 45m after 540m, at most 360m without a break, breaks under 15m don't count"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use prettytable::{row, Table};
+use std::collections::HashMap;
+
+use crate::units::aggregate_day::AggregateDay;
+use crate::units::task_table::{TaskRow, TaskTableOptions};
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+/// Two periods side by side, with the changes going from the second to the first.
+pub struct Comparison<'a> {
+    pub first_label: String,
+    pub first: &'a AggregateDay,
+    pub second_label: String,
+    pub second: &'a AggregateDay,
+}
+
+fn render_percentage_change(first: i64, second: i64) -> String {
+    if second == 0 {
+        return match first {
+            0 => "-".to_string(),
+            _ => "new".to_string(),
+        };
+    }
+    let change: f64 = ((first - second) as f64) * 100.0 / (second as f64);
+    return format!("{:+.1}%", change);
+}
+
+fn render_secs_change(first: i64, second: i64, show_times_in_hours: bool) -> String {
+    let delta: i64 = first - second;
+    let sign: &str = if delta > 0 { "+" } else { "" };
+    return sign.to_string() + &render_seconds_human_readable(delta, show_times_in_hours);
+}
+
+fn render_count_change(first: u64, second: u64) -> String {
+    let delta: i64 = first as i64 - second as i64;
+    let sign: &str = if delta > 0 { "+" } else { "" };
+    return format!("{}{}", sign, delta);
+}
+
+impl<'a> Comparison<'a> {
+    fn is_break_task(&self, task_name: &str) -> bool {
+        return self.first.is_break_task(task_name) || self.second.is_break_task(task_name);
+    }
+
+    /// Tasks done in either period with their combined totals, first period's tasks first.
+    fn get_combined_task_rows(&self) -> Vec<TaskRow> {
+        let mut combined_rows: Vec<TaskRow> = Vec::new();
+        for task_row in self
+            .first
+            .get_task_rows()
+            .into_iter()
+            .chain(self.second.get_task_rows())
+        {
+            match combined_rows
+                .iter_mut()
+                .find(|x| x.task_name == task_row.task_name)
+            {
+                Some(combined_row) => {
+                    combined_row.secs += task_row.secs;
+                    combined_row.blocks += task_row.blocks;
+                }
+                None => combined_rows.push(task_row),
+            }
+        }
+        return combined_rows;
+    }
+
+    /// Tasks (not breaks) done in either period, picked and ordered by the task table options.
+    pub fn get_task_order(&self, task_options: &TaskTableOptions) -> Vec<String> {
+        let task_rows: Vec<TaskRow> = self
+            .get_combined_task_rows()
+            .into_iter()
+            .filter(|x| !self.is_break_task(&x.task_name))
+            .collect();
+        return task_options
+            .select(task_rows)
+            .into_iter()
+            .map(|x| x.task_name)
+            .collect();
+    }
+
+    pub fn render_totals_table(&self, show_times_in_hours: bool) -> Table {
+        let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+        let mut table = Table::new();
+        table.set_titles(row!["", self.first_label, self.second_label, "Change", "%"]);
+        let secs_rows: [(&str, i64, i64); 3] = [
+            (
+                "Time done",
+                self.first.get_total_time_done() as i64,
+                self.second.get_total_time_done() as i64,
+            ),
+            (
+                "Break time",
+                self.first.total_break_time as i64,
+                self.second.total_break_time as i64,
+            ),
+            (
+                "Average day length",
+                self.first.get_average_day_length_secs(),
+                self.second.get_average_day_length_secs(),
+            ),
+        ];
+        for (label, first, second) in secs_rows {
+            table.add_row(row![
+                label,
+                render(first),
+                render(second),
+                render_secs_change(first, second, show_times_in_hours),
+                render_percentage_change(first, second)
+            ]);
+        }
+        let count_rows: [(&str, u64, u64); 3] = [
+            ("Days", self.first.num_days, self.second.num_days),
+            (
+                "Blocks (excluding breaks)",
+                self.first.get_total_non_break_blocks(),
+                self.second.get_total_non_break_blocks(),
+            ),
+            ("Breaks", self.first.num_breaks, self.second.num_breaks),
+        ];
+        for (label, first, second) in count_rows {
+            table.add_row(row![
+                label,
+                first,
+                second,
+                render_count_change(first, second),
+                render_percentage_change(first as i64, second as i64)
+            ]);
+        }
+        return table;
+    }
+
+    pub fn render_tasks_table(
+        &self,
+        show_times_in_hours: bool,
+        task_options: &TaskTableOptions,
+    ) -> Table {
+        let first_times: HashMap<String, i64> = self.first.get_task_times_secs();
+        let second_times: HashMap<String, i64> = self.second.get_task_times_secs();
+        let mut table = Table::new();
+        table.set_titles(row![
+            "Task",
+            self.first_label,
+            self.second_label,
+            "Change",
+            "%",
+            "Only in"
+        ]);
+        for task_name in self.get_task_order(task_options) {
+            let first_opt: Option<&i64> = first_times.get(&task_name);
+            let second_opt: Option<&i64> = second_times.get(&task_name);
+            let only_in: &str = match (first_opt, second_opt) {
+                (Some(_), None) => &self.first_label,
+                (None, Some(_)) => &self.second_label,
+                _ => "",
+            };
+            let (first, second): (i64, i64) = (*first_opt.unwrap_or(&0), *second_opt.unwrap_or(&0));
+            table.add_row(row![
+                task_name,
+                render_seconds_human_readable(first, show_times_in_hours),
+                render_seconds_human_readable(second, show_times_in_hours),
+                render_secs_change(first, second, show_times_in_hours),
+                render_percentage_change(first, second),
+                only_in
+            ]);
+        }
+        return table;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::day::Day;
+    use chrono::{DateTime, Duration, Local, TimeZone};
+    use test_case::test_case;
+
+    fn aggregate_day_of(tasks: &[&str]) -> AggregateDay {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, tasks[0].to_string(), 480, None);
+        for (i, task_name) in tasks.iter().enumerate().skip(1) {
+            day.start_new_block(task_name.to_string(), &(start + Duration::hours(i as i64)))
+                .unwrap();
+        }
+        day.end_day_at(&(start + Duration::hours(tasks.len() as i64)), false)
+            .unwrap();
+        let mut aggregated: AggregateDay = AggregateDay::new(0);
+        aggregated.add_day(day).unwrap();
+        return aggregated;
+    }
+
+    #[test_case(150, 100, "+50.0%" ; "increase")]
+    #[test_case(50, 100, "-50.0%" ; "decrease")]
+    #[test_case(0, 100, "-100.0%" ; "stopped")]
+    #[test_case(100, 0, "new" ; "started")]
+    #[test_case(0, 0, "-" ; "neither")]
+    fn test_render_percentage_change(first: i64, second: i64, expected: &str) {
+        assert_eq!(render_percentage_change(first, second), expected);
+    }
+
+    #[test]
+    fn test_render_secs_change() {
+        assert_eq!(render_secs_change(7200, 3600, true), "+1h 0m 0s");
+        assert_eq!(render_secs_change(3600, 7200, true), "-1h 0m 0s");
+        assert_eq!(render_secs_change(60, 60, true), "0s");
+    }
+
+    #[test]
+    fn test_render_tasks_table_marks_tasks_in_one_period() {
+        let first: AggregateDay = aggregate_day_of(&["coding", "review", "coding"]);
+        let second: AggregateDay = aggregate_day_of(&["coding", "meetings"]);
+        let comparison: Comparison = Comparison {
+            first_label: "this-week".to_string(),
+            first: &first,
+            second_label: "last-week".to_string(),
+            second: &second,
+        };
+        let default_options: TaskTableOptions = TaskTableOptions::default();
+        assert_eq!(
+            comparison.get_task_order(&default_options),
+            vec!["coding", "review", "meetings"]
+        );
+        let table_str: String = comparison
+            .render_tasks_table(true, &default_options)
+            .to_string();
+        let lines: Vec<&str> = table_str.lines().collect();
+        assert!(lines[3].contains("| coding   | 2h 0m 0s  | 1h 0m 0s  | +1h 0m 0s | +100.0% |"));
+        assert!(lines[5]
+            .contains("| review   | 1h 0m 0s  | 0s        | +1h 0m 0s | new     | this-week |"));
+        assert!(lines[7]
+            .contains("| meetings | 0s        | 1h 0m 0s  | -1h 0m 0s | -100.0% | last-week |"));
+    }
+}
@@ -16,6 +16,14 @@ impl Note {
             msg: msg.to_string(),
         };
     }
+
+    pub fn get_time(&self) -> Dt {
+        return self.time;
+    }
+
+    pub fn get_msg(&self) -> String {
+        return self.msg.clone();
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -92,8 +100,26 @@ impl TimeBlock {
         return self.interval.get_length_mins();
     }
 
+    pub fn get_notes(&self) -> &[Note] {
+        return &self.notes;
+    }
+
     pub fn add_note(&mut self, time: &DateTime<Local>, msg: &String) {
         let new_note: Note = Note::new(time, msg);
         self.notes.push(new_note);
     }
+
+    pub fn split_at(&mut self, at: &DateTime<Local>) -> TimeBlock {
+        // Ends this block at `at` and returns a new block for the same task covering the rest.
+        let mut tail: TimeBlock = TimeBlock::new(self.task_name.clone(), at);
+        if let Some(end) = self.get_end() {
+            tail.end_at(&end.as_dt());
+        }
+        let (notes_before, notes_after): (Vec<Note>, Vec<Note>) =
+            self.notes.drain(..).partition(|x| x.time.as_dt() < *at);
+        self.notes = notes_before;
+        tail.notes = notes_after;
+        self.end_at(at);
+        return tail;
+    }
 }
@@ -1,16 +1,21 @@
 use chrono::prelude::{DateTime, Local};
-use chrono::Duration;
+use chrono::{Duration, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+use crate::storage::{get_storage, Storage};
 use crate::units::components::TimeBlock;
 use crate::units::interval::{Dt, Interval, DATETIME_FMT, DATE_FMT};
+use crate::units::rounding::{RoundingPolicy, RoundingScope};
+use crate::units::task_table::{TaskRow, TaskTableOptions};
 use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::dates_and_times::{get_date_in_timezone, get_working_timezone_name, DateRange};
 
 use crate::utils::file_io::{
-    create_dir_if_not_exists, expand_path, read_file, write_file, FromString, SafeFileEdit, ToFile,
-    BASE_DIR,
+    create_dir_if_not_exists, expand_path, FromString, SafeFileEdit, ToFile,
 };
+use crate::utils::journal::{record_in_journal, JournalTarget};
+use crate::utils::profiles::get_data_dir;
 use crate::utils::work_summary::WorkSummary;
 
 pub const DAILY_DIR: &str = "days/";
@@ -25,6 +30,10 @@ pub struct Day {
     pub time_to_do: u64,
     pub time_to_do_seconds_in_addition: Option<u64>,
     pub summaries: Vec<WorkSummary>,
+    // Blocks are found by their start, since edits can move them around in `timeblocks`.
+    focus_block_starts: Option<Vec<Dt>>,
+    timezone: Option<String>,
+    secondary_blocks: Option<Vec<TimeBlock>>,
 }
 
 impl Day {
@@ -44,6 +53,9 @@ impl Day {
             time_to_do: time_to_do,
             time_to_do_seconds_in_addition: time_to_do_seconds_in_addition,
             summaries: Vec::new(),
+            focus_block_starts: None,
+            timezone: get_working_timezone_name(),
+            secondary_blocks: None,
         };
     }
 
@@ -53,6 +65,7 @@ impl Day {
         }
         self.overall_interval.end_at(at);
         self.end_current_block_at(at);
+        let _ = self.stop_secondary_block_at(at);
         if time_to_do_done {
             let total_time_done: u64 = self.get_time_done_secs().unwrap() as u64;
             let minutes_done: u64 = total_time_done / 60;
@@ -142,6 +155,345 @@ impl Day {
         }
     }
 
+    pub fn start_secondary_block(
+        &mut self,
+        task_name: String,
+        at: &DateTime<Local>,
+    ) -> Result<(), &str> {
+        // The secondary track runs alongside the main blocks, e.g. for being on-call.
+        if self.has_ended() {
+            return Err("Can't start a secondary block because day is already over!");
+        }
+        let _ = self.stop_secondary_block_at(at);
+        self.secondary_blocks
+            .get_or_insert_with(Vec::new)
+            .push(TimeBlock::new(task_name, at));
+        return Ok(());
+    }
+
+    pub fn stop_secondary_block_at(&mut self, at: &DateTime<Local>) -> Result<String, &str> {
+        let current_block: Option<&mut TimeBlock> = self
+            .secondary_blocks
+            .as_mut()
+            .and_then(|x| x.last_mut())
+            .filter(|x| !x.has_end());
+        return match current_block {
+            Some(block) => {
+                block.end_at(at);
+                Ok(block.get_task_name())
+            }
+            None => Err("Nothing is running on the secondary track!"),
+        };
+    }
+
+    pub fn get_current_secondary_task(&self) -> Option<String> {
+        return self
+            .secondary_blocks
+            .as_ref()?
+            .last()
+            .filter(|x| !x.has_end())
+            .map(|x| x.get_task_name());
+    }
+
+    pub fn get_secondary_blocks(&self) -> &[TimeBlock] {
+        return match &self.secondary_blocks {
+            Some(blocks) => blocks,
+            None => &[],
+        };
+    }
+
+    pub fn get_secondary_task_times_secs_and_num_blocks(&self) -> HashMap<String, (i64, u64)> {
+        let mut task_summaries: HashMap<String, (i64, u64)> = HashMap::new();
+        for block in self.get_secondary_blocks() {
+            let entry: &mut (i64, u64) = task_summaries.entry(block.get_task_name()).or_default();
+            entry.0 += block.get_length_secs().unwrap_or(0);
+            entry.1 += 1;
+        }
+        return task_summaries;
+    }
+
+    pub fn get_secondary_tasks_in_chronological_order(&self) -> Vec<String> {
+        let mut task_set = HashSet::new();
+        let mut task_name_vec: Vec<String> = self
+            .get_secondary_blocks()
+            .iter()
+            .map(|x| x.get_task_name())
+            .collect();
+        task_name_vec.retain(|x| task_set.insert(x.clone()));
+        return task_name_vec;
+    }
+
+    pub fn get_latest_block_start(&self) -> Dt {
+        return self
+            .timeblocks
+            .last()
+            .expect("Expected there to be at least one block!")
+            .get_start();
+    }
+
+    pub fn complete_focus_block(
+        &mut self,
+        block_start: &DateTime<Local>,
+        break_name: String,
+        at: &DateTime<Local>,
+    ) -> Result<(), &str> {
+        // A focus block only counts as completed if it's still the ongoing block when it ends.
+        if self.has_ended()
+            || self.on_break
+            || (self.get_latest_block_start().as_dt() != *block_start)
+        {
+            return Err("The focus block is no longer the current block!");
+        }
+        self.start_break_at(break_name, at)
+            .expect("Should be able to start a break since we're not on one!");
+        self.focus_block_starts
+            .get_or_insert_with(Vec::new)
+            .push(Dt(*block_start));
+        return Ok(());
+    }
+
+    fn is_focus_block_start(&self, start: &Dt) -> bool {
+        return self
+            .focus_block_starts
+            .as_ref()
+            .is_some_and(|x| x.iter().any(|y| y.as_dt() == start.as_dt()));
+    }
+
+    pub fn get_completed_focus_sessions_per_task(&self) -> HashMap<String, u64> {
+        // Focus blocks that have since been edited away or turned into breaks aren't counted.
+        let mut sessions: HashMap<String, u64> = HashMap::new();
+        for (ind, block) in self.timeblocks.iter().enumerate() {
+            if !self.is_break(ind) && self.is_focus_block_start(&block.get_start()) {
+                *sessions.entry(block.get_task_name()).or_insert(0) += 1;
+            }
+        }
+        return sessions;
+    }
+
+    pub fn find_idle_gaps(
+        &self,
+        activity: &Vec<DateTime<Local>>,
+        threshold_secs: i64,
+        until: &DateTime<Local>,
+    ) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        // Punch commands count as activity too, so every gap sits within a single block.
+        let day_start: DateTime<Local> = self.get_day_start().as_dt();
+        let day_end: DateTime<Local> = match self.get_day_end() {
+            Some(end) => end.as_dt(),
+            None => *until,
+        };
+        let mut times: Vec<DateTime<Local>> = activity
+            .iter()
+            .filter(|x| (**x >= day_start) && (**x <= day_end))
+            .cloned()
+            .collect();
+        for block in self.timeblocks.iter() {
+            times.push(block.get_start().as_dt());
+            times.push(block.get_end().map(|x| x.as_dt()).unwrap_or(day_end));
+        }
+        times.sort();
+        times.dedup();
+
+        let break_indices: HashSet<&usize> = HashSet::from_iter(self.breaks.iter());
+        let mut gaps: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+        for pair in times.windows(2) {
+            let (gap_start, gap_end) = (pair[0], pair[1]);
+            if (gap_end - gap_start).num_seconds() <= threshold_secs {
+                continue;
+            }
+            match self.find_block_index_at(&gap_start) {
+                Some(ind) if !break_indices.contains(&ind) => gaps.push((gap_start, gap_end)),
+                _ => (),
+            }
+        }
+        return gaps;
+    }
+
+    pub fn get_work_intervals(&self) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        let break_indices: HashSet<&usize> = HashSet::from_iter(self.breaks.iter());
+        return self
+            .timeblocks
+            .iter()
+            .enumerate()
+            .filter(|(ind, _)| !break_indices.contains(ind))
+            .filter_map(|(_, block)| {
+                block
+                    .get_end()
+                    .map(|end| (block.get_start().as_dt(), end.as_dt()))
+            })
+            .collect();
+    }
+
+    fn find_block_index_at(&self, at: &DateTime<Local>) -> Option<usize> {
+        return self.timeblocks.iter().position(|x| {
+            let starts_before: bool = x.get_start().as_dt() <= *at;
+            let ends_after: bool = match x.get_end() {
+                Some(end) => end.as_dt() > *at,
+                None => true,
+            };
+            starts_before && ends_after
+        });
+    }
+
+    pub fn insert_break_between(
+        &mut self,
+        break_name: String,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Result<(), &str> {
+        if start >= end {
+            return Err("A break must end after it starts!");
+        }
+        let block_ind: usize = match self.find_block_index_at(start) {
+            Some(ind) => ind,
+            None => return Err("No block found at the start of the break!"),
+        };
+        if self.breaks.contains(&block_ind) {
+            return Err("Can't insert a break into another break!");
+        }
+        if let Some(block_end) = self.timeblocks[block_ind].get_end() {
+            if *end > block_end.as_dt() {
+                return Err("A break can't be inserted across more than one block!");
+            }
+        }
+
+        let mut break_block: TimeBlock = self.timeblocks[block_ind].split_at(start);
+        let rest_of_block: TimeBlock = break_block.split_at(end);
+        break_block
+            .update_task_name(break_name)
+            .expect("Should be able to rename a block!");
+        self.timeblocks.insert(block_ind + 1, break_block);
+        self.timeblocks.insert(block_ind + 2, rest_of_block);
+
+        // Everything after the split block has moved along by the two new blocks.
+        let shift = |x: usize| if x > block_ind { x + 2 } else { x };
+        self.breaks = self.breaks.iter().map(|x| shift(*x)).collect();
+        self.breaks.push(block_ind + 1);
+        self.breaks.sort();
+        self.rebuild_task_indices();
+        return Ok(());
+    }
+
+    fn rebuild_task_indices(&mut self) {
+        self.tasks = HashMap::new();
+        for (i, block) in self.timeblocks.iter().enumerate() {
+            self.tasks.entry(block.get_task_name()).or_default().push(i);
+        }
+    }
+
+    pub fn clip_to(&self, start: &DateTime<Local>, end: &DateTime<Local>) -> Option<Day> {
+        // Returns the part of an ended day between start and end, or None if they don't overlap.
+        // Time to do and summaries stay with the part containing the start of the day.
+        let day_start: DateTime<Local> = self.get_day_start().as_dt();
+        let day_end: DateTime<Local> = self.get_day_end()?.as_dt();
+        if (day_end <= *start) || (day_start >= *end) {
+            return None;
+        }
+        let contains_day_start: bool = (day_start >= *start) && (day_start < *end);
+        let break_indices: HashSet<&usize> = HashSet::from_iter(self.breaks.iter());
+
+        let mut timeblocks: Vec<TimeBlock> = Vec::new();
+        let mut breaks: Vec<usize> = Vec::new();
+        let mut focus_block_starts: Vec<Dt> = Vec::new();
+        for (i, block) in self.timeblocks.iter().enumerate() {
+            let block_start: DateTime<Local> = block.get_start().as_dt();
+            let block_end: DateTime<Local> = block
+                .get_end()
+                .expect("Day has ended so all blocks should be finished.")
+                .as_dt();
+            if (block_end <= *start) || (block_start >= *end) {
+                continue;
+            }
+            let mut clipped: TimeBlock = block.clone();
+            if block_start < *start {
+                clipped = clipped.split_at(start);
+            }
+            if block_end > *end {
+                clipped.split_at(end);
+            }
+            if break_indices.contains(&i) {
+                breaks.push(timeblocks.len());
+            }
+            if self.is_focus_block_start(&block.get_start()) {
+                focus_block_starts.push(clipped.get_start());
+            }
+            timeblocks.push(clipped);
+        }
+
+        let mut secondary_blocks: Vec<TimeBlock> = Vec::new();
+        for block in self.get_secondary_blocks() {
+            let block_start: DateTime<Local> = block.get_start().as_dt();
+            let block_end: DateTime<Local> = block.get_end().map(|x| x.as_dt()).unwrap_or(day_end);
+            if (block_end <= *start) || (block_start >= *end) {
+                continue;
+            }
+            let mut clipped: TimeBlock = block.clone();
+            if block_start < *start {
+                clipped = clipped.split_at(start);
+            }
+            if block_end > *end {
+                clipped.split_at(end);
+            }
+            secondary_blocks.push(clipped);
+        }
+
+        let mut overall_interval: Interval = Interval::new(&day_start.max(*start));
+        overall_interval.end_at(&day_end.min(*end));
+        let mut clipped_day: Day = Day {
+            overall_interval: overall_interval,
+            timeblocks: timeblocks,
+            tasks: HashMap::new(),
+            breaks: breaks,
+            on_break: false,
+            time_to_do: if contains_day_start {
+                self.time_to_do
+            } else {
+                0
+            },
+            time_to_do_seconds_in_addition: if contains_day_start {
+                self.time_to_do_seconds_in_addition
+            } else {
+                None
+            },
+            summaries: if contains_day_start {
+                self.summaries.clone()
+            } else {
+                Vec::new()
+            },
+            focus_block_starts: if focus_block_starts.len() > 0 {
+                Some(focus_block_starts)
+            } else {
+                None
+            },
+            timezone: self.timezone.clone(),
+            secondary_blocks: if secondary_blocks.len() > 0 {
+                Some(secondary_blocks)
+            } else {
+                None
+            },
+        };
+        clipped_day.rebuild_task_indices();
+        return Some(clipped_day);
+    }
+
+    pub fn split_at_midnights(&self) -> Vec<(NaiveDate, Day)> {
+        // Splits an ended day into one part per calendar date it covers.
+        let (day_start, day_end) = match (self.get_day_start(), self.get_day_end()) {
+            (start, Some(end)) => (start.as_dt(), end.as_dt()),
+            (_, None) => return Vec::new(),
+        };
+        let mut parts: Vec<(NaiveDate, Day)> = Vec::new();
+        for date in DateRange(day_start.date_naive(), day_end.date_naive()) {
+            let next_date: NaiveDate = date + Duration::days(1);
+            let part_opt: Option<Day> =
+                self.clip_to(&get_start_of_date(date), &get_start_of_date(next_date));
+            if let Some(part) = part_opt {
+                parts.push((date, part));
+            }
+        }
+        return parts;
+    }
+
     pub fn restart_day(
         &mut self,
         break_name: String,
@@ -173,6 +525,17 @@ impl Day {
         return self.overall_interval.get_start();
     }
 
+    pub fn get_date(&self) -> NaiveDate {
+        // Days are stored under the date they started on in their own timezone,
+        // so they keep the same file even after travelling.
+        return self.get_date_of(&self.get_day_start().as_dt());
+    }
+
+    /// The date a time falls on in this day's own timezone.
+    pub fn get_date_of(&self, at: &DateTime<Local>) -> NaiveDate {
+        return get_date_in_timezone(at, self.timezone.as_ref());
+    }
+
     pub fn get_day_start_as_str(&self) -> String {
         return self.get_day_start().as_string();
     }
@@ -243,6 +606,26 @@ impl Day {
         ));
     }
 
+    pub fn get_rounded_task_times_secs(&self, policy: &RoundingPolicy) -> HashMap<String, i64> {
+        // Rounding per invoice happens over the whole period, so days keep the raw times then.
+        let mut rounded_times: HashMap<String, i64> = HashMap::new();
+        match policy.get_scope() {
+            RoundingScope::Block => {
+                for block in self.timeblocks.iter() {
+                    *rounded_times.entry(block.get_task_name()).or_insert(0) +=
+                        policy.round_secs(block.get_length_secs().unwrap_or(0));
+                }
+            }
+            RoundingScope::TaskDay => {
+                for (task_name, secs) in self.get_task_times_secs() {
+                    rounded_times.insert(task_name, policy.round_secs(secs));
+                }
+            }
+            RoundingScope::Invoice => rounded_times = self.get_task_times_secs(),
+        }
+        return rounded_times;
+    }
+
     pub fn get_total_break_time_secs(&self) -> Option<i64> {
         return match self.on_break {
             true => None,
@@ -347,6 +730,18 @@ impl Day {
         self.summaries.push(summary);
     }
 
+    pub fn get_break_task_names(&self) -> HashSet<String> {
+        return self
+            .breaks
+            .iter()
+            .map(|i| self.timeblocks[*i].get_task_name())
+            .collect();
+    }
+
+    pub fn is_break(&self, ind: usize) -> bool {
+        return self.breaks.contains(&ind);
+    }
+
     pub fn get_total_break_timeblocks(&self) -> u64 {
         return self.breaks.len() as u64;
     }
@@ -371,10 +766,29 @@ impl Day {
         return task_name_vec;
     }
 
+    pub fn get_task_rows(&self) -> Vec<TaskRow> {
+        let task_summaries: HashMap<String, (i64, u64)> = self.get_task_times_secs_and_num_blocks();
+        let break_task_names: HashSet<String> = self.get_break_task_names();
+        return self
+            .get_tasks_in_chronological_order()
+            .into_iter()
+            .map(|task_name| {
+                let (secs, blocks) = task_summaries[&task_name];
+                TaskRow {
+                    is_break: break_task_names.contains(&task_name),
+                    task_name: task_name,
+                    secs: secs,
+                    blocks: blocks,
+                }
+            })
+            .collect();
+    }
+
     pub fn render_human_readable_summary(
         &self,
         initial_time_behind_opt: Option<i64>,
         show_times_in_hours: bool,
+        task_options: &TaskTableOptions,
     ) -> Result<String, String> {
         if !self.has_ended() {
             return Err("Can't summarise a day before it has ended!".to_string());
@@ -389,7 +803,6 @@ impl Day {
         let break_time: i64 = self
             .get_total_break_time_secs()
             .expect("Day is over so we should be able to calculate total break time!");
-        let task_summaries: HashMap<String, (i64, u64)> = self.get_task_times_secs_and_num_blocks();
         let total_blocks: u64 = self.get_total_timeblocks();
         let num_breaks: u64 = self.get_number_of_breaks().unwrap();
         let total_blocks_without_breaks: u64 = self.get_total_timeblocks_without_breaks();
@@ -419,17 +832,43 @@ impl Day {
 
         summary_str += &format!("\nLatest task: '{}'", self.get_latest_task_name());
         summary_str += &format!("\nTask times, blocks:");
-        for task_name in self.get_tasks_in_chronological_order() {
-            let (time, blocks) = task_summaries.get(&task_name).unwrap();
+        for task_row in task_options.select(self.get_task_rows()) {
             summary_str += &format!(
                 "\n\t{}: {}, {} blocks",
-                task_name,
-                render_seconds_human_readable(*time, show_times_in_hours),
-                blocks
+                task_row.task_name,
+                render_seconds_human_readable(task_row.secs, show_times_in_hours),
+                task_row.blocks
             );
         }
         summary_str += "\n";
 
+        let focus_sessions: HashMap<String, u64> = self.get_completed_focus_sessions_per_task();
+        if focus_sessions.len() > 0 {
+            summary_str += &format!("\nCompleted focus sessions:");
+            for task_name in self.get_tasks_in_chronological_order() {
+                if let Some(num_sessions) = focus_sessions.get(&task_name) {
+                    summary_str += &format!("\n\t{}: {}", task_name, num_sessions);
+                }
+            }
+            summary_str += "\n";
+        }
+
+        let secondary_summaries: HashMap<String, (i64, u64)> =
+            self.get_secondary_task_times_secs_and_num_blocks();
+        if secondary_summaries.len() > 0 {
+            summary_str += &format!("\nSecondary track (not counted in time done):");
+            for task_name in self.get_secondary_tasks_in_chronological_order() {
+                let (time, blocks) = secondary_summaries.get(&task_name).unwrap();
+                summary_str += &format!(
+                    "\n\t{}: {}, {} blocks",
+                    task_name,
+                    render_seconds_human_readable(*time, show_times_in_hours),
+                    blocks
+                );
+            }
+            summary_str += "\n";
+        }
+
         let time_to_do_sec: i64 = self.get_time_to_do_secs() as i64;
         summary_str += &format!(
             "\nTime to do today: {}",
@@ -505,12 +944,21 @@ impl FromString<Day, serde_yaml::Error> for Day {
 
 impl ToFile for Day {
     fn get_path(&self) -> String {
-        return get_day_file_path(&self.get_day_start().as_dt());
+        return get_day_file_path_from_date_str(&self.get_date().format(DATE_FMT).to_string());
     }
 
     fn write(&self) {
-        let path: &String = &self.get_path();
-        write_file(path, self.as_string());
+        write_day(self);
+    }
+
+    fn to_file_string(&self) -> String {
+        return self.as_string();
+    }
+
+    fn read_stored_string(&self) -> Option<String> {
+        return read_day_from_date_str(&self.get_date().format(DATE_FMT).to_string())
+            .ok()
+            .map(|x| x.as_string());
     }
 }
 
@@ -525,31 +973,49 @@ pub fn string_as_time(time_str: &String) -> DateTime<Local> {
 }
 
 pub fn get_day_file_path_from_date_str(date_str: &str) -> String {
-    return expand_path(BASE_DIR) + &(DAILY_DIR.to_string()) + date_str;
+    return expand_path(&get_data_dir()) + &(DAILY_DIR.to_string()) + date_str;
 }
 
+#[allow(dead_code)]
 pub fn get_day_file_path(now: &DateTime<Local>) -> String {
     let day_string: String = now.format(DATE_FMT).to_string();
     return get_day_file_path_from_date_str(&day_string);
 }
 
 pub fn write_day(day: &Day) {
-    let path: &String = &get_day_file_path(&day.get_day_start().as_dt());
-    write_file(path, day.as_string());
+    let storage: Box<dyn Storage> = get_storage();
+    let date_str: String = day.get_date().format(DATE_FMT).to_string();
+    let before: Option<String> = storage
+        .read_day_from_date_str(&date_str)
+        .ok()
+        .map(|x| x.as_string());
+    storage.write_day(day);
+    record_in_journal(JournalTarget::Day(date_str), before, Some(day.as_string()));
 }
 
 pub fn read_day_from_date_str(date_str: &str) -> Result<Day, std::io::Error> {
-    let path: &String = &get_day_file_path_from_date_str(date_str);
-    let read_result: Result<String, std::io::Error> = read_file(path);
-    return match read_result {
-        Ok(string) => Ok(Day::from_string(&string)),
-        Err(err) => Err(err),
-    };
+    return get_storage().read_day_from_date_str(date_str);
 }
 
+/// Reads the day stored under the date `now` falls on, in the same way `write_day` stores it.
 pub fn read_day(now: &DateTime<Local>) -> Result<Day, std::io::Error> {
-    let day_string: String = now.format(DATE_FMT).to_string();
-    return read_day_from_date_str(&day_string);
+    // The day's own timezone may be a date ahead of or behind the working one, e.g. after `punch tz`.
+    let local_date: NaiveDate = now.date_naive();
+    for date in [
+        local_date,
+        local_date - Duration::days(1),
+        local_date + Duration::days(1),
+    ] {
+        if let Ok(day) = read_day_from_date_str(&date.format(DATE_FMT).to_string()) {
+            if day.get_date() == day.get_date_of(now) {
+                return Ok(day);
+            }
+        }
+    }
+    return Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("No day stored for {}", local_date.format(DATE_FMT)),
+    ));
 }
 
 pub fn get_current_day(now: &DateTime<Local>) -> Result<Day, String> {
@@ -558,12 +1024,235 @@ pub fn get_current_day(now: &DateTime<Local>) -> Result<Day, String> {
         return Ok(day);
     } else if let Ok(day) = read_day(&yesterday) {
         return Ok(day);
+    } else if let Some(day) = get_latest_day().filter(|x| !x.has_ended()) {
+        // A day left open for more than a day is still the current day until it's closed.
+        return Ok(day);
     } else {
         return Err("Can't get current day. Have you punched in?".to_string());
     }
 }
 
+pub fn get_latest_day_date_str() -> Option<String> {
+    return get_storage().list_day_date_strs().into_iter().max();
+}
+
+pub fn get_latest_day() -> Option<Day> {
+    return read_day_from_date_str(&get_latest_day_date_str()?).ok();
+}
+
+pub fn get_start_of_date(date: NaiveDate) -> DateTime<Local> {
+    // Midnight may not exist or may happen twice around DST changes, so take the earliest valid time.
+    return date
+        .and_hms_opt(0, 0, 0)
+        .expect("Midnight should be a valid time")
+        .and_local_timezone(Local)
+        .earliest()
+        .unwrap_or_else(|| {
+            (date
+                .and_hms_opt(1, 0, 0)
+                .expect("1am should be a valid time"))
+            .and_local_timezone(Local)
+            .earliest()
+            .expect("1am should exist in the local timezone")
+        });
+}
+
 pub fn create_daily_dir_if_not_exists() {
-    let daily_dir: String = BASE_DIR.to_string() + &(DAILY_DIR.to_string());
+    let daily_dir: String = get_data_dir() + &(DAILY_DIR.to_string());
     create_dir_if_not_exists(&daily_dir);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn at(hour: u32, minute: u32) -> DateTime<Local> {
+        return Local
+            .with_ymd_and_hms(2026, 10, 5, hour, minute, 0)
+            .unwrap();
+    }
+
+    fn example_day() -> Day {
+        let mut day: Day = Day::new(&at(9, 0), "coding".to_string(), 480, None);
+        day.start_break_at("Break".to_string(), &at(10, 0)).unwrap();
+        day.start_new_block("review".to_string(), &at(10, 15))
+            .unwrap();
+        day.end_day_at(&at(13, 0), false).unwrap();
+        return day;
+    }
+
+    #[test]
+    fn test_find_idle_gaps_ignores_breaks_and_short_gaps() {
+        let day: Day = example_day();
+        let activity: Vec<DateTime<Local>> =
+            vec![at(9, 20), at(9, 40), at(10, 30), at(11, 0), at(12, 30)];
+        let gaps = day.find_idle_gaps(&activity, 30 * 60, &at(13, 0));
+        assert_eq!(gaps, vec![(at(11, 0), at(12, 30))]);
+    }
+
+    #[test]
+    fn test_insert_break_between_splits_block() {
+        let mut day: Day = example_day();
+        day.add_note(&at(12, 0), &"a note".to_string());
+        day.insert_break_between("Lunch".to_string(), &at(11, 0), &at(12, 30))
+            .unwrap();
+
+        let task_names: Vec<String> = (0..day.timeblocks.len())
+            .map(|x| day.get_task_name(x as isize))
+            .collect();
+        assert_eq!(
+            task_names,
+            vec!["coding", "Break", "review", "Lunch", "review"]
+        );
+        assert_eq!(day.get_total_break_time_secs(), Some(105 * 60));
+        assert_eq!(day.get_number_of_breaks(), Some(2));
+        assert_eq!(
+            day.get_task_times_secs_and_num_blocks().get("review"),
+            Some(&(75 * 60, 2))
+        );
+    }
+
+    #[test]
+    fn test_insert_break_between_rejects_gap_in_break() {
+        let mut day: Day = example_day();
+        let result = day.insert_break_between("Lunch".to_string(), &at(10, 1), &at(10, 5));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_split_at_midnights_divides_time_between_dates() {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 22, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, "on-call".to_string(), 240, None);
+        day.start_break_at(
+            "Break".to_string(),
+            &Local.with_ymd_and_hms(2026, 10, 5, 23, 30, 0).unwrap(),
+        )
+        .unwrap();
+        day.start_new_block(
+            "on-call".to_string(),
+            &Local.with_ymd_and_hms(2026, 10, 6, 0, 30, 0).unwrap(),
+        )
+        .unwrap();
+        day.end_day_at(
+            &Local.with_ymd_and_hms(2026, 10, 6, 2, 0, 0).unwrap(),
+            false,
+        )
+        .unwrap();
+
+        let parts: Vec<(NaiveDate, Day)> = day.split_at_midnights();
+        assert_eq!(parts.len(), 2);
+        let (first_date, first_part) = &parts[0];
+        let (second_date, second_part) = &parts[1];
+        assert_eq!(*first_date, NaiveDate::from_ymd_opt(2026, 10, 5).unwrap());
+        assert_eq!(*second_date, NaiveDate::from_ymd_opt(2026, 10, 6).unwrap());
+        assert_eq!(first_part.get_time_done_secs(), Some(90 * 60));
+        assert_eq!(first_part.get_total_break_time_secs(), Some(30 * 60));
+        assert_eq!(first_part.get_time_to_do_secs(), 240 * 60);
+        assert_eq!(second_part.get_time_done_secs(), Some(90 * 60));
+        assert_eq!(second_part.get_total_break_time_secs(), Some(30 * 60));
+        assert_eq!(second_part.get_time_to_do_secs(), 0);
+    }
+
+    #[test]
+    fn test_secondary_track_is_not_counted_in_time_done() {
+        let mut day: Day = Day::new(&at(9, 0), "coding".to_string(), 480, None);
+        day.start_secondary_block("on-call".to_string(), &at(9, 30))
+            .unwrap();
+        assert_eq!(
+            day.get_current_secondary_task(),
+            Some("on-call".to_string())
+        );
+        day.start_new_block("review".to_string(), &at(10, 0))
+            .unwrap();
+        day.end_day_at(&at(11, 0), false).unwrap();
+
+        assert_eq!(day.get_current_secondary_task(), None);
+        assert_eq!(day.get_time_done_secs(), Some(2 * 3600));
+        assert_eq!(
+            day.get_secondary_task_times_secs_and_num_blocks()
+                .get("on-call"),
+            Some(&(90 * 60, 1))
+        );
+        let clipped: Day = day.clip_to(&at(10, 0), &at(12, 0)).unwrap();
+        assert_eq!(
+            clipped
+                .get_secondary_task_times_secs_and_num_blocks()
+                .get("on-call"),
+            Some(&(60 * 60, 1))
+        );
+    }
+
+    #[test]
+    fn test_focus_sessions_are_counted_per_task() {
+        let mut day: Day = Day::new(&at(9, 0), "coding".to_string(), 480, None);
+        day.complete_focus_block(&at(9, 0), "Break".to_string(), &at(9, 25))
+            .unwrap();
+        day.start_new_block("review".to_string(), &at(9, 30))
+            .unwrap();
+        day.complete_focus_block(&at(9, 30), "Break".to_string(), &at(9, 55))
+            .unwrap();
+        day.start_new_block("coding".to_string(), &at(10, 0))
+            .unwrap();
+        day.complete_focus_block(&at(10, 0), "Break".to_string(), &at(10, 25))
+            .unwrap();
+        let sessions: HashMap<String, u64> = day.get_completed_focus_sessions_per_task();
+        assert_eq!(sessions.get("coding"), Some(&2));
+        assert_eq!(sessions.get("review"), Some(&1));
+        assert_eq!(sessions.get("Break"), None);
+    }
+
+    #[test]
+    fn test_focus_block_must_still_be_current() {
+        let mut day: Day = Day::new(&at(9, 0), "coding".to_string(), 480, None);
+        day.start_new_block("review".to_string(), &at(9, 10))
+            .unwrap();
+        assert!(day
+            .complete_focus_block(&at(9, 0), "Break".to_string(), &at(9, 25))
+            .is_err());
+        day.start_break_at("Break".to_string(), &at(9, 20)).unwrap();
+        assert!(day
+            .complete_focus_block(&at(9, 10), "Break".to_string(), &at(9, 25))
+            .is_err());
+        assert_eq!(day.get_completed_focus_sessions_per_task().len(), 0);
+    }
+
+    #[test]
+    fn test_focus_sessions_survive_edits_to_the_blocks() {
+        let mut day: Day = Day::new(&at(9, 0), "coding".to_string(), 480, None);
+        day.start_new_block("review".to_string(), &at(10, 0))
+            .unwrap();
+        day.complete_focus_block(&at(10, 0), "Break".to_string(), &at(10, 25))
+            .unwrap();
+        day.end_day_at(&at(11, 0), false).unwrap();
+        // Splitting an earlier block moves the focus block along in `timeblocks`.
+        day.insert_break_between("Break".to_string(), &at(9, 20), &at(9, 30))
+            .unwrap();
+        assert_eq!(
+            day.get_completed_focus_sessions_per_task().get("review"),
+            Some(&1)
+        );
+        // Removing the focus block by hand, like `punch edit` could, just stops it counting.
+        day.timeblocks.remove(3);
+        day.rebuild_task_indices();
+        assert_eq!(day.get_completed_focus_sessions_per_task().len(), 0);
+        let clipped: Day = day.clip_to(&at(9, 0), &at(12, 0)).unwrap();
+        assert_eq!(clipped.get_completed_focus_sessions_per_task().len(), 0);
+    }
+
+    #[test]
+    fn test_dates_are_in_the_days_own_timezone() {
+        let utc = |hour: u32, minute: u32| {
+            Utc.with_ymd_and_hms(2026, 10, 5, hour, minute, 0)
+                .unwrap()
+                .with_timezone(&Local)
+        };
+        let mut day: Day = Day::new(&utc(14, 0), "coding".to_string(), 480, None);
+        day.timezone = Some("Asia/Tokyo".to_string());
+        let date = |day: u32| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        // 14:00 UTC is 23:00 in Tokyo, so it's stored under the 5th whatever the working timezone.
+        assert_eq!(day.get_date(), date(5));
+        assert_eq!(day.get_date_of(&utc(14, 59)), date(5));
+        assert_eq!(day.get_date_of(&utc(15, 0)), date(6));
+    }
+}
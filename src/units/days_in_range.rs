@@ -0,0 +1,93 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+
+use crate::storage::get_storage;
+use crate::units::day::Day;
+use crate::utils::dates_and_times::{convert_date_to_date_str, DateRange};
+
+pub struct DaysInRange {
+    pub days: Vec<(NaiveDate, Day)>,
+    pub days_not_there: Vec<String>,
+    pub days_not_ended: Vec<String>,
+}
+
+impl DaysInRange {
+    pub fn get_dates_included(&self) -> Vec<String> {
+        let mut dates: Vec<String> = self
+            .days
+            .iter()
+            .map(|(x, _)| convert_date_to_date_str(*x))
+            .collect();
+        dates.dedup();
+        return dates;
+    }
+}
+
+pub fn read_days_in_range(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    local_now: &DateTime<Local>,
+    split_at_midnight: bool,
+) -> Result<DaysInRange, String> {
+    // When splitting at midnight, the day before the range may spill over into it.
+    let first_file_date: NaiveDate = if split_at_midnight {
+        start_date - Duration::days(1)
+    } else {
+        start_date
+    };
+    let todays_date: NaiveDate = local_now.date_naive();
+    let mut stored_days: HashMap<NaiveDate, Day> = get_storage()
+        .read_days_between(first_file_date, end_date)
+        .into_iter()
+        .collect();
+    let mut days: Vec<(NaiveDate, Day)> = Vec::new();
+    let mut dates_without_files: Vec<NaiveDate> = Vec::new();
+    let mut days_not_ended: Vec<String> = Vec::new();
+    for local_date in DateRange(first_file_date, end_date) {
+        let in_range: bool = local_date >= start_date;
+        let this_date_str: String = convert_date_to_date_str(local_date);
+        let this_day_opt: Option<Day> = stored_days.remove(&local_date);
+
+        if this_day_opt.is_none() {
+            if in_range {
+                dates_without_files.push(local_date);
+            }
+            continue;
+        }
+        let mut this_day: Day = this_day_opt.expect("Already handled missing day!");
+        if !this_day.has_ended() && (local_date == todays_date) {
+            if let Err(err_msg) = this_day.end_day_at(local_now, false) {
+                return Err(format!("Failed to end today: {err_msg}"));
+            }
+        } else if !this_day.has_ended() {
+            if in_range {
+                days_not_ended.push(this_date_str.clone());
+            }
+            continue;
+        }
+
+        if split_at_midnight {
+            days.extend(
+                this_day
+                    .split_at_midnights()
+                    .into_iter()
+                    .filter(|(x, _)| (*x >= start_date) && (*x <= end_date)),
+            );
+        } else {
+            days.push((local_date, this_day));
+        }
+    }
+    days.sort_by_key(|(x, y)| (*x, y.get_day_start().as_dt()));
+
+    let dates_with_days: HashSet<NaiveDate> = days.iter().map(|(x, _)| *x).collect();
+    let days_not_there: Vec<String> = dates_without_files
+        .into_iter()
+        .filter(|x| !dates_with_days.contains(x))
+        .map(convert_date_to_date_str)
+        .collect();
+    return Ok(DaysInRange {
+        days: days,
+        days_not_there: days_not_there,
+        days_not_ended: days_not_ended,
+    });
+}
@@ -153,3 +153,26 @@ impl Interval {
         self.end = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("2026-03-29 00:30:00 +0000", "2026-03-29 03:30:00 +0100", 2 * 60 * 60 ; "over the spring DST change")]
+    #[test_case("2026-10-25 00:30:00 +0100", "2026-10-25 03:30:00 +0000", 4 * 60 * 60 ; "over the autumn DST change")]
+    #[test_case("2026-10-05 09:00:00 +0100", "2026-10-05 17:00:00 +0100", 8 * 60 * 60 ; "no DST change")]
+    #[test_case("2026-10-05 09:00:00 +0100", "2026-10-05 11:00:00 +0200", 60 * 60 ; "travelling east")]
+    fn test_get_length_secs(start: &str, end: &str, expected: i64) {
+        let yaml_str: String = format!("start: {}\nend: {}\n", start, end);
+        let interval: Interval = Interval::from_string(&yaml_str);
+        assert_eq!(interval.get_length_secs(), Some(expected));
+    }
+
+    #[test]
+    fn test_get_length_secs_without_end() {
+        let interval: Interval =
+            Interval::from_string(&"start: 2026-10-05 09:00:00 +0100\nend: null\n".to_string());
+        assert_eq!(interval.get_length_secs(), None);
+    }
+}
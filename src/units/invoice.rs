@@ -0,0 +1,346 @@
+use chrono::NaiveDate;
+use prettytable::{row, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::units::interval::DATE_FMT;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TaskRate {
+    task_prefix: Option<String>,
+    tag: Option<String>,
+    rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Billing {
+    currency: Option<String>,
+    default_rate: Option<f64>,
+    rates: Option<Vec<TaskRate>>,
+}
+
+impl Billing {
+    pub fn get_currency(&self) -> String {
+        return self.currency.clone().unwrap_or_default();
+    }
+
+    /// A tag (`#tag` in the task name) beats a task prefix, which beats the profile's default rate.
+    /// The longest matching prefix wins.
+    pub fn find_rate(&self, task_name: &str) -> Option<f64> {
+        let rates: Vec<TaskRate> = self.rates.clone().unwrap_or_default();
+        let tags: Vec<&str> = get_tags(task_name);
+        let tag_rate: Option<f64> = rates
+            .iter()
+            .find(|x| {
+                x.tag
+                    .as_ref()
+                    .is_some_and(|tag| tags.contains(&tag.as_str()))
+            })
+            .map(|x| x.rate);
+        if tag_rate.is_some() {
+            return tag_rate;
+        }
+        let prefix_rate: Option<f64> = rates
+            .iter()
+            .filter(|x| {
+                x.task_prefix
+                    .as_ref()
+                    .is_some_and(|prefix| task_name.starts_with(prefix.as_str()))
+            })
+            .max_by_key(|x| x.task_prefix.as_ref().map(|prefix| prefix.len()))
+            .map(|x| x.rate);
+        return prefix_rate.or(self.default_rate);
+    }
+}
+
+fn get_tags(task_name: &str) -> Vec<&str> {
+    return task_name
+        .split_whitespace()
+        .filter_map(|x| x.strip_prefix('#'))
+        .collect();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub task_name: String,
+    pub hours: f64,
+    pub rate: f64,
+}
+
+impl InvoiceLine {
+    /// Each line is rounded to the cent on its own, so the lines always add up to the subtotal.
+    pub fn get_amount_cents(&self) -> i64 {
+        return (self.hours * self.rate * 100.0).round() as i64;
+    }
+
+    pub fn get_amount(&self) -> f64 {
+        return self.get_amount_cents() as f64 / 100.0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvoiceFormat {
+    Text,
+    Csv,
+    Markdown,
+    Html,
+}
+
+impl InvoiceFormat {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        return match name.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "'{}' isn't an invoice format. Try one of: text, csv, markdown, html.",
+                other
+            )),
+        };
+    }
+}
+
+pub struct Invoice {
+    pub client: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+    pub unbilled_tasks: Vec<String>,
+}
+
+impl Invoice {
+    /// Builds the line items from per-task times, leaving out tasks without a rate.
+    pub fn new(
+        client: String,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        billing: &Billing,
+        task_times_secs: &HashMap<String, i64>,
+    ) -> Self {
+        let mut task_names: Vec<&String> = task_times_secs.keys().collect();
+        task_names.sort();
+        let mut lines: Vec<InvoiceLine> = Vec::new();
+        let mut unbilled_tasks: Vec<String> = Vec::new();
+        for task_name in task_names {
+            let secs: i64 = task_times_secs[task_name];
+            if secs == 0 {
+                continue;
+            }
+            match billing.find_rate(task_name) {
+                Some(rate) => lines.push(InvoiceLine {
+                    task_name: task_name.clone(),
+                    hours: secs as f64 / 3600.0,
+                    rate: rate,
+                }),
+                None => unbilled_tasks.push(task_name.clone()),
+            }
+        }
+        return Self {
+            client: client,
+            start_date: start_date,
+            end_date: end_date,
+            currency: billing.get_currency(),
+            lines: lines,
+            unbilled_tasks: unbilled_tasks,
+        };
+    }
+
+    pub fn get_subtotal(&self) -> f64 {
+        let subtotal_cents: i64 = self.lines.iter().map(|x| x.get_amount_cents()).sum();
+        return subtotal_cents as f64 / 100.0;
+    }
+
+    pub fn get_total_hours(&self) -> f64 {
+        return self.lines.iter().fold(0.0, |total, x| total + x.hours);
+    }
+
+    fn get_title(&self) -> String {
+        return format!(
+            "Invoice for {}: {} to {}",
+            self.client,
+            self.start_date.format(DATE_FMT),
+            self.end_date.format(DATE_FMT)
+        );
+    }
+
+    fn render_money(&self, amount: f64) -> String {
+        return match self.currency.len() {
+            0 => format!("{:.2}", amount),
+            _ => format!("{:.2} {}", amount, self.currency),
+        };
+    }
+
+    pub fn render(&self, format: InvoiceFormat) -> String {
+        return match format {
+            InvoiceFormat::Text => self.render_text(),
+            InvoiceFormat::Csv => self.render_csv(),
+            InvoiceFormat::Markdown => self.render_markdown(),
+            InvoiceFormat::Html => self.render_html(),
+        };
+    }
+
+    fn render_text(&self) -> String {
+        let mut table: Table = Table::new();
+        table.set_titles(row!["Task", "Hours", "Rate", "Amount"]);
+        for line in self.lines.iter() {
+            table.add_row(row![
+                &line.task_name,
+                format!("{:.2}", line.hours),
+                self.render_money(line.rate),
+                self.render_money(line.get_amount())
+            ]);
+        }
+        table.add_row(row![
+            "Subtotal",
+            format!("{:.2}", self.get_total_hours()),
+            "",
+            self.render_money(self.get_subtotal())
+        ]);
+        return format!("{}\n{}", self.get_title(), table);
+    }
+
+    fn render_csv(&self) -> String {
+        let mut csv_str: String = "task,hours,rate,amount,currency\n".to_string();
+        for line in self.lines.iter() {
+            csv_str += &format!(
+                "{},{:.2},{:.2},{:.2},{}\n",
+                escape_csv(&line.task_name),
+                line.hours,
+                line.rate,
+                line.get_amount(),
+                escape_csv(&self.currency)
+            );
+        }
+        csv_str += &format!(
+            "Subtotal,{:.2},,{:.2},{}\n",
+            self.get_total_hours(),
+            self.get_subtotal(),
+            escape_csv(&self.currency)
+        );
+        return csv_str;
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut markdown_str: String = format!("# {}\n\n", self.get_title());
+        markdown_str += "| Task | Hours | Rate | Amount |\n";
+        markdown_str += "| --- | ---: | ---: | ---: |\n";
+        for line in self.lines.iter() {
+            markdown_str += &format!(
+                "| {} | {:.2} | {} | {} |\n",
+                line.task_name.replace('|', "\\|"),
+                line.hours,
+                self.render_money(line.rate),
+                self.render_money(line.get_amount())
+            );
+        }
+        markdown_str += &format!(
+            "| **Subtotal** | **{:.2}** | | **{}** |\n",
+            self.get_total_hours(),
+            self.render_money(self.get_subtotal())
+        );
+        return markdown_str;
+    }
+
+    fn render_html(&self) -> String {
+        let mut html_str: String = format!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{}</title></head>\n<body>\n<h1>{}</h1>\n<table>\n",
+            escape_html(&self.get_title()),
+            escape_html(&self.get_title())
+        );
+        html_str += "<tr><th>Task</th><th>Hours</th><th>Rate</th><th>Amount</th></tr>\n";
+        for line in self.lines.iter() {
+            html_str += &format!(
+                "<tr><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&line.task_name),
+                line.hours,
+                escape_html(&self.render_money(line.rate)),
+                escape_html(&self.render_money(line.get_amount()))
+            );
+        }
+        html_str += &format!(
+            "<tr><th>Subtotal</th><th>{:.2}</th><th></th><th>{}</th></tr>\n",
+            self.get_total_hours(),
+            escape_html(&self.render_money(self.get_subtotal()))
+        );
+        html_str += "</table>\n</body>\n</html>\n";
+        return html_str;
+    }
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    return field.to_string();
+}
+
+pub fn escape_html(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_billing() -> Billing {
+        return serde_yaml::from_str(
+            "currency: EUR\ndefault_rate: 80\nrates:\n  - task_prefix: ACME-\n    rate: 100\n  - task_prefix: ACME-OPS\n    rate: 110\n  - tag: urgent\n    rate: 150\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_rate() {
+        let billing: Billing = example_billing();
+        assert_eq!(billing.find_rate("ACME-12 review"), Some(100.0));
+        assert_eq!(billing.find_rate("ACME-OPS deploy"), Some(110.0));
+        assert_eq!(billing.find_rate("ACME-12 fix #urgent"), Some(150.0));
+        assert_eq!(billing.find_rate("admin"), Some(80.0));
+    }
+
+    #[test]
+    fn test_invoice_lines_and_csv() {
+        let mut billing: Billing = example_billing();
+        billing.default_rate = None;
+        let task_times: HashMap<String, i64> = HashMap::from([
+            ("ACME-12, review".to_string(), 5400),
+            ("admin".to_string(), 600),
+        ]);
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        let invoice: Invoice = Invoice::new("acme".to_string(), date, date, &billing, &task_times);
+        assert_eq!(invoice.unbilled_tasks, vec!["admin".to_string()]);
+        assert_eq!(invoice.get_subtotal(), 150.0);
+        assert_eq!(
+            invoice.render(InvoiceFormat::Csv),
+            "task,hours,rate,amount,currency\n\"ACME-12, review\",1.50,100.00,150.00,EUR\nSubtotal,1.50,,150.00,EUR\n"
+        );
+    }
+
+    #[test]
+    fn test_subtotal_adds_up_the_rounded_lines() {
+        let billing: Billing = example_billing();
+        let task_times: HashMap<String, i64> = HashMap::from([
+            ("ACME-1".to_string(), 1200),
+            ("ACME-2".to_string(), 1200),
+            ("ACME-3".to_string(), 1200),
+        ]);
+        let date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        let invoice: Invoice = Invoice::new("acme".to_string(), date, date, &billing, &task_times);
+        assert_eq!(invoice.lines[0].get_amount(), 33.33);
+        assert_eq!(invoice.get_subtotal(), 99.99);
+        assert!(invoice
+            .render(InvoiceFormat::Csv)
+            .ends_with("Subtotal,1.00,,99.99,EUR\n"));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<b>R&D</b>"), "&lt;b&gt;R&amp;D&lt;/b&gt;");
+    }
+}
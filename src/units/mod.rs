@@ -1,4 +1,12 @@
 pub mod aggregate_day;
+pub mod break_policy;
+pub mod comparison;
 pub mod components;
 pub mod day;
+pub mod days_in_range;
 pub mod interval;
+pub mod invoice;
+pub mod reminders;
+pub mod rounding;
+pub mod task_table;
+pub mod working_time_limits;
@@ -0,0 +1,260 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::units::day::Day;
+use crate::units::interval::DATETIME_FMT;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReminderEvent {
+    TimeToDoReached,
+    BreakOverdue,
+    BreakTooLong,
+    ForgotToPunchOut,
+}
+
+impl ReminderEvent {
+    pub const ALL: [ReminderEvent; 4] = [
+        Self::TimeToDoReached,
+        Self::BreakOverdue,
+        Self::BreakTooLong,
+        Self::ForgotToPunchOut,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Self::TimeToDoReached => "time-to-do-reached",
+            Self::BreakOverdue => "break-overdue",
+            Self::BreakTooLong => "break-too-long",
+            Self::ForgotToPunchOut => "forgot-to-punch-out",
+        };
+    }
+}
+
+/// Which hook to run for each reminder, and when breaks count as overdue or too long.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReminderConfig {
+    #[serde(default)]
+    hooks: BTreeMap<String, String>,
+    break_overdue_minutes: Option<u64>,
+    max_break_minutes: Option<u64>,
+}
+
+impl ReminderConfig {
+    pub fn get_hook(&self, event: ReminderEvent) -> Option<&String> {
+        return self.hooks.get(event.as_str());
+    }
+
+    pub fn check_hook_names(&self) -> Result<(), String> {
+        for event_name in self.hooks.keys() {
+            if !ReminderEvent::ALL.iter().any(|x| x.as_str() == event_name) {
+                let allowed: Vec<&str> = ReminderEvent::ALL.iter().map(|x| x.as_str()).collect();
+                return Err(format!(
+                    "'{}' isn't a reminder. Hooks can be set for: {}.",
+                    event_name,
+                    allowed.join(", ")
+                ));
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub event: ReminderEvent,
+    /// Identifies this occurrence, so the same reminder isn't sent twice.
+    pub key: String,
+    pub message: String,
+}
+
+/// When each reminder is due, with any thresholds that aren't set turning that reminder off.
+pub struct ReminderThresholds {
+    pub break_overdue_secs: Option<i64>,
+    pub max_break_secs: Option<i64>,
+    pub forgot_to_punch_out_secs: i64,
+}
+
+impl ReminderThresholds {
+    /// Breaks become overdue after the break policy's longest stretch if there's no threshold of their own.
+    pub fn new(
+        reminder_config: &ReminderConfig,
+        break_policy_max_minutes: Option<u64>,
+        stale_day_hours: u64,
+    ) -> Self {
+        return Self {
+            break_overdue_secs: reminder_config
+                .break_overdue_minutes
+                .or(break_policy_max_minutes)
+                .map(|x| (x * 60) as i64),
+            max_break_secs: reminder_config.max_break_minutes.map(|x| (x * 60) as i64),
+            forgot_to_punch_out_secs: (stale_day_hours * 60 * 60) as i64,
+        };
+    }
+}
+
+/// The reminders due for a day that's still going at `now`.
+pub fn find_due_reminders(
+    day: &Day,
+    now: &DateTime<Local>,
+    thresholds: &ReminderThresholds,
+    show_times_in_hours: bool,
+) -> Vec<Reminder> {
+    let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+    let mut reminders: Vec<Reminder> = Vec::new();
+    if day.has_ended() {
+        return reminders;
+    }
+
+    let mut day_so_far: Day = day.clone();
+    day_so_far
+        .end_day_at(now, false)
+        .expect("The day hasn't ended yet");
+    let time_to_do_secs: i64 = day.get_time_to_do_secs() as i64;
+    if let Some(done_at) = day_so_far.find_time_when_amount_of_time_was_done(time_to_do_secs) {
+        if done_at.as_dt() <= *now {
+            reminders.push(Reminder {
+                event: ReminderEvent::TimeToDoReached,
+                key: ReminderEvent::TimeToDoReached.as_str().to_string(),
+                message: format!(
+                    "You've done your time for the day ({}) as of {}.",
+                    render(time_to_do_secs),
+                    done_at.as_dt().format("%H:%M")
+                ),
+            });
+        }
+    }
+
+    let latest_block_start: DateTime<Local> = day.get_latest_block_start().as_dt();
+    if day.on_break {
+        let break_secs: i64 = (*now - latest_block_start).num_seconds();
+        if let Some(max_break_secs) = thresholds.max_break_secs {
+            if break_secs > max_break_secs {
+                reminders.push(Reminder {
+                    event: ReminderEvent::BreakTooLong,
+                    key: format!(
+                        "{} {}",
+                        ReminderEvent::BreakTooLong.as_str(),
+                        latest_block_start.format(DATETIME_FMT)
+                    ),
+                    message: format!(
+                        "Your break has lasted {}, longer than the planned {}.",
+                        render(break_secs),
+                        render(max_break_secs)
+                    ),
+                });
+            }
+        }
+    } else if let Some(break_overdue_secs) = thresholds.break_overdue_secs {
+        let last_break_end_opt: Option<DateTime<Local>> = (0..day.timeblocks.len())
+            .rev()
+            .find(|x| day.is_break(*x))
+            .and_then(|x| day.timeblocks[x].get_end())
+            .map(|x| x.as_dt());
+        let stretch_start: DateTime<Local> =
+            last_break_end_opt.unwrap_or(day.get_day_start().as_dt());
+        let stretch_secs: i64 = (*now - stretch_start).num_seconds();
+        if stretch_secs > break_overdue_secs {
+            reminders.push(Reminder {
+                event: ReminderEvent::BreakOverdue,
+                key: format!(
+                    "{} {}",
+                    ReminderEvent::BreakOverdue.as_str(),
+                    stretch_start.format(DATETIME_FMT)
+                ),
+                message: format!(
+                    "You've worked {} without a break, so a break is overdue.",
+                    render(stretch_secs)
+                ),
+            });
+        }
+    }
+
+    let day_start: DateTime<Local> = day.get_day_start().as_dt();
+    let punched_in_secs: i64 = (*now - day_start).num_seconds();
+    if punched_in_secs > thresholds.forgot_to_punch_out_secs {
+        reminders.push(Reminder {
+            event: ReminderEvent::ForgotToPunchOut,
+            key: ReminderEvent::ForgotToPunchOut.as_str().to_string(),
+            message: format!(
+                "You've been punched in since {} ({}). Did you forget to punch out?",
+                day_start.format("%Y-%m-%d %H:%M"),
+                render(punched_in_secs)
+            ),
+        });
+    }
+    return reminders;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn thresholds() -> ReminderThresholds {
+        return ReminderThresholds {
+            break_overdue_secs: Some(4 * 60 * 60),
+            max_break_secs: Some(60 * 60),
+            forgot_to_punch_out_secs: 16 * 60 * 60,
+        };
+    }
+
+    fn start() -> DateTime<Local> {
+        return Local.with_ymd_and_hms(2026, 10, 5, 8, 0, 0).unwrap();
+    }
+
+    fn events_at(day: &Day, hours_after_start: i64) -> Vec<ReminderEvent> {
+        let now: DateTime<Local> = start() + Duration::hours(hours_after_start);
+        return find_due_reminders(day, &now, &thresholds(), true)
+            .into_iter()
+            .map(|x| x.event)
+            .collect();
+    }
+
+    #[test]
+    fn test_break_overdue_then_time_to_do_then_forgot_to_punch_out() {
+        let day: Day = Day::new(&start(), "coding".to_string(), 480, None);
+        assert_eq!(events_at(&day, 3), vec![]);
+        assert_eq!(events_at(&day, 5), vec![ReminderEvent::BreakOverdue]);
+        assert_eq!(
+            events_at(&day, 9),
+            vec![ReminderEvent::TimeToDoReached, ReminderEvent::BreakOverdue]
+        );
+        assert_eq!(
+            events_at(&day, 17),
+            vec![
+                ReminderEvent::TimeToDoReached,
+                ReminderEvent::BreakOverdue,
+                ReminderEvent::ForgotToPunchOut
+            ]
+        );
+    }
+
+    #[test]
+    fn test_break_too_long_and_stretch_after_break() {
+        let mut day: Day = Day::new(&start(), "coding".to_string(), 480, None);
+        day.start_break_at("Break".to_string(), &(start() + Duration::hours(3)))
+            .unwrap();
+        assert_eq!(events_at(&day, 4), vec![]);
+        let reminders: Vec<Reminder> =
+            find_due_reminders(&day, &(start() + Duration::hours(5)), &thresholds(), true);
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(
+            reminders[0].message,
+            "Your break has lasted 2h 0m 0s, longer than the planned 1h 0m 0s."
+        );
+        day.start_new_block("coding".to_string(), &(start() + Duration::hours(5)))
+            .unwrap();
+        // The stretch restarts after the break, so it isn't overdue yet.
+        assert_eq!(events_at(&day, 8), vec![]);
+    }
+
+    #[test]
+    fn test_ended_day_has_no_reminders() {
+        let mut day: Day = Day::new(&start(), "coding".to_string(), 480, None);
+        day.end_day_at(&(start() + Duration::hours(10)), false)
+            .unwrap();
+        assert_eq!(events_at(&day, 20), vec![]);
+    }
+}
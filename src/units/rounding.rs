@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundingScope {
+    Block,
+    TaskDay,
+    Invoice,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoundingPolicy {
+    increment_minutes: u64,
+    mode: RoundingMode,
+    scope: RoundingScope,
+}
+
+impl RoundingPolicy {
+    pub fn get_scope(&self) -> RoundingScope {
+        return self.scope;
+    }
+
+    pub fn round_secs(&self, secs: i64) -> i64 {
+        let increment: i64 = (self.increment_minutes * 60) as i64;
+        if increment == 0 {
+            return secs;
+        }
+        let remainder: i64 = secs.rem_euclid(increment);
+        if remainder == 0 {
+            return secs;
+        }
+        let rounded_down: i64 = secs - remainder;
+        return match self.mode {
+            RoundingMode::Up => rounded_down + increment,
+            RoundingMode::Down => rounded_down,
+            RoundingMode::Nearest if remainder * 2 >= increment => rounded_down + increment,
+            RoundingMode::Nearest => rounded_down,
+        };
+    }
+
+    pub fn describe(&self) -> String {
+        let mode_str: &str = match self.mode {
+            RoundingMode::Up => "up",
+            RoundingMode::Down => "down",
+            RoundingMode::Nearest => "to the nearest",
+        };
+        let scope_str: &str = match self.scope {
+            RoundingScope::Block => "per block",
+            RoundingScope::TaskDay => "per task per day",
+            RoundingScope::Invoice => "per task over the whole period",
+        };
+        return format!(
+            "{} minute increments, rounded {} {}",
+            self.increment_minutes, mode_str, scope_str
+        );
+    }
+}
+
+/// Lists the rounded time per task next to the raw time and the difference, so nothing is hidden.
+pub fn render_rounded_task_times(
+    policy: &RoundingPolicy,
+    task_order: &Vec<String>,
+    raw_times: &HashMap<String, i64>,
+    rounded_times: &HashMap<String, i64>,
+    show_times_in_hours: bool,
+) -> String {
+    let render_difference = |difference: i64| -> String {
+        let sign: &str = if difference > 0 { "+" } else { "" };
+        return sign.to_string() + &render_seconds_human_readable(difference, show_times_in_hours);
+    };
+    let mut summary_str: String = format!("Rounded task times ({}):", policy.describe());
+    let (mut total_raw, mut total_rounded): (i64, i64) = (0, 0);
+    for task_name in task_order.iter() {
+        let raw: i64 = *raw_times.get(task_name).unwrap_or(&0);
+        let rounded: i64 = *rounded_times.get(task_name).unwrap_or(&raw);
+        total_raw += raw;
+        total_rounded += rounded;
+        summary_str += &format!(
+            "\n\t{}: {} (raw {}, {})",
+            task_name,
+            render_seconds_human_readable(rounded, show_times_in_hours),
+            render_seconds_human_readable(raw, show_times_in_hours),
+            render_difference(rounded - raw)
+        );
+    }
+    summary_str += &format!(
+        "\n\tTotal: {} (raw {}, {})",
+        render_seconds_human_readable(total_rounded, show_times_in_hours),
+        render_seconds_human_readable(total_raw, show_times_in_hours),
+        render_difference(total_rounded - total_raw)
+    );
+    return summary_str;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(15, RoundingMode::Up, 61, 900 ; "up to first increment")]
+    #[test_case(15, RoundingMode::Up, 900, 900 ; "up on an increment")]
+    #[test_case(15, RoundingMode::Down, 1799, 900 ; "down")]
+    #[test_case(6, RoundingMode::Nearest, 179, 0 ; "nearest below half")]
+    #[test_case(6, RoundingMode::Nearest, 180, 360 ; "nearest at half")]
+    #[test_case(0, RoundingMode::Up, 61, 61 ; "no increment")]
+    fn test_round_secs(increment_minutes: u64, mode: RoundingMode, secs: i64, expected: i64) {
+        let policy: RoundingPolicy = RoundingPolicy {
+            increment_minutes: increment_minutes,
+            mode: mode,
+            scope: RoundingScope::Block,
+        };
+        assert_eq!(policy.round_secs(secs), expected);
+    }
+
+    #[test]
+    fn test_policy_from_config_yaml() {
+        let policy: RoundingPolicy =
+            serde_yaml::from_str("increment_minutes: 6\nmode: nearest\nscope: task-day\n").unwrap();
+        assert_eq!(policy.increment_minutes, 6);
+        assert_eq!(policy.mode, RoundingMode::Nearest);
+        assert_eq!(policy.scope, RoundingScope::TaskDay);
+    }
+}
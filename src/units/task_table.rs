@@ -0,0 +1,195 @@
+use regex::Regex;
+
+use crate::user_interaction::convert_input::convert_input_to_seconds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TaskSort {
+    #[default]
+    FirstSeen,
+    Time,
+    Blocks,
+    Name,
+}
+
+impl TaskSort {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        return match name.to_lowercase().as_str() {
+            "first-seen" => Ok(Self::FirstSeen),
+            "time" => Ok(Self::Time),
+            "blocks" => Ok(Self::Blocks),
+            "name" => Ok(Self::Name),
+            other => Err(format!(
+                "'{}' isn't a way to sort tasks. Try one of: time, blocks, name, first-seen.",
+                other
+            )),
+        };
+    }
+}
+
+/// A task's totals over whatever a table covers, e.g. a day or a range of days.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRow {
+    pub task_name: String,
+    pub secs: i64,
+    pub blocks: u64,
+    pub is_break: bool,
+}
+
+/// Which tasks to show in summaries and tables, and in what order.
+#[derive(Debug, Clone, Default)]
+pub struct TaskTableOptions {
+    sort: TaskSort,
+    top: Option<usize>,
+    min_secs: Option<i64>,
+    task_regex: Option<Regex>,
+    exclude_breaks: bool,
+}
+
+impl TaskTableOptions {
+    /// Filters and sorts rows given in the order the tasks were first seen.
+    pub fn select(&self, rows: Vec<TaskRow>) -> Vec<TaskRow> {
+        let mut selected: Vec<TaskRow> = rows
+            .into_iter()
+            .filter(|x| !(self.exclude_breaks && x.is_break))
+            .filter(|x| self.min_secs.is_none_or(|min_secs| x.secs >= min_secs))
+            .filter(|x| {
+                self.task_regex
+                    .as_ref()
+                    .is_none_or(|task_regex| task_regex.is_match(&x.task_name))
+            })
+            .collect();
+        // The sorts are stable, so ties stay in the order they were first seen.
+        match self.sort {
+            TaskSort::FirstSeen => (),
+            TaskSort::Time => selected.sort_by_key(|x| -x.secs),
+            TaskSort::Blocks => selected.sort_by_key(|x| -(x.blocks as i64)),
+            TaskSort::Name => selected.sort_by(|a, b| a.task_name.cmp(&b.task_name)),
+        }
+        if let Some(top) = self.top {
+            selected.truncate(top);
+        }
+        return selected;
+    }
+}
+
+/// Takes the task table flags out of the args, leaving the rest for the command.
+pub fn take_task_table_options(
+    args: Vec<String>,
+) -> Result<(TaskTableOptions, Vec<String>), String> {
+    let mut options: TaskTableOptions = TaskTableOptions::default();
+    let mut remaining_args: Vec<String> = Vec::new();
+    let mut args_iter = args.into_iter();
+    while let Some(arg) = args_iter.next() {
+        let mut get_value = || -> Result<String, String> {
+            return args_iter
+                .next()
+                .ok_or(format!("'{}' needs a value after it.", arg));
+        };
+        match arg.as_str() {
+            "--sort" => options.sort = TaskSort::from_string(&get_value()?)?,
+            "--top" => {
+                let value: String = get_value()?;
+                options.top =
+                    Some(value.parse::<usize>().map_err(|_| {
+                        format!("'--top' needs a number of tasks. Got: '{}'", value)
+                    })?);
+            }
+            "--min" => options.min_secs = Some(convert_input_to_seconds(&get_value()?)?),
+            "--task" => {
+                let value: String = get_value()?;
+                options.task_regex = Some(
+                    Regex::new(&value)
+                        .map_err(|err| format!("'{}' isn't a valid regex: {}", value, err))?,
+                );
+            }
+            "--exclude-breaks" => options.exclude_breaks = true,
+            _ => remaining_args.push(arg),
+        }
+    }
+    return Ok((options, remaining_args));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<TaskRow> {
+        return [
+            ("coding", 3 * 3600, 2, false),
+            ("lunch", 1800, 1, true),
+            ("review", 3600, 3, false),
+            ("admin", 600, 1, false),
+        ]
+        .map(|(task_name, secs, blocks, is_break)| TaskRow {
+            task_name: task_name.to_string(),
+            secs: secs,
+            blocks: blocks,
+            is_break: is_break,
+        })
+        .to_vec();
+    }
+
+    fn select(args: &[&str]) -> Vec<String> {
+        let (options, remaining_args) =
+            take_task_table_options(args.iter().map(|x| x.to_string()).collect()).unwrap();
+        assert_eq!(remaining_args, vec!["2026-10-01".to_string()]);
+        return options
+            .select(rows())
+            .into_iter()
+            .map(|x| x.task_name)
+            .collect();
+    }
+
+    #[test]
+    fn test_default_keeps_first_seen_order() {
+        assert_eq!(
+            select(&["2026-10-01"]),
+            vec!["coding", "lunch", "review", "admin"]
+        );
+    }
+
+    #[test]
+    fn test_sorting() {
+        assert_eq!(
+            select(&["--sort", "time", "2026-10-01"]),
+            vec!["coding", "review", "lunch", "admin"]
+        );
+        assert_eq!(
+            select(&["2026-10-01", "--sort", "blocks"]),
+            vec!["review", "coding", "lunch", "admin"]
+        );
+        assert_eq!(
+            select(&["--sort", "name", "2026-10-01"]),
+            vec!["admin", "coding", "lunch", "review"]
+        );
+    }
+
+    #[test]
+    fn test_filtering() {
+        assert_eq!(
+            select(&["--exclude-breaks", "--min", "15m", "2026-10-01"]),
+            vec!["coding", "review"]
+        );
+        assert_eq!(
+            select(&["--task", "^(co|ad)", "2026-10-01"]),
+            vec!["coding", "admin"]
+        );
+        assert_eq!(
+            select(&["--sort", "time", "--top", "2", "2026-10-01"]),
+            vec!["coding", "review"]
+        );
+    }
+
+    #[test]
+    fn test_bad_options() {
+        for args in [
+            vec!["--sort", "colour"],
+            vec!["--top", "many"],
+            vec!["--task", "("],
+            vec!["--min"],
+        ] {
+            let args: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+            assert!(take_task_table_options(args).is_err());
+        }
+    }
+}
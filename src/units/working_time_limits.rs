@@ -0,0 +1,430 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::units::day::{get_start_of_date, Day};
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+use crate::utils::dates_and_times::get_week_range;
+
+const NIGHT_START_DEFAULT: &str = "23:00";
+const NIGHT_END_DEFAULT: &str = "06:00";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WorkingTimeLimits {
+    max_daily_minutes: Option<u64>,
+    max_weekly_minutes: Option<u64>,
+    min_rest_minutes: Option<u64>,
+    max_weekend_minutes: Option<u64>,
+    max_night_minutes: Option<u64>,
+    night_start: Option<String>,
+    night_end: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkingTimeViolation {
+    DailyMaximum {
+        date: NaiveDate,
+        work_secs: i64,
+        max_secs: i64,
+    },
+    WeeklyMaximum {
+        week_start: NaiveDate,
+        work_secs: i64,
+        max_secs: i64,
+    },
+    RestPeriod {
+        previous_end: DateTime<Local>,
+        next_start: DateTime<Local>,
+        min_secs: i64,
+    },
+    WeekendWork {
+        date: NaiveDate,
+        work_secs: i64,
+        max_secs: i64,
+    },
+    NightWork {
+        date: NaiveDate,
+        work_secs: i64,
+        max_secs: i64,
+    },
+}
+
+impl WorkingTimeViolation {
+    /// The date the violation is listed under, which is the first date of the week for weekly ones.
+    pub fn get_date(&self) -> NaiveDate {
+        return match self {
+            Self::DailyMaximum { date, .. } => *date,
+            Self::WeeklyMaximum { week_start, .. } => *week_start,
+            Self::RestPeriod { next_start, .. } => next_start.date_naive(),
+            Self::WeekendWork { date, .. } => *date,
+            Self::NightWork { date, .. } => *date,
+        };
+    }
+
+    fn get_last_date(&self) -> NaiveDate {
+        return match self {
+            Self::WeeklyMaximum { week_start, .. } => *week_start + Duration::days(6),
+            _ => self.get_date(),
+        };
+    }
+
+    pub fn render(&self, show_times_in_hours: bool) -> String {
+        let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+        return match self {
+            Self::DailyMaximum {
+                work_secs,
+                max_secs,
+                ..
+            } => format!(
+                "Worked {} in the day, over the daily maximum of {}.",
+                render(*work_secs),
+                render(*max_secs)
+            ),
+            Self::WeeklyMaximum {
+                week_start,
+                work_secs,
+                max_secs,
+            } => format!(
+                "Worked {} in the week starting {}, over the weekly maximum of {}.",
+                render(*work_secs),
+                week_start.format("%Y-%m-%d"),
+                render(*max_secs)
+            ),
+            Self::RestPeriod {
+                previous_end,
+                next_start,
+                min_secs,
+            } => format!(
+                "Only rested {} between punching out at {} and starting again at {}, under the minimum of {}.",
+                render((*next_start - *previous_end).num_seconds()),
+                previous_end.format("%Y-%m-%d %H:%M"),
+                next_start.format("%Y-%m-%d %H:%M"),
+                render(*min_secs)
+            ),
+            Self::WeekendWork {
+                work_secs,
+                max_secs,
+                ..
+            } => format!(
+                "Worked {} on the weekend, over the maximum of {}.",
+                render(*work_secs),
+                render(*max_secs)
+            ),
+            Self::NightWork {
+                work_secs,
+                max_secs,
+                ..
+            } => format!(
+                "Worked {} during the night, over the maximum of {}.",
+                render(*work_secs),
+                render(*max_secs)
+            ),
+        };
+    }
+}
+
+fn parse_time_of_day_secs(time_str: &str, field: &str) -> Result<i64, String> {
+    return match NaiveTime::parse_from_str(time_str, "%H:%M") {
+        Ok(time) => Ok(time.num_seconds_from_midnight() as i64),
+        Err(_) => Err(format!(
+            "'{}' in '{}' isn't a time of day. Use 'HH:MM', e.g. '23:00'.",
+            time_str, field
+        )),
+    };
+}
+
+fn get_overlap_secs(
+    intervals: &[(DateTime<Local>, DateTime<Local>)],
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> i64 {
+    return intervals
+        .iter()
+        .map(|(interval_start, interval_end)| {
+            let overlap_start: DateTime<Local> = (*interval_start).max(start);
+            let overlap_end: DateTime<Local> = (*interval_end).min(end);
+            (overlap_end - overlap_start).num_seconds().max(0)
+        })
+        .sum();
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    return matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+}
+
+impl WorkingTimeLimits {
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(max_minutes) = self.max_daily_minutes {
+            parts.push(format!("at most {}m a day", max_minutes));
+        }
+        if let Some(max_minutes) = self.max_weekly_minutes {
+            parts.push(format!("at most {}m a week", max_minutes));
+        }
+        if let Some(min_minutes) = self.min_rest_minutes {
+            parts.push(format!("at least {}m of rest between days", min_minutes));
+        }
+        if let Some(max_minutes) = self.max_weekend_minutes {
+            parts.push(format!("at most {}m a day on weekends", max_minutes));
+        }
+        if let Some(max_minutes) = self.max_night_minutes {
+            parts.push(format!(
+                "at most {}m a night between {} and {}",
+                max_minutes,
+                self.night_start.as_deref().unwrap_or(NIGHT_START_DEFAULT),
+                self.night_end.as_deref().unwrap_or(NIGHT_END_DEFAULT)
+            ));
+        }
+        return parts.join(", ");
+    }
+
+    pub fn check_rest(
+        &self,
+        previous_end: &DateTime<Local>,
+        next_start: &DateTime<Local>,
+    ) -> Option<WorkingTimeViolation> {
+        let min_secs: i64 = (self.min_rest_minutes? * 60) as i64;
+        if (*next_start - *previous_end).num_seconds() >= min_secs {
+            return None;
+        }
+        return Some(WorkingTimeViolation::RestPeriod {
+            previous_end: *previous_end,
+            next_start: *next_start,
+            min_secs: min_secs,
+        });
+    }
+
+    /// Checks days against the limits, only listing violations from `start_date` on.
+    ///
+    /// Days before `start_date` can be included so rest periods and weekly totals going into the range
+    /// are checked too. Only finished blocks count as work.
+    pub fn check_days(
+        &self,
+        days: &[(NaiveDate, Day)],
+        start_date: NaiveDate,
+        week_start: Weekday,
+    ) -> Result<Vec<WorkingTimeViolation>, String> {
+        let mut sorted_days: Vec<&(NaiveDate, Day)> = days.iter().collect();
+        sorted_days.sort_by_key(|(date, _)| *date);
+        let mut violations: Vec<WorkingTimeViolation> = Vec::new();
+
+        let mut weekly_secs: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for (date, day) in sorted_days.iter() {
+            let work_secs: i64 = day
+                .get_work_intervals()
+                .iter()
+                .map(|(start, end)| (*end - *start).num_seconds())
+                .sum();
+            if let Some(max_minutes) = self.max_daily_minutes {
+                let max_secs: i64 = (max_minutes * 60) as i64;
+                if work_secs > max_secs {
+                    violations.push(WorkingTimeViolation::DailyMaximum {
+                        date: *date,
+                        work_secs: work_secs,
+                        max_secs: max_secs,
+                    });
+                }
+            }
+            let (this_week_start, _) = get_week_range(*date, week_start);
+            *weekly_secs.entry(this_week_start).or_insert(0) += work_secs;
+        }
+        if let Some(max_minutes) = self.max_weekly_minutes {
+            let max_secs: i64 = (max_minutes * 60) as i64;
+            for (this_week_start, work_secs) in weekly_secs {
+                if work_secs > max_secs {
+                    violations.push(WorkingTimeViolation::WeeklyMaximum {
+                        week_start: this_week_start,
+                        work_secs: work_secs,
+                        max_secs: max_secs,
+                    });
+                }
+            }
+        }
+
+        for pair in sorted_days.windows(2) {
+            let (previous_day, next_day) = (&pair[0].1, &pair[1].1);
+            if let Some(previous_end) = previous_day.get_day_end() {
+                let next_start: DateTime<Local> = next_day.get_day_start().as_dt();
+                violations.extend(self.check_rest(&previous_end.as_dt(), &next_start));
+            }
+        }
+
+        let intervals: Vec<(DateTime<Local>, DateTime<Local>)> = sorted_days
+            .iter()
+            .flat_map(|(_, day)| day.get_work_intervals())
+            .collect();
+        let (first_date, last_date) = match (intervals.first(), intervals.last()) {
+            (Some((first_start, _)), Some((_, last_end))) => {
+                (first_start.date_naive(), last_end.date_naive())
+            }
+            _ => return Ok(violations),
+        };
+        let night_start_secs: i64 = parse_time_of_day_secs(
+            self.night_start.as_deref().unwrap_or(NIGHT_START_DEFAULT),
+            "night_start",
+        )?;
+        let night_end_secs: i64 = parse_time_of_day_secs(
+            self.night_end.as_deref().unwrap_or(NIGHT_END_DEFAULT),
+            "night_end",
+        )?;
+        // The night before the first date can run into it.
+        let mut date: NaiveDate = first_date - Duration::days(1);
+        while date <= last_date {
+            let midnight: DateTime<Local> = get_start_of_date(date);
+            if let (Some(max_minutes), true) = (self.max_weekend_minutes, is_weekend(date)) {
+                let max_secs: i64 = (max_minutes * 60) as i64;
+                let work_secs: i64 = get_overlap_secs(
+                    &intervals,
+                    midnight,
+                    get_start_of_date(date.succ_opt().unwrap()),
+                );
+                if work_secs > max_secs {
+                    violations.push(WorkingTimeViolation::WeekendWork {
+                        date: date,
+                        work_secs: work_secs,
+                        max_secs: max_secs,
+                    });
+                }
+            }
+            if let Some(max_minutes) = self.max_night_minutes {
+                // A night that runs past midnight is listed under the date it starts on.
+                let night_end_offset: i64 = if night_end_secs <= night_start_secs {
+                    night_end_secs + 24 * 60 * 60
+                } else {
+                    night_end_secs
+                };
+                let max_secs: i64 = (max_minutes * 60) as i64;
+                let work_secs: i64 = get_overlap_secs(
+                    &intervals,
+                    midnight + Duration::seconds(night_start_secs),
+                    midnight + Duration::seconds(night_end_offset),
+                );
+                if work_secs > max_secs {
+                    violations.push(WorkingTimeViolation::NightWork {
+                        date: date,
+                        work_secs: work_secs,
+                        max_secs: max_secs,
+                    });
+                }
+            }
+            date += Duration::days(1);
+        }
+
+        violations.retain(|x| x.get_last_date() >= start_date);
+        violations.sort_by_key(|x| x.get_date());
+        return Ok(violations);
+    }
+}
+
+pub fn render_working_time_violations(
+    limits: &WorkingTimeLimits,
+    violations: &[WorkingTimeViolation],
+    show_times_in_hours: bool,
+) -> String {
+    let mut summary_str: String = format!("Working time limits ({}):", limits.describe());
+    if violations.len() == 0 {
+        summary_str += "\n\tNo violations.";
+    }
+    for violation in violations.iter() {
+        summary_str += &format!(
+            "\n\t{}: {}",
+            violation.get_date().format("%Y-%m-%d"),
+            violation.render(show_times_in_hours)
+        );
+    }
+    return summary_str;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn limits() -> WorkingTimeLimits {
+        return WorkingTimeLimits {
+            max_daily_minutes: Some(600),
+            max_weekly_minutes: Some(1200),
+            min_rest_minutes: Some(660),
+            max_weekend_minutes: Some(0),
+            max_night_minutes: Some(60),
+            night_start: None,
+            night_end: None,
+        };
+    }
+
+    /// A day on October 2026 with a single block of work between two times.
+    fn day_between(
+        day_of_month: u32,
+        (start_hour, start_min): (u32, u32),
+        length_mins: i64,
+    ) -> (NaiveDate, Day) {
+        let start: DateTime<Local> = Local
+            .with_ymd_and_hms(2026, 10, day_of_month, start_hour, start_min, 0)
+            .unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        day.end_day_at(&(start + Duration::minutes(length_mins)), false)
+            .unwrap();
+        return (start.date_naive(), day);
+    }
+
+    fn date(day_of_month: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 10, day_of_month).unwrap();
+    }
+
+    #[test]
+    fn test_compliant_week() {
+        // 2026-10-05 is a Monday.
+        let days: Vec<(NaiveDate, Day)> = (5..7).map(|x| day_between(x, (9, 0), 480)).collect();
+        assert_eq!(
+            limits().check_days(&days, date(5), Weekday::Mon),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_violations() {
+        let days: Vec<(NaiveDate, Day)> = vec![
+            day_between(8, (9, 0), 660),
+            day_between(9, (3, 0), 480),
+            day_between(10, (10, 0), 120),
+        ];
+        let violations: Vec<WorkingTimeViolation> =
+            limits().check_days(&days, date(8), Weekday::Mon).unwrap();
+        let rendered: Vec<String> = violations.iter().map(|x| x.render(true)).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "Worked 21h 0m 0s in the week starting 2026-10-05, over the weekly maximum of 20h 0m 0s.",
+                "Worked 11h 0m 0s in the day, over the daily maximum of 10h 0m 0s.",
+                "Worked 3h 0m 0s during the night, over the maximum of 1h 0m 0s.",
+                "Only rested 7h 0m 0s between punching out at 2026-10-08 20:00 and starting again at 2026-10-09 03:00, under the minimum of 11h 0m 0s.",
+                "Worked 2h 0m 0s on the weekend, over the maximum of 0s.",
+            ]
+        );
+        assert_eq!(violations[2].get_date(), date(8));
+    }
+
+    #[test]
+    fn test_days_before_start_are_context_only() {
+        let days: Vec<(NaiveDate, Day)> =
+            vec![day_between(6, (9, 0), 660), day_between(7, (6, 0), 480)];
+        let violations: Vec<WorkingTimeViolation> =
+            limits().check_days(&days, date(7), Weekday::Mon).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            violations[0],
+            WorkingTimeViolation::RestPeriod { .. }
+        ));
+    }
+
+    #[test]
+    fn test_check_rest() {
+        let previous_end: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 22, 0, 0).unwrap();
+        assert!(limits()
+            .check_rest(&previous_end, &(previous_end + Duration::hours(11)))
+            .is_none());
+        assert!(limits()
+            .check_rest(&previous_end, &(previous_end + Duration::hours(8)))
+            .is_some());
+    }
+}
@@ -0,0 +1,461 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use std::collections::HashMap;
+use std::env::var;
+use std::io::{stdout, IsTerminal};
+
+use crate::units::day::Day;
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+const RESET: &str = "\x1b[0m";
+const HEATMAP_COLOURS: [&str; 5] = [
+    "\x1b[38;5;240m",
+    "\x1b[38;5;22m",
+    "\x1b[38;5;28m",
+    "\x1b[38;5;34m",
+    "\x1b[38;5;46m",
+];
+const HEATMAP_CHARS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+const TASK_COLOURS: [&str; 6] = [
+    "\x1b[38;5;33m",
+    "\x1b[38;5;208m",
+    "\x1b[38;5;70m",
+    "\x1b[38;5;170m",
+    "\x1b[38;5;220m",
+    "\x1b[38;5;44m",
+];
+// Without colour, tasks are told apart by their fill instead.
+const TASK_CHARS: [char; 6] = ['█', '▓', '▒', '░', '#', '='];
+const BREAK_COLOUR: &str = "\x1b[38;5;245m";
+const BREAK_CHAR: char = '╱';
+const NOTE_CHAR: char = '^';
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Colours are only used in a terminal, and never when `NO_COLOR` is set.
+pub fn use_colour() -> bool {
+    return stdout().is_terminal() && var("NO_COLOR").is_err();
+}
+
+fn paint(text: &str, colour: &str, use_colour: bool) -> String {
+    if use_colour {
+        return format!("{}{}{}", colour, text, RESET);
+    }
+    return text.to_string();
+}
+
+/// How full a day is compared to the target: 0 for nothing done up to 4 for the target met.
+pub fn get_heatmap_level(secs: i64, target_secs: i64) -> usize {
+    if secs <= 0 {
+        return 0;
+    }
+    if target_secs <= 0 {
+        return 4;
+    }
+    return match (secs * 4) / target_secs {
+        0 => 1,
+        1 => 2,
+        2 | 3 => 3,
+        _ => 4,
+    };
+}
+
+/// A GitHub-style grid with a row per weekday and a column per week.
+pub fn render_heatmap(
+    times_done: &HashMap<NaiveDate, i64>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    target_secs: i64,
+    use_colour: bool,
+) -> String {
+    let first_monday: NaiveDate =
+        start_date - Duration::days(start_date.weekday().num_days_from_monday() as i64);
+    let num_weeks: i64 = (end_date - first_monday).num_days() / 7 + 1;
+
+    // Month names go above the first week starting in that month, if there's room for them.
+    let mut month_row: Vec<char> = vec![' '; 2 * num_weeks as usize + 3];
+    let mut last_month: Option<u32> = None;
+    let mut next_free_column: usize = 0;
+    for week in 0..num_weeks {
+        let week_start: NaiveDate = (first_monday + Duration::days(week * 7)).max(start_date);
+        if last_month == Some(week_start.month()) {
+            continue;
+        }
+        last_month = Some(week_start.month());
+        let column: usize = 2 * week as usize;
+        if column >= next_free_column {
+            for (i, c) in week_start.format("%b").to_string().chars().enumerate() {
+                month_row[column + i] = c;
+            }
+            next_free_column = column + 4;
+        }
+    }
+    let mut heatmap_str: String =
+        "    ".to_string() + month_row.iter().collect::<String>().trim_end();
+
+    for (weekday, label) in WEEKDAY_LABELS.iter().enumerate() {
+        heatmap_str += &format!("\n{} ", label);
+        for week in 0..num_weeks {
+            let date: NaiveDate = first_monday + Duration::days(week * 7 + weekday as i64);
+            if (date < start_date) || (date > end_date) {
+                heatmap_str += "  ";
+                continue;
+            }
+            let level: usize = get_heatmap_level(*times_done.get(&date).unwrap_or(&0), target_secs);
+            heatmap_str += &paint(
+                &HEATMAP_CHARS[level].to_string(),
+                HEATMAP_COLOURS[level],
+                use_colour,
+            );
+            heatmap_str += " ";
+        }
+        heatmap_str = heatmap_str.trim_end().to_string();
+    }
+
+    heatmap_str += "\nLess ";
+    for level in 0..HEATMAP_CHARS.len() {
+        heatmap_str += &paint(
+            &HEATMAP_CHARS[level].to_string(),
+            HEATMAP_COLOURS[level],
+            use_colour,
+        );
+        heatmap_str += " ";
+    }
+    heatmap_str += "More";
+    return heatmap_str;
+}
+
+/// A bar per date, split into one segment per task and scaled to the longest day.
+pub fn render_stacked_bars(
+    rows: &Vec<(NaiveDate, Vec<(String, i64)>)>,
+    width: usize,
+    use_colour: bool,
+    show_times_in_hours: bool,
+) -> String {
+    let mut task_order: Vec<String> = Vec::new();
+    for (_, tasks) in rows.iter() {
+        for (task_name, _) in tasks.iter() {
+            if !task_order.contains(task_name) {
+                task_order.push(task_name.clone());
+            }
+        }
+    }
+    let task_style = |task_name: &String| -> (&str, char) {
+        let ind: usize = task_order.iter().position(|x| x == task_name).unwrap_or(0);
+        return (
+            TASK_COLOURS[ind % TASK_COLOURS.len()],
+            TASK_CHARS[ind % TASK_CHARS.len()],
+        );
+    };
+    let max_secs: i64 = rows
+        .iter()
+        .map(|(_, tasks)| tasks.iter().map(|(_, secs)| *secs).sum::<i64>())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines: Vec<String> = Vec::new();
+    for (date, tasks) in rows.iter() {
+        let total_secs: i64 = tasks.iter().map(|(_, secs)| *secs).sum();
+        let mut bar: String = String::new();
+        let (mut secs_so_far, mut cells_so_far): (i64, usize) = (0, 0);
+        for (task_name, secs) in tasks.iter() {
+            // Cells are given out by the running total so rounding doesn't add up.
+            secs_so_far += secs;
+            let cells_until_now: usize = if max_secs > 0 {
+                ((secs_so_far * width as i64) / max_secs) as usize
+            } else {
+                0
+            };
+            let num_cells: usize = cells_until_now.saturating_sub(cells_so_far);
+            cells_so_far = cells_until_now.max(cells_so_far);
+            let (colour, fill) = task_style(task_name);
+            bar += &paint(&fill.to_string().repeat(num_cells), colour, use_colour);
+        }
+        bar += &" ".repeat(width.saturating_sub(cells_so_far));
+        lines.push(format!(
+            "{} {} {}",
+            date.format("%Y-%m-%d"),
+            bar,
+            render_seconds_human_readable(total_secs, show_times_in_hours)
+        ));
+    }
+    let legend: Vec<String> = task_order
+        .iter()
+        .map(|task_name| {
+            let (colour, fill) = task_style(task_name);
+            format!(
+                "{} {}",
+                paint(&fill.to_string(), colour, use_colour),
+                task_name
+            )
+        })
+        .collect();
+    lines.push(legend.join("  "));
+    return lines.join("\n");
+}
+
+/// A line of the running balance with a column per date, from the most behind at the top.
+pub fn render_balance_line(
+    points: &Vec<(NaiveDate, i64)>,
+    height: usize,
+    show_times_in_hours: bool,
+) -> String {
+    if points.len() == 0 {
+        return "No days to chart.".to_string();
+    }
+    let max_value: i64 = points.iter().map(|(_, x)| *x).max().unwrap().max(0);
+    let min_value: i64 = points.iter().map(|(_, x)| *x).min().unwrap().min(0);
+    let height: usize = height.max(2);
+    let span: i64 = (max_value - min_value).max(1);
+    let row_of = |value: i64| -> usize {
+        return (((max_value - value) * (height as i64 - 1) + span / 2) / span) as usize;
+    };
+    let zero_row: usize = row_of(0);
+
+    let max_label: String = render_seconds_human_readable(max_value, show_times_in_hours);
+    let min_label: String = render_seconds_human_readable(min_value, show_times_in_hours);
+    let label_width: usize = max_label.len().max(min_label.len()).max(1);
+    let mut lines: Vec<String> = Vec::new();
+    for row in 0..height {
+        let label: String = if row == 0 {
+            max_label.clone()
+        } else if row == height - 1 {
+            min_label.clone()
+        } else if row == zero_row {
+            "0s".to_string()
+        } else {
+            String::new()
+        };
+        let mut line: String = format!("{:>width$} ┤", label, width = label_width);
+        for (_, value) in points.iter() {
+            line.push(if row_of(*value) == row {
+                '●'
+            } else if row == zero_row {
+                '─'
+            } else {
+                ' '
+            });
+        }
+        lines.push(line);
+    }
+    let first_date: NaiveDate = points.first().unwrap().0;
+    let last_date: NaiveDate = points.last().unwrap().0;
+    lines.push(format!(
+        "{:>width$}  {} to {}",
+        "",
+        first_date.format("%Y-%m-%d"),
+        last_date.format("%Y-%m-%d"),
+        width = label_width
+    ));
+    return lines.join("\n");
+}
+
+/// A bar from the start of the day to its end (or `now`) with a cell per slice of time, coloured by task.
+pub fn render_day_timeline(
+    day: &Day,
+    now: &DateTime<Local>,
+    width: usize,
+    use_colour: bool,
+    show_times_in_hours: bool,
+) -> String {
+    let width: usize = width.max(1);
+    let start: DateTime<Local> = day.get_day_start().as_dt();
+    let end: DateTime<Local> = day.get_day_end().map(|x| x.as_dt()).unwrap_or(*now);
+    let span_secs: i64 = (end - start).num_seconds().max(1);
+    let spans: Vec<(usize, i64, i64)> = day
+        .timeblocks
+        .iter()
+        .enumerate()
+        .map(|(ind, block)| {
+            let block_end: DateTime<Local> = block.get_end().map(|x| x.as_dt()).unwrap_or(end);
+            (
+                ind,
+                (block.get_start().as_dt() - start).num_seconds(),
+                (block_end - start).num_seconds(),
+            )
+        })
+        .collect();
+
+    let mut task_order: Vec<String> = Vec::new();
+    let mut break_order: Vec<String> = Vec::new();
+    let mut task_secs: HashMap<String, i64> = HashMap::new();
+    for (ind, block_start, block_end) in spans.iter() {
+        let task_name: String = day.timeblocks[*ind].get_task_name();
+        let order: &mut Vec<String> = if day.is_break(*ind) {
+            &mut break_order
+        } else {
+            &mut task_order
+        };
+        if !order.contains(&task_name) {
+            order.push(task_name.clone());
+        }
+        *task_secs.entry(task_name).or_insert(0) += block_end - block_start;
+    }
+    let task_style = |task_name: &String| -> (&str, char) {
+        let ind: usize = task_order.iter().position(|x| x == task_name).unwrap_or(0);
+        return (
+            TASK_COLOURS[ind % TASK_COLOURS.len()],
+            TASK_CHARS[ind % TASK_CHARS.len()],
+        );
+    };
+
+    let mut bar: String = String::new();
+    for cell in 0..width {
+        // Each cell shows whatever was happening half way through its slice of time.
+        let cell_middle: i64 = ((2 * cell as i64 + 1) * span_secs) / (2 * width as i64);
+        let block_opt: Option<&(usize, i64, i64)> =
+            spans.iter().rev().find(|(_, block_start, block_end)| {
+                *block_start <= cell_middle && cell_middle < *block_end
+            });
+        bar += &match block_opt {
+            Some((ind, _, _)) if day.is_break(*ind) => {
+                paint(&BREAK_CHAR.to_string(), BREAK_COLOUR, use_colour)
+            }
+            Some((ind, _, _)) => {
+                let (colour, fill) = task_style(&day.timeblocks[*ind].get_task_name());
+                paint(&fill.to_string(), colour, use_colour)
+            }
+            None => " ".to_string(),
+        };
+    }
+
+    let mut note_row: Vec<char> = vec![' '; width];
+    let mut note_lines: Vec<String> = Vec::new();
+    for block in day.timeblocks.iter() {
+        for note in block.get_notes().iter() {
+            let note_secs: i64 = (note.get_time().as_dt() - start).num_seconds();
+            let cell: usize = ((note_secs.max(0) * width as i64) / span_secs) as usize;
+            note_row[cell.min(width - 1)] = NOTE_CHAR;
+            note_lines.push(format!(
+                "{} {} [{}] {}",
+                NOTE_CHAR,
+                note.get_time().as_dt().format("%H:%M"),
+                block.get_task_name(),
+                note.get_msg()
+            ));
+        }
+    }
+
+    let start_label: String = start.format("%H:%M").to_string();
+    let end_label: String = match day.get_day_end() {
+        Some(_) => end.format("%H:%M").to_string(),
+        None => format!("{} (now)", end.format("%H:%M")),
+    };
+    let mut lines: Vec<String> = vec![
+        format!(
+            "{}{}{}",
+            start_label,
+            " ".repeat(
+                (width + 2)
+                    .saturating_sub(start_label.len() + end_label.len())
+                    .max(1)
+            ),
+            end_label
+        ),
+        format!("|{}|", bar),
+    ];
+    if note_lines.len() > 0 {
+        lines.push(
+            format!(" {}", note_row.iter().collect::<String>())
+                .trim_end()
+                .to_string(),
+        );
+    }
+    for task_name in task_order.iter() {
+        let (colour, fill) = task_style(task_name);
+        lines.push(format!(
+            "{} {}: {}",
+            paint(&fill.to_string(), colour, use_colour),
+            task_name,
+            render_seconds_human_readable(task_secs[task_name], show_times_in_hours)
+        ));
+    }
+    for break_name in break_order.iter() {
+        lines.push(format!(
+            "{} {}: {}",
+            paint(&BREAK_CHAR.to_string(), BREAK_COLOUR, use_colour),
+            break_name,
+            render_seconds_human_readable(task_secs[break_name], show_times_in_hours)
+        ));
+    }
+    lines.extend(note_lines);
+    return lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    fn date(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+    }
+
+    #[test_case(0, 0 ; "nothing done")]
+    #[test_case(60, 1 ; "a little")]
+    #[test_case(3 * 3600, 2 ; "under half")]
+    #[test_case(6 * 3600, 3 ; "most of the day")]
+    #[test_case(8 * 3600, 4 ; "target met")]
+    fn test_get_heatmap_level(secs: i64, expected: usize) {
+        assert_eq!(get_heatmap_level(secs, 8 * 3600), expected);
+    }
+
+    #[test]
+    fn test_render_heatmap_without_colour() {
+        // 2026-10-05 is a Monday.
+        let times: HashMap<NaiveDate, i64> = HashMap::from([(date(5), 8 * 3600), (date(6), 3600)]);
+        let heatmap: String = render_heatmap(&times, date(5), date(11), 8 * 3600, false);
+        let lines: Vec<&str> = heatmap.lines().collect();
+        assert_eq!(lines[0], "    Oct");
+        assert_eq!(lines[1], "Mon █");
+        assert_eq!(lines[2], "Tue ░");
+        assert_eq!(lines[3], "Wed ·");
+        assert_eq!(lines[8], "Less · ░ ▒ ▓ █ More");
+    }
+
+    #[test]
+    fn test_render_stacked_bars_without_colour() {
+        let rows = vec![
+            (
+                date(5),
+                vec![("coding".to_string(), 3600), ("review".to_string(), 3600)],
+            ),
+            (date(6), vec![("review".to_string(), 3600)]),
+        ];
+        let bars: String = render_stacked_bars(&rows, 10, false, true);
+        let lines: Vec<&str> = bars.lines().collect();
+        assert_eq!(lines[0], "2026-10-05 █████▓▓▓▓▓ 2h 0m 0s");
+        assert_eq!(lines[1], "2026-10-06 ▓▓▓▓▓      1h 0m 0s");
+        assert_eq!(lines[2], "█ coding  ▓ review");
+    }
+
+    #[test]
+    fn test_render_balance_line() {
+        let points = vec![(date(5), 3600), (date(6), 0), (date(7), -3600)];
+        let chart: String = render_balance_line(&points, 3, true);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines[0], " 1h 0m 0s ┤●  ");
+        assert_eq!(lines[1], "       0s ┤─●─");
+        assert_eq!(lines[2], "-1h 0m 0s ┤  ●");
+    }
+
+    #[test]
+    fn test_render_day_timeline_without_colour() {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        day.add_note(&(start + Duration::minutes(30)), &"standup".to_string());
+        day.start_break_at("lunch".to_string(), &(start + Duration::hours(2)))
+            .unwrap();
+        day.start_new_block("review".to_string(), &(start + Duration::hours(3)))
+            .unwrap();
+        let now: DateTime<Local> = start + Duration::hours(4);
+        let timeline: String = render_day_timeline(&day, &now, 8, false, true);
+        let lines: Vec<&str> = timeline.lines().collect();
+        assert_eq!(lines[0], "09:00 13:00 (now)");
+        assert_eq!(lines[1], "|████╱╱▓▓|");
+        assert_eq!(lines[2], "  ^");
+        assert_eq!(lines[3], "█ coding: 2h 0m 0s");
+        assert_eq!(lines[4], "▓ review: 1h 0m 0s");
+        assert_eq!(lines[5], "╱ lunch: 1h 0m 0s");
+        assert_eq!(lines[6], "^ 09:30 [coding] standup");
+    }
+}
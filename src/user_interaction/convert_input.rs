@@ -1,3 +1,4 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use regex::Regex;
 use std::collections::HashMap;
 
@@ -7,18 +8,18 @@ pub fn convert_input_to_seconds(input_str: &str) -> Result<i64, String> {
         return Ok(secs);
     }
     let err_msg: String = format!(
-        "Malformed number of seconds. Should be either an integer or of the form: [zh][ym]xs. Got {}", input_str
+        "Malformed number of seconds. Should be either an integer or of the form: [zh][ym][xs]. Got {}", input_str
     );
     let mut secs: i64 = 0;
     let rest: String = input_str.to_lowercase().clone();
 
-    let check_regex = Regex::new(r"^(\-)?(\d+h)?(\d+m)?(\d+s)$").unwrap();
-    if !check_regex.is_match(&rest) {
+    let check_regex = Regex::new(r"^(\-)?(\d+h)?(\d+m)?(\d+s)?$").unwrap();
+    let re = Regex::new(r"(\d+)([hms])").unwrap();
+    if !check_regex.is_match(&rest) || !re.is_match(&rest) {
         return Err(err_msg);
     }
 
     let units_to_secs: HashMap<&str, i64> = HashMap::from([("h", 60 * 60), ("m", 60), ("s", 1)]);
-    let re = Regex::new(r"(\d+)([hms])").unwrap();
     let sign: i64 = if rest.starts_with('-') { -1 } else { 1 };
     for (_, [amount, unit]) in re.captures_iter(&rest).map(|c| c.extract()) {
         let parse_result = amount.parse::<i64>();
@@ -32,6 +33,27 @@ pub fn convert_input_to_seconds(input_str: &str) -> Result<i64, String> {
     return Ok(sign * secs);
 }
 
+pub fn convert_input_to_naive_datetime(
+    input_str: &str,
+    default_date: NaiveDate,
+) -> Result<NaiveDateTime, String> {
+    let trimmed: &str = input_str.trim();
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(trimmed, fmt) {
+            return Ok(datetime);
+        }
+    }
+    for fmt in ["%H:%M:%S", "%H:%M"] {
+        if let Ok(time) = NaiveTime::parse_from_str(trimmed, fmt) {
+            return Ok(default_date.and_time(time));
+        }
+    }
+    return Err(format!(
+        "Malformed time. Should be of the form 'HH:MM[:SS]' or 'YYYY-mm-dd HH:MM[:SS]'. Got {}",
+        input_str
+    ));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,9 +110,41 @@ mod tests {
         assert_eq!(out, expected);
     }
 
-    #[test]
-    fn test_errors_for_bad_string() {
-        let out = convert_input_to_seconds("abc");
+    #[test_case("2h", 7200 ; "positive hours only")]
+    #[test_case("-2h", -7200 ; "negative hours only")]
+    #[test_case("1h30m", 5400 ; "hours and minutes")]
+    #[test_case("-15m", -900 ; "negative minutes only")]
+    fn test_without_seconds_str(test_input: &str, expected: i64) {
+        let out: i64 = convert_input_to_seconds(test_input).unwrap();
+        assert_eq!(out, expected);
+    }
+
+    #[test_case("abc" ; "letters")]
+    #[test_case("" ; "empty string")]
+    #[test_case("-" ; "just a sign")]
+    #[test_case("1s2m" ; "units out of order")]
+    fn test_errors_for_bad_string(test_input: &str) {
+        let out = convert_input_to_seconds(test_input);
         assert!(out.is_err());
     }
+
+    #[test_case("18:30", "2026-10-05 18:30:00" ; "time without seconds")]
+    #[test_case("18:30:15", "2026-10-05 18:30:15" ; "time with seconds")]
+    #[test_case("2026-10-07 09:05", "2026-10-07 09:05:00" ; "datetime without seconds")]
+    #[test_case(" 2026-10-07 09:05:01 ", "2026-10-07 09:05:01" ; "datetime with whitespace")]
+    fn test_naive_datetime(test_input: &str, expected: &str) {
+        let default_date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        let out: NaiveDateTime = convert_input_to_naive_datetime(test_input, default_date).unwrap();
+        let expected_dt: NaiveDateTime =
+            NaiveDateTime::parse_from_str(expected, "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(out, expected_dt);
+    }
+
+    #[test_case("" ; "empty string")]
+    #[test_case("25:00" ; "invalid hour")]
+    #[test_case("yesterday" ; "words")]
+    fn test_naive_datetime_errors_for_bad_string(test_input: &str) {
+        let default_date: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 5).unwrap();
+        assert!(convert_input_to_naive_datetime(test_input, default_date).is_err());
+    }
 }
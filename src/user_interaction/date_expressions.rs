@@ -0,0 +1,315 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use regex::Regex;
+
+use crate::utils::config::get_config;
+use crate::utils::dates_and_times::{
+    get_local_now, get_month_range, get_quarter_range, get_week_range, get_year_range,
+};
+
+const RANGE_SEPARATOR: &str = "..";
+const RELATIVE_WORDS: [&str; 2] = ["this", "last"];
+
+/// What relative expressions like 'yesterday' and 'this-week' are relative to.
+pub struct DateContext {
+    pub today: NaiveDate,
+    pub week_start: Weekday,
+}
+
+impl DateContext {
+    pub fn new(today: NaiveDate, week_start: Weekday) -> Self {
+        return Self {
+            today: today,
+            week_start: week_start,
+        };
+    }
+
+    pub fn from_config() -> Self {
+        return Self::new(
+            get_local_now().date_naive(),
+            get_config().week_start_or_default(),
+        );
+    }
+}
+
+/// The dates a date expression covers, which is a single date unless it names a period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateSpan {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateSpan {
+    fn single(date: NaiveDate) -> Self {
+        return Self {
+            start: date,
+            end: date,
+        };
+    }
+
+    fn between((start, end): (NaiveDate, NaiveDate)) -> Self {
+        return Self {
+            start: start,
+            end: end,
+        };
+    }
+
+    pub fn is_single_date(&self) -> bool {
+        return self.start == self.end;
+    }
+}
+
+fn get_unknown_expression_msg(expr: &str) -> String {
+    return format!(
+        "'{}' isn't a date. Use 'YYYY-mm-dd', 'today', 'yesterday', '-3d', 'last monday', '2026-W41', 'this-month', 'last-month', '2026-Q4' or a range like '2026-10-01..2026-10-15'.",
+        expr
+    );
+}
+
+fn parse_single_expression(expr: &str, context: &DateContext) -> Result<DateSpan, String> {
+    let today: NaiveDate = context.today;
+    let normalised: String = expr.trim().to_lowercase().replace(' ', "-");
+    match normalised.as_str() {
+        "today" => return Ok(DateSpan::single(today)),
+        "yesterday" => return Ok(DateSpan::single(today - Duration::days(1))),
+        "tomorrow" => return Ok(DateSpan::single(today + Duration::days(1))),
+        "this-week" => return Ok(DateSpan::between(get_week_range(today, context.week_start))),
+        "last-week" => {
+            return Ok(DateSpan::between(get_week_range(
+                today - Duration::days(7),
+                context.week_start,
+            )))
+        }
+        "this-month" => return Ok(DateSpan::between(get_month_range(today))),
+        "last-month" => {
+            let (this_month_start, _) = get_month_range(today);
+            return Ok(DateSpan::between(get_month_range(
+                this_month_start - Duration::days(1),
+            )));
+        }
+        "this-quarter" => return Ok(DateSpan::between(get_quarter_range(today))),
+        "last-quarter" => {
+            let (this_quarter_start, _) = get_quarter_range(today);
+            return Ok(DateSpan::between(get_quarter_range(
+                this_quarter_start - Duration::days(1),
+            )));
+        }
+        "this-year" => return Ok(DateSpan::between(get_year_range(today))),
+        "last-year" => {
+            let (this_year_start, _) = get_year_range(today);
+            return Ok(DateSpan::between(get_year_range(
+                this_year_start - Duration::days(1),
+            )));
+        }
+        _ => (),
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&normalised, "%Y-%m-%d") {
+        return Ok(DateSpan::single(date));
+    }
+    let offset_regex = Regex::new(r"^([+-])(\d+)([dw])$").unwrap();
+    if let Some(captures) = offset_regex.captures(&normalised) {
+        let amount: i64 = captures[2]
+            .parse::<i64>()
+            .map_err(|_| get_unknown_expression_msg(expr))?;
+        let days: i64 = if &captures[3] == "w" {
+            amount * 7
+        } else {
+            amount
+        };
+        let sign: i64 = if &captures[1] == "-" { -1 } else { 1 };
+        return Ok(DateSpan::single(today + Duration::days(sign * days)));
+    }
+    if let Some(weekday_str) = normalised.strip_prefix("last-") {
+        if let Ok(weekday) = weekday_str.parse::<Weekday>() {
+            // The most recent one before today, so 'last monday' on a Monday is a week ago.
+            let days_back: u32 =
+                (today.weekday().num_days_from_monday() + 6 - weekday.num_days_from_monday()) % 7
+                    + 1;
+            return Ok(DateSpan::single(today - Duration::days(days_back as i64)));
+        }
+    }
+    let iso_week_regex = Regex::new(r"^(\d{4})-w(\d{1,2})$").unwrap();
+    if let Some(captures) = iso_week_regex.captures(&normalised) {
+        let year: i32 = captures[1].parse::<i32>().unwrap();
+        let week: u32 = captures[2].parse::<u32>().unwrap();
+        return match NaiveDate::from_isoywd_opt(year, week, Weekday::Mon) {
+            // ISO weeks always start on a Monday, whatever the configured week start.
+            Some(start) => Ok(DateSpan::between(get_week_range(start, Weekday::Mon))),
+            None => Err(format!("'{}' isn't a week in {}.", expr, year)),
+        };
+    }
+    let quarter_regex = Regex::new(r"^(\d{4})-q([1-4])$").unwrap();
+    if let Some(captures) = quarter_regex.captures(&normalised) {
+        let year: i32 = captures[1].parse::<i32>().unwrap();
+        let quarter: u32 = captures[2].parse::<u32>().unwrap();
+        let start: NaiveDate = NaiveDate::from_ymd_opt(year, 3 * quarter - 2, 1)
+            .expect("The first of a month always exists");
+        return Ok(DateSpan::between(get_quarter_range(start)));
+    }
+    let month_regex = Regex::new(r"^(\d{4})-(\d{1,2})$").unwrap();
+    if let Some(captures) = month_regex.captures(&normalised) {
+        let year: i32 = captures[1].parse::<i32>().unwrap();
+        let month: u32 = captures[2].parse::<u32>().unwrap();
+        return match NaiveDate::from_ymd_opt(year, month, 1) {
+            Some(start) => Ok(DateSpan::between(get_month_range(start))),
+            None => Err(get_unknown_expression_msg(expr)),
+        };
+    }
+    return Err(get_unknown_expression_msg(expr));
+}
+
+/// Parses a date expression, e.g. '2026-10-01', 'yesterday', '-3d', 'last monday', '2026-W41',
+/// 'this-month' or a range like 'last-month..today'.
+pub fn parse_date_span(expr: &str, context: &DateContext) -> Result<DateSpan, String> {
+    let span: DateSpan = match expr.split_once(RANGE_SEPARATOR) {
+        Some((start_expr, end_expr)) => DateSpan {
+            start: parse_single_expression(start_expr, context)?.start,
+            end: parse_single_expression(end_expr, context)?.end,
+        },
+        None => parse_single_expression(expr, context)?,
+    };
+    if span.start > span.end {
+        return Err(format!("'{}' ends before it starts.", expr));
+    }
+    return Ok(span);
+}
+
+/// Parses a date expression that has to be a single date.
+pub fn parse_date(expr: &str, context: &DateContext) -> Result<NaiveDate, String> {
+    let span: DateSpan = parse_date_span(expr, context)?;
+    if !span.is_single_date() {
+        return Err(format!(
+            "'{}' covers more than one date, but a single date is needed here.",
+            expr
+        ));
+    }
+    return Ok(span.start);
+}
+
+/// The dates covered by `<start> [end]` arguments, where either can be any date expression.
+pub fn parse_date_range(
+    start_expr: &str,
+    end_expr: Option<&str>,
+    context: &DateContext,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    let start_span: DateSpan = parse_date_span(start_expr, context)?;
+    let end_date: NaiveDate = match end_expr {
+        Some(end_expr) => parse_date_span(end_expr, context)?.end,
+        None => start_span.end,
+    };
+    if start_span.start > end_date {
+        return Err(format!(
+            "The range from '{}' ends before it starts.",
+            start_expr
+        ));
+    }
+    return Ok((start_span.start, end_date));
+}
+
+/// Joins unquoted expressions like `last monday` back into a single argument.
+pub fn join_date_words(args: Vec<String>) -> Vec<String> {
+    let mut joined: Vec<String> = Vec::new();
+    let mut args_iter = args.into_iter().peekable();
+    while let Some(arg) = args_iter.next() {
+        if RELATIVE_WORDS.contains(&arg.to_lowercase().as_str()) {
+            if let Some(next_arg) = args_iter.next_if(|x| !x.starts_with('-')) {
+                joined.push(format!("{} {}", arg, next_arg));
+                continue;
+            }
+        }
+        joined.push(arg);
+    }
+    return joined;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+    }
+
+    fn context() -> DateContext {
+        return DateContext::new(date(10, 15), Weekday::Mon);
+    }
+
+    // 2026-10-15 is a Thursday.
+    #[test_case("2026-10-01", date(10, 1), date(10, 1) ; "plain date")]
+    #[test_case("today", date(10, 15), date(10, 15) ; "today")]
+    #[test_case("yesterday", date(10, 14), date(10, 14) ; "yesterday")]
+    #[test_case("-3d", date(10, 12), date(10, 12) ; "days ago")]
+    #[test_case("-2w", date(10, 1), date(10, 1) ; "weeks ago")]
+    #[test_case("last monday", date(10, 12), date(10, 12) ; "last monday")]
+    #[test_case("last-thu", date(10, 8), date(10, 8) ; "last thursday on a thursday")]
+    #[test_case("2026-W41", date(10, 5), date(10, 11) ; "iso week")]
+    #[test_case("this-week", date(10, 12), date(10, 18) ; "this week")]
+    #[test_case("last-week", date(10, 5), date(10, 11) ; "last week")]
+    #[test_case("this-month", date(10, 1), date(10, 31) ; "this month")]
+    #[test_case("last-month", date(9, 1), date(9, 30) ; "last month")]
+    #[test_case("2026-02", date(2, 1), date(2, 28) ; "month")]
+    #[test_case("2026-Q3", date(7, 1), date(9, 30) ; "quarter")]
+    #[test_case("last-quarter", date(7, 1), date(9, 30) ; "last quarter")]
+    #[test_case("this-year", date(1, 1), date(12, 31) ; "this year")]
+    #[test_case("2026-10-01..2026-10-15", date(10, 1), date(10, 15) ; "range")]
+    #[test_case("last-month..yesterday", date(9, 1), date(10, 14) ; "range of expressions")]
+    fn test_parse_date_span(expr: &str, start: NaiveDate, end: NaiveDate) {
+        assert_eq!(
+            parse_date_span(expr, &context()),
+            Ok(DateSpan {
+                start: start,
+                end: end
+            })
+        );
+    }
+
+    #[test_case("someday" ; "unknown word")]
+    #[test_case("2026-13-01" ; "bad date")]
+    #[test_case("2026-W60" ; "bad week")]
+    #[test_case("today..yesterday" ; "backwards range")]
+    fn test_parse_date_span_errors(expr: &str) {
+        assert!(parse_date_span(expr, &context()).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_and_range() {
+        assert_eq!(parse_date("yesterday", &context()), Ok(date(10, 14)));
+        assert!(parse_date("this-month", &context()).is_err());
+        assert_eq!(
+            parse_date_range("2026-W41", None, &context()),
+            Ok((date(10, 5), date(10, 11)))
+        );
+        assert_eq!(
+            parse_date_range("last-month", Some("2026-W41"), &context()),
+            Ok((date(9, 1), date(10, 11)))
+        );
+    }
+
+    #[test]
+    fn test_week_start() {
+        let sunday_context: DateContext = DateContext::new(date(10, 15), Weekday::Sun);
+        assert_eq!(
+            parse_date_span("this-week", &sunday_context),
+            Ok(DateSpan {
+                start: date(10, 11),
+                end: date(10, 17)
+            })
+        );
+        assert_eq!(
+            parse_date_span("2026-W41", &sunday_context).unwrap().start,
+            date(10, 5)
+        );
+    }
+
+    #[test]
+    fn test_join_date_words() {
+        let args: Vec<String> = ["last", "monday", "today", "-o", "x", "last", "-3d"]
+            .map(|x| x.to_string())
+            .to_vec();
+        assert_eq!(
+            join_date_words(args),
+            ["last monday", "today", "-o", "x", "last", "-3d"].map(|x| x.to_string())
+        );
+    }
+}
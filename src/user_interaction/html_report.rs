@@ -0,0 +1,533 @@
+use chrono::{DateTime, Local, NaiveDate};
+use std::collections::HashMap;
+
+use crate::units::aggregate_day::AggregateDay;
+use crate::units::break_policy::{BreakPolicy, BreakViolation};
+use crate::units::day::{get_start_of_date, Day};
+use crate::units::days_in_range::DaysInRange;
+use crate::units::invoice::escape_html;
+use crate::units::working_time_limits::{WorkingTimeLimits, WorkingTimeViolation};
+use crate::user_interaction::human_readable::render_seconds_human_readable;
+
+const TASK_COLOURS: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#b07aa1", "#edc948", "#76b7b2", "#e15759", "#9c755f",
+];
+const SECS_IN_HOUR: i64 = 60 * 60;
+// Kept inline so the report is a single file that opens anywhere, offline.
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+h1, h2, h3 { font-weight: 600; }
+table { border-collapse: collapse; margin: 0.5em 0 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.75em; text-align: left; }
+.day { margin-bottom: 2em; }
+.timeline { position: relative; height: 1.5em; background: #f4f4f4; border: 1px solid #ccc; }
+.block { position: absolute; top: 0; bottom: 0; }
+.break { background: repeating-linear-gradient(45deg, #bbb, #bbb 4px, #ddd 4px, #ddd 8px); }
+.axis { position: relative; height: 1.2em; font-size: 0.75em; color: #666; }
+.axis span { position: absolute; transform: translateX(-50%); }
+.legend span { display: inline-block; margin-right: 1em; }
+.swatch { display: inline-block; width: 0.8em; height: 0.8em; margin-right: 0.3em; }
+";
+
+/// The policies a report checks the days against, where they're set.
+#[derive(Default)]
+pub struct ReportPolicies<'a> {
+    pub break_policy: Option<&'a BreakPolicy>,
+    pub working_time: Option<(&'a WorkingTimeLimits, &'a [WorkingTimeViolation])>,
+}
+
+struct TimelineWindow {
+    start_offset: i64,
+    end_offset: i64,
+}
+
+impl TimelineWindow {
+    fn get_percent(&self, offset: i64) -> f64 {
+        let span: i64 = self.end_offset - self.start_offset;
+        return ((offset - self.start_offset) as f64) * 100.0 / (span as f64);
+    }
+}
+
+/// Seconds from the start of `date` to `time`, which can go past a day for days running over midnight.
+fn get_offset_from_date(date: NaiveDate, time: &DateTime<Local>) -> i64 {
+    return (*time - get_start_of_date(date)).num_seconds();
+}
+
+fn get_block_spans(date: NaiveDate, day: &Day) -> Vec<(usize, i64, i64)> {
+    let day_end: Option<DateTime<Local>> = day.get_day_end().map(|x| x.as_dt());
+    return day
+        .timeblocks
+        .iter()
+        .enumerate()
+        .filter_map(|(ind, block)| {
+            let end: DateTime<Local> = block.get_end().map(|x| x.as_dt()).or(day_end)?;
+            Some((
+                ind,
+                get_offset_from_date(date, &block.get_start().as_dt()),
+                get_offset_from_date(date, &end),
+            ))
+        })
+        .collect();
+}
+
+/// The same hours are used for every day so that timelines line up down the page.
+fn get_timeline_window(days: &[(NaiveDate, Day)]) -> TimelineWindow {
+    let spans: Vec<(usize, i64, i64)> = days
+        .iter()
+        .flat_map(|(date, day)| get_block_spans(*date, day))
+        .collect();
+    let earliest: i64 = spans
+        .iter()
+        .map(|(_, x, _)| *x)
+        .min()
+        .unwrap_or(9 * SECS_IN_HOUR);
+    let latest: i64 = spans
+        .iter()
+        .map(|(_, _, x)| *x)
+        .max()
+        .unwrap_or(17 * SECS_IN_HOUR);
+    let start_offset: i64 = earliest.div_euclid(SECS_IN_HOUR) * SECS_IN_HOUR;
+    let end_offset: i64 = (latest + SECS_IN_HOUR - 1).div_euclid(SECS_IN_HOUR) * SECS_IN_HOUR;
+    return TimelineWindow {
+        start_offset: start_offset,
+        end_offset: end_offset.max(start_offset + SECS_IN_HOUR),
+    };
+}
+
+fn get_task_colours(task_order: &[String]) -> HashMap<String, &'static str> {
+    return task_order
+        .iter()
+        .enumerate()
+        .map(|(i, task_name)| (task_name.clone(), TASK_COLOURS[i % TASK_COLOURS.len()]))
+        .collect();
+}
+
+fn render_time_of_day(time: &DateTime<Local>) -> String {
+    return time.format("%H:%M").to_string();
+}
+
+fn render_timeline(
+    date: NaiveDate,
+    day: &Day,
+    window: &TimelineWindow,
+    task_colours: &HashMap<String, &'static str>,
+) -> String {
+    let mut html_str: String = "<div class=\"timeline\">\n".to_string();
+    for (ind, start, end) in get_block_spans(date, day) {
+        let block = &day.timeblocks[ind];
+        let task_name: String = block.get_task_name();
+        let end_str: String = block
+            .get_end()
+            .or(day.get_day_end())
+            .map(|x| render_time_of_day(&x.as_dt()))
+            .unwrap_or_default();
+        let (class, style): (&str, String) = if day.is_break(ind) {
+            ("block break", String::new())
+        } else {
+            let colour: &str = task_colours.get(&task_name).unwrap_or(&TASK_COLOURS[0]);
+            ("block", format!("background: {};", colour))
+        };
+        html_str += &format!(
+            "<div class=\"{}\" style=\"left: {:.3}%; width: {:.3}%; {}\" title=\"{} {}-{}\"></div>\n",
+            class,
+            window.get_percent(start),
+            window.get_percent(end) - window.get_percent(start),
+            style,
+            escape_html(&task_name),
+            render_time_of_day(&block.get_start().as_dt()),
+            end_str
+        );
+    }
+    html_str += "</div>\n<div class=\"axis\">";
+    let mut hour_offset: i64 = window.start_offset;
+    while hour_offset <= window.end_offset {
+        html_str += &format!(
+            "<span style=\"left: {:.3}%\">{:02}:00</span>",
+            window.get_percent(hour_offset),
+            (hour_offset / SECS_IN_HOUR).rem_euclid(24)
+        );
+        hour_offset += SECS_IN_HOUR;
+    }
+    html_str += "</div>\n";
+    return html_str;
+}
+
+fn render_day(
+    date: NaiveDate,
+    day: &Day,
+    window: &TimelineWindow,
+    task_colours: &HashMap<String, &'static str>,
+    show_times_in_hours: bool,
+) -> String {
+    let mut html_str: String = format!(
+        "<div class=\"day\">\n<h3>{}</h3>\n",
+        date.format("%A %Y-%m-%d")
+    );
+    html_str += &format!(
+        "<p>Time done: {} of {}. Breaks: {} taking {}.</p>\n",
+        render_seconds_human_readable(day.get_time_done_secs().unwrap_or(0), show_times_in_hours),
+        render_seconds_human_readable(day.get_time_to_do_secs() as i64, show_times_in_hours),
+        day.get_number_of_breaks().unwrap_or(0),
+        render_seconds_human_readable(
+            day.get_total_break_time_secs().unwrap_or(0),
+            show_times_in_hours
+        )
+    );
+    html_str += &render_timeline(date, day, window, task_colours);
+
+    let notes: Vec<(String, String, String)> = day
+        .timeblocks
+        .iter()
+        .flat_map(|block| {
+            block.get_notes().iter().map(|note| {
+                (
+                    render_time_of_day(&note.get_time().as_dt()),
+                    block.get_task_name(),
+                    note.get_msg(),
+                )
+            })
+        })
+        .collect();
+    if notes.len() > 0 {
+        html_str += "<h4>Notes</h4>\n<ul>\n";
+        for (time_str, task_name, msg) in notes.iter() {
+            html_str += &format!(
+                "<li>{} <b>{}</b>: {}</li>\n",
+                time_str,
+                escape_html(task_name),
+                escape_html(msg)
+            );
+        }
+        html_str += "</ul>\n";
+    }
+
+    if day.summaries.len() > 0 {
+        html_str += "<h4>Summaries</h4>\n<table>\n";
+        html_str += "<tr><th>Category</th><th>Project</th><th>Task</th><th>Summary</th></tr>\n";
+        for summary in day.summaries.iter() {
+            html_str += &format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&summary.get_category()),
+                escape_html(&summary.get_project()),
+                escape_html(&summary.get_task()),
+                escape_html(&summary.get_summary())
+            );
+        }
+        html_str += "</table>\n";
+    }
+    html_str += "</div>\n";
+    return html_str;
+}
+
+fn render_overview(
+    days_in_range: &DaysInRange,
+    aggregated: &AggregateDay,
+    include_overall_time_behind: bool,
+    show_times_in_hours: bool,
+) -> String {
+    let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+    let mut rows: Vec<(&str, String)> = vec![
+        ("Days summarised", aggregated.num_days.to_string()),
+        (
+            "Total work time (including breaks)",
+            render(aggregated.total_time as i64),
+        ),
+        (
+            "Total time working (excluding breaks)",
+            render(aggregated.get_total_time_done() as i64),
+        ),
+        (
+            "Time to do over period",
+            render(aggregated.total_time_to_do as i64),
+        ),
+        (
+            "Time behind over period",
+            render(aggregated.get_time_behind_over_period()),
+        ),
+    ];
+    if aggregated.balance_adjustments != 0 {
+        rows.push((
+            "Balance adjustments over period",
+            render(aggregated.balance_adjustments),
+        ));
+    }
+    if include_overall_time_behind {
+        rows.push((
+            "Time behind overall",
+            render(aggregated.get_time_behind_overall()),
+        ));
+    }
+    if days_in_range.days_not_there.len() > 0 {
+        rows.push(("Days not there", days_in_range.days_not_there.join(", ")));
+    }
+    if days_in_range.days_not_ended.len() > 0 {
+        rows.push(("Days not ended", days_in_range.days_not_ended.join(", ")));
+    }
+
+    let mut html_str: String = "<h2>Overview</h2>\n<table>\n".to_string();
+    for (label, value) in rows.iter() {
+        html_str += &format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            label,
+            escape_html(value)
+        );
+    }
+    html_str += "</table>\n";
+    return html_str;
+}
+
+fn render_break_statistics(
+    days: &[(NaiveDate, Day)],
+    aggregated: &AggregateDay,
+    show_times_in_hours: bool,
+) -> String {
+    let render = |secs: i64| render_seconds_human_readable(secs, show_times_in_hours);
+    let break_lengths: Vec<i64> = days
+        .iter()
+        .flat_map(|(date, day)| {
+            get_block_spans(*date, day)
+                .into_iter()
+                .filter(|(ind, _, _)| day.is_break(*ind))
+                .map(|(_, start, end)| end - start)
+        })
+        .collect();
+    let average_secs: i64 = match break_lengths.len() {
+        0 => 0,
+        num_breaks => break_lengths.iter().sum::<i64>() / (num_breaks as i64),
+    };
+    let mut html_str: String = "<h2>Breaks</h2>\n<table>\n".to_string();
+    html_str += &format!(
+        "<tr><th>Number of breaks</th><td>{}</td></tr>\n",
+        aggregated.num_breaks
+    );
+    html_str += &format!(
+        "<tr><th>Total time on break</th><td>{}</td></tr>\n",
+        render(aggregated.total_break_time as i64)
+    );
+    html_str += &format!(
+        "<tr><th>Average break</th><td>{}</td></tr>\n",
+        render(average_secs)
+    );
+    html_str += &format!(
+        "<tr><th>Longest break</th><td>{}</td></tr>\n",
+        render(break_lengths.iter().max().copied().unwrap_or(0))
+    );
+    html_str += "</table>\n";
+    return html_str;
+}
+
+fn render_break_policy(
+    policy: &BreakPolicy,
+    days: &[(NaiveDate, Day)],
+    show_times_in_hours: bool,
+) -> String {
+    let violations: Vec<(NaiveDate, BreakViolation)> = policy.find_violations_in_days(days);
+    let mut html_str: String = format!(
+        "<h2>Break policy</h2>\n<p>{}</p>\n",
+        escape_html(&policy.describe())
+    );
+    if violations.len() == 0 {
+        html_str += "<p>No violations.</p>\n";
+        return html_str;
+    }
+    html_str += "<table>\n<tr><th>Date</th><th>Violation</th></tr>\n";
+    for (date, violation) in violations.iter() {
+        html_str += &format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            date.format("%Y-%m-%d"),
+            escape_html(&violation.render(show_times_in_hours))
+        );
+    }
+    html_str += "</table>\n";
+    return html_str;
+}
+
+fn render_working_time_limits(
+    limits: &WorkingTimeLimits,
+    violations: &[WorkingTimeViolation],
+    show_times_in_hours: bool,
+) -> String {
+    let mut html_str: String = format!(
+        "<h2>Working time limits</h2>\n<p>{}</p>\n",
+        escape_html(&limits.describe())
+    );
+    if violations.len() == 0 {
+        html_str += "<p>No violations.</p>\n";
+        return html_str;
+    }
+    html_str += "<table>\n<tr><th>Date</th><th>Violation</th></tr>\n";
+    for violation in violations.iter() {
+        html_str += &format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            violation.get_date().format("%Y-%m-%d"),
+            escape_html(&violation.render(show_times_in_hours))
+        );
+    }
+    html_str += "</table>\n";
+    return html_str;
+}
+
+fn render_task_totals(
+    task_order: &[String],
+    aggregated: &AggregateDay,
+    task_colours: &HashMap<String, &'static str>,
+    show_times_in_hours: bool,
+) -> String {
+    let task_times: HashMap<String, i64> = aggregated.get_task_times_secs();
+    let rounded_times_opt: Option<HashMap<String, i64>> = aggregated.get_rounded_task_times_secs();
+    let mut html_str: String =
+        "<h2>Task totals</h2>\n<table>\n<tr><th>Task</th><th>Time</th>".to_string();
+    if rounded_times_opt.is_some() {
+        html_str += "<th>Rounded</th>";
+    }
+    html_str += "</tr>\n";
+    for task_name in task_order.iter() {
+        let swatch: String = if aggregated.is_break_task(task_name) {
+            "<span class=\"swatch break\"></span>".to_string()
+        } else {
+            format!(
+                "<span class=\"swatch\" style=\"background: {};\"></span>",
+                task_colours.get(task_name).unwrap_or(&TASK_COLOURS[0])
+            )
+        };
+        html_str += &format!(
+            "<tr><td>{}{}</td><td>{}</td>",
+            swatch,
+            escape_html(task_name),
+            render_seconds_human_readable(
+                *task_times.get(task_name).unwrap_or(&0),
+                show_times_in_hours
+            )
+        );
+        if let Some(rounded_times) = &rounded_times_opt {
+            html_str += &format!(
+                "<td>{}</td>",
+                render_seconds_human_readable(
+                    *rounded_times.get(task_name).unwrap_or(&0),
+                    show_times_in_hours
+                )
+            );
+        }
+        html_str += "</tr>\n";
+    }
+    html_str += "</table>\n";
+    return html_str;
+}
+
+/// A standalone HTML page with the same numbers as 'punch summarise-days' plus per-day timelines.
+pub fn render_html_report(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    days_in_range: &DaysInRange,
+    aggregated: &AggregateDay,
+    include_overall_time_behind: bool,
+    show_times_in_hours: bool,
+    policies: &ReportPolicies,
+) -> String {
+    let task_times: HashMap<String, i64> = aggregated.get_task_times_secs();
+    let mut task_order: Vec<String> = task_times.keys().cloned().collect();
+    task_order.sort_by(|a, b| task_times[b].cmp(&task_times[a]).then(a.cmp(b)));
+    let task_colours: HashMap<String, &'static str> = get_task_colours(&task_order);
+    let window: TimelineWindow = get_timeline_window(&days_in_range.days);
+
+    let title: String = format!(
+        "Work report {} to {}",
+        start_date.format("%Y-%m-%d"),
+        end_date.format("%Y-%m-%d")
+    );
+    let mut html_str: String = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, STYLE, title
+    );
+    html_str += &render_overview(
+        days_in_range,
+        aggregated,
+        include_overall_time_behind,
+        show_times_in_hours,
+    );
+    html_str += &render_task_totals(&task_order, aggregated, &task_colours, show_times_in_hours);
+    html_str += &render_break_statistics(&days_in_range.days, aggregated, show_times_in_hours);
+    if let Some(policy) = policies.break_policy {
+        html_str += &render_break_policy(policy, &days_in_range.days, show_times_in_hours);
+    }
+    if let Some((limits, violations)) = policies.working_time {
+        html_str += &render_working_time_limits(limits, violations, show_times_in_hours);
+    }
+
+    html_str += "<h2>Days</h2>\n<p class=\"legend\">";
+    for task_name in task_order.iter().filter(|x| !aggregated.is_break_task(x)) {
+        html_str += &format!(
+            "<span><span class=\"swatch\" style=\"background: {};\"></span>{}</span>",
+            task_colours[task_name],
+            escape_html(task_name)
+        );
+    }
+    html_str += "<span><span class=\"swatch break\"></span>Breaks</span></p>\n";
+    for (date, day) in days_in_range.days.iter() {
+        html_str += &render_day(*date, day, &window, &task_colours, show_times_in_hours);
+    }
+    html_str += "</body>\n</html>\n";
+    return html_str;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn make_day() -> (NaiveDate, Day) {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 12, 9, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        day.add_note(&(start + Duration::minutes(5)), &"<setup>".to_string());
+        day.start_break_at("lunch".to_string(), &(start + Duration::hours(3)))
+            .unwrap();
+        day.start_new_block("review".to_string(), &(start + Duration::hours(4)))
+            .unwrap();
+        day.add_summary(
+            "dev".to_string(),
+            "punch".to_string(),
+            "review".to_string(),
+            "Went through PRs".to_string(),
+        );
+        day.end_day_at(&(start + Duration::hours(8) + Duration::minutes(30)), false)
+            .unwrap();
+        return (start.date_naive(), day);
+    }
+
+    #[test]
+    fn test_timeline_window_is_whole_hours_around_blocks() {
+        let days: Vec<(NaiveDate, Day)> = vec![make_day()];
+        let window: TimelineWindow = get_timeline_window(&days);
+        assert_eq!(window.start_offset, 9 * SECS_IN_HOUR);
+        assert_eq!(window.end_offset, 18 * SECS_IN_HOUR);
+        assert_eq!(window.get_percent(9 * SECS_IN_HOUR), 0.0);
+        assert_eq!(window.get_percent(18 * SECS_IN_HOUR), 100.0);
+    }
+
+    #[test]
+    fn test_render_html_report_is_self_contained() {
+        let (date, day) = make_day();
+        let mut aggregated: AggregateDay = AggregateDay::new(0);
+        aggregated.add_day(day.clone()).unwrap();
+        let days_in_range: DaysInRange = DaysInRange {
+            days: vec![(date, day)],
+            days_not_there: vec!["2026-10-13".to_string()],
+            days_not_ended: Vec::new(),
+        };
+        let html_str: String = render_html_report(
+            date,
+            date,
+            &days_in_range,
+            &aggregated,
+            false,
+            true,
+            &ReportPolicies::default(),
+        );
+        assert!(html_str.starts_with("<!DOCTYPE html>"));
+        assert!(!html_str.contains("src="));
+        assert!(!html_str.contains("href="));
+        assert!(html_str.contains("&lt;setup&gt;"));
+        assert!(html_str.contains("Went through PRs"));
+        assert!(html_str.contains("class=\"block break\""));
+        assert!(html_str.contains("Days not there</th><td>2026-10-13"));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod charts;
 pub mod convert_input;
+pub mod date_expressions;
+pub mod html_report;
 pub mod human_readable;
+pub mod prompt;
 pub mod render_list_for_user;
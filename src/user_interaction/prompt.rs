@@ -0,0 +1,67 @@
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::process::Command;
+
+use crate::utils::lock::without_data_lock;
+
+pub fn prompt_for_input(prompt: &str) -> String {
+    print!("{} ", prompt);
+    stdout().flush().expect("Couldn't flush stdout!");
+    let mut input: String = String::new();
+    // Nobody else should have to wait on someone answering, so the data isn't locked meanwhile.
+    without_data_lock(|| {
+        stdin()
+            .read_line(&mut input)
+            .expect("Couldn't read from stdin!")
+    });
+    return input.trim().to_string();
+}
+
+/// Like `prompt_for_input`, but what's typed isn't shown, e.g. for a passphrase.
+pub fn prompt_for_secret(prompt: &str) -> String {
+    let is_hidden: bool = can_prompt() && set_terminal_echo(false);
+    let input: String = prompt_for_input(prompt);
+    if is_hidden {
+        set_terminal_echo(true);
+        println!();
+    }
+    return input;
+}
+
+fn set_terminal_echo(is_on: bool) -> bool {
+    let setting: &str = if is_on { "echo" } else { "-echo" };
+    return Command::new("stty")
+        .arg(setting)
+        .status()
+        .map(|x| x.success())
+        .unwrap_or(false);
+}
+
+/// Whether someone can answer prompts, rather than punch being run from a script or cron.
+pub fn can_prompt() -> bool {
+    return stdin().is_terminal();
+}
+
+pub fn ask_for_confirmation(prompt: &str) -> bool {
+    let answer: String = prompt_for_input(&format!("{} [y/N]", prompt));
+    return is_yes(&answer);
+}
+
+fn is_yes(answer: &str) -> bool {
+    return matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("y", true ; "short yes")]
+    #[test_case("Yes", true ; "long yes with capital")]
+    #[test_case(" y ", true ; "yes with whitespace")]
+    #[test_case("", false ; "empty answer")]
+    #[test_case("n", false ; "no")]
+    #[test_case("yeah", false ; "anything else")]
+    fn test_is_yes(answer: &str, expected: bool) {
+        assert_eq!(is_yes(answer), expected);
+    }
+}
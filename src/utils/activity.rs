@@ -0,0 +1,40 @@
+use chrono::prelude::{DateTime, Local};
+use chrono::NaiveDate;
+
+use crate::units::interval::{DATETIME_FMT, DATE_FMT};
+use crate::utils::dates_and_times::{convert_date_to_date_str, DateRange};
+use crate::utils::file_io::{append_to_file, create_dir_if_not_exists, expand_path, read_file};
+use crate::utils::profiles::get_data_dir;
+
+pub const HEARTBEAT_DIR: &str = "heartbeats/";
+
+pub fn get_heartbeat_file_path_from_date_str(date_str: &str) -> String {
+    return expand_path(&get_data_dir()) + HEARTBEAT_DIR + date_str;
+}
+
+pub fn record_heartbeat(now: &DateTime<Local>) {
+    let path: String = get_heartbeat_file_path_from_date_str(&now.format(DATE_FMT).to_string());
+    append_to_file(&path, now.format(DATETIME_FMT).to_string() + "\n");
+}
+
+pub fn read_heartbeats_between(start_date: NaiveDate, end_date: NaiveDate) -> Vec<DateTime<Local>> {
+    let mut heartbeats: Vec<DateTime<Local>> = Vec::new();
+    for date in DateRange(start_date, end_date) {
+        let path: String = get_heartbeat_file_path_from_date_str(&convert_date_to_date_str(date));
+        if let Ok(contents) = read_file(&path) {
+            // Skip anything malformed rather than failing: heartbeats are best effort.
+            heartbeats.extend(contents.lines().filter_map(|x| {
+                DateTime::parse_from_str(x.trim(), DATETIME_FMT)
+                    .ok()
+                    .map(|y| y.with_timezone(&Local))
+            }));
+        }
+    }
+    heartbeats.sort();
+    return heartbeats;
+}
+
+pub fn create_heartbeat_dir_if_not_exists() {
+    let heartbeat_dir: String = get_data_dir() + HEARTBEAT_DIR;
+    create_dir_if_not_exists(&heartbeat_dir);
+}
@@ -0,0 +1,160 @@
+use chrono::prelude::{DateTime, Local};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{get_storage, Storage};
+use crate::units::interval::Dt;
+use crate::utils::file_io::expand_path;
+use crate::utils::journal::{record_in_journal, JournalTarget};
+use crate::utils::profiles::get_data_dir;
+
+pub const BALANCE_FILE: &str = "balance";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BalanceAdjustment {
+    time: Dt,
+    seconds: i64,
+    reason: Option<String>,
+}
+
+impl BalanceAdjustment {
+    pub fn new(time: &DateTime<Local>, seconds: i64, reason: Option<String>) -> Self {
+        return Self {
+            time: Dt(*time),
+            seconds: seconds,
+            reason: reason,
+        };
+    }
+
+    pub fn get_time(&self) -> Dt {
+        return self.time;
+    }
+
+    pub fn get_date(&self) -> NaiveDate {
+        return self.time.as_dt().date_naive();
+    }
+
+    pub fn get_seconds(&self) -> i64 {
+        return self.seconds;
+    }
+
+    pub fn get_time_behind_delta(&self) -> i64 {
+        return -self.seconds;
+    }
+
+    pub fn get_reason(&self) -> Option<&String> {
+        return self.reason.as_ref();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BalanceLedger {
+    adjustments: Vec<BalanceAdjustment>,
+}
+
+impl BalanceLedger {
+    pub fn as_string(&self) -> String {
+        return serde_yaml::to_string(&self).unwrap();
+    }
+
+    pub fn add_adjustment(&mut self, adjustment: BalanceAdjustment) {
+        self.adjustments.push(adjustment);
+    }
+
+    pub fn get_adjustments(&self) -> &Vec<BalanceAdjustment> {
+        return &self.adjustments;
+    }
+
+    pub fn get_adjustments_between(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Vec<BalanceAdjustment> {
+        return self
+            .adjustments
+            .iter()
+            .filter(|x| (x.get_date() >= start_date) && (x.get_date() <= end_date))
+            .cloned()
+            .collect();
+    }
+}
+
+pub fn get_balance_path() -> String {
+    return expand_path(&(get_data_dir() + BALANCE_FILE));
+}
+
+pub fn get_balance_ledger() -> BalanceLedger {
+    return get_storage().read_balance_ledger();
+}
+
+pub fn write_balance_ledger(ledger: &BalanceLedger) {
+    let storage: Box<dyn Storage> = get_storage();
+    let before: String = storage.read_balance_ledger().as_string();
+    storage.write_balance_ledger(ledger);
+    record_in_journal(
+        JournalTarget::Balance,
+        Some(before),
+        Some(ledger.as_string()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    fn adjustment_on(day: u32, seconds: i64) -> BalanceAdjustment {
+        let time: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap();
+        return BalanceAdjustment::new(&time, seconds, None);
+    }
+
+    fn ledger() -> BalanceLedger {
+        let mut ledger: BalanceLedger = BalanceLedger::default();
+        for (day, seconds) in [(5, 3600), (7, -1800), (9, 600)] {
+            ledger.add_adjustment(adjustment_on(day, seconds));
+        }
+        return ledger;
+    }
+
+    #[test]
+    fn test_adjustment_reduces_time_behind() {
+        let adjustment: BalanceAdjustment = adjustment_on(5, 3600);
+        assert_eq!(adjustment.get_seconds(), 3600);
+        assert_eq!(adjustment.get_time_behind_delta(), -3600);
+        assert_eq!(
+            adjustment.get_date(),
+            NaiveDate::from_ymd_opt(2026, 10, 5).unwrap()
+        );
+    }
+
+    #[test_case(5, 9, vec![3600, -1800, 600] ; "whole range")]
+    #[test_case(6, 8, vec![-1800] ; "inside")]
+    #[test_case(9, 9, vec![600] ; "single date")]
+    #[test_case(10, 12, vec![] ; "after")]
+    fn test_get_adjustments_between(start_day: u32, end_day: u32, expected: Vec<i64>) {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+        let seconds: Vec<i64> = ledger()
+            .get_adjustments_between(date(start_day), date(end_day))
+            .iter()
+            .map(|x| x.get_seconds())
+            .collect();
+        assert_eq!(seconds, expected);
+    }
+
+    #[test]
+    fn test_ledger_round_trips_through_yaml() {
+        let mut ledger: BalanceLedger = ledger();
+        let time: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 10, 9, 0, 0).unwrap();
+        ledger.add_adjustment(BalanceAdjustment::new(
+            &time,
+            900,
+            Some("Overtime payout".to_string()),
+        ));
+        let read_back: BalanceLedger = serde_yaml::from_str(&ledger.as_string()).unwrap();
+        assert_eq!(read_back.get_adjustments().len(), 4);
+        let last: &BalanceAdjustment = &read_back.get_adjustments()[3];
+        assert_eq!(last.get_reason(), Some(&"Overtime payout".to_string()));
+        assert_eq!(last.get_time().as_dt(), time);
+    }
+}
@@ -1,14 +1,28 @@
-use crate::utils::file_io::{
-    expand_path, read_file, write_file, FromString, SafeFileEdit, ToFile, BASE_DIR,
-};
+use crate::storage::{get_storage, Storage};
+use crate::units::break_policy::BreakPolicy;
+use crate::units::invoice::Billing;
+use crate::units::reminders::ReminderConfig;
+use crate::units::rounding::RoundingPolicy;
+use crate::units::working_time_limits::WorkingTimeLimits;
+use crate::utils::event_hooks::StateEvent;
+use crate::utils::fatal::exit_with_error;
+use crate::utils::file_io::{expand_path, read_file, write_file, FromString, SafeFileEdit, ToFile};
+use crate::utils::journal::{record_in_journal, JournalTarget};
+use crate::utils::profiles::get_data_dir;
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
 
 pub const CONFIG_FILE: &str = "punch.cfg";
 const DEFAULT_TIME_MINS: i64 = 480;
 const DEFAULT_PUNCH_IN_TASK: &str = "Starting-up";
 const DEFAULT_BREAK_TASK: &str = "Break";
 const SHOW_TIMES_IN_HOURS_DEFAULT: bool = true;
+const IDLE_THRESHOLD_MINUTES_DEFAULT: u64 = 30;
+const STALE_DAY_HOURS_DEFAULT: u64 = 16;
+const SPLIT_DAYS_AT_MIDNIGHT_DEFAULT: bool = false;
+const GIT_HISTORY_DEFAULT: bool = false;
+const WEEK_START_DEFAULT: Weekday = Weekday::Mon;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -20,6 +34,20 @@ pub struct Config {
     minutes_behind_non_neg: u64,
     editor_path: Option<String>,
     show_times_in_hours: Option<bool>,
+    focus_hook: Option<String>,
+    idle_threshold_minutes: Option<u64>,
+    stale_day_hours: Option<u64>,
+    close_stale_days_at: Option<String>,
+    split_days_at_midnight: Option<bool>,
+    timezone: Option<String>,
+    git_history: Option<bool>,
+    rounding: Option<RoundingPolicy>,
+    billing: Option<Billing>,
+    week_start: Option<String>,
+    break_policy: Option<BreakPolicy>,
+    working_time_limits: Option<WorkingTimeLimits>,
+    reminders: Option<ReminderConfig>,
+    event_hooks: Option<BTreeMap<String, String>>,
 }
 
 impl Config {
@@ -44,6 +72,20 @@ impl Config {
             } as u64,
             editor_path: Some("vim".to_string()),
             show_times_in_hours: show_times_in_hours,
+            focus_hook: None,
+            idle_threshold_minutes: None,
+            stale_day_hours: None,
+            close_stale_days_at: None,
+            split_days_at_midnight: None,
+            timezone: None,
+            git_history: None,
+            rounding: None,
+            billing: None,
+            week_start: None,
+            break_policy: None,
+            working_time_limits: None,
+            reminders: None,
+            event_hooks: None,
         };
     }
 
@@ -73,6 +115,100 @@ impl Config {
             .unwrap_or(SHOW_TIMES_IN_HOURS_DEFAULT);
     }
 
+    pub fn focus_hook(&self) -> Option<&String> {
+        return self.focus_hook.as_ref();
+    }
+
+    pub fn idle_threshold_minutes_or_default(&self) -> u64 {
+        return self
+            .idle_threshold_minutes
+            .unwrap_or(IDLE_THRESHOLD_MINUTES_DEFAULT);
+    }
+
+    pub fn stale_day_hours_or_default(&self) -> u64 {
+        return self.stale_day_hours.unwrap_or(STALE_DAY_HOURS_DEFAULT);
+    }
+
+    pub fn close_stale_days_at(&self) -> Option<&String> {
+        return self.close_stale_days_at.as_ref();
+    }
+
+    pub fn split_days_at_midnight_or_default(&self) -> bool {
+        return self
+            .split_days_at_midnight
+            .unwrap_or(SPLIT_DAYS_AT_MIDNIGHT_DEFAULT);
+    }
+
+    pub fn timezone(&self) -> Option<&String> {
+        return self.timezone.as_ref();
+    }
+
+    pub fn set_timezone(&mut self, timezone: Option<String>) {
+        self.timezone = timezone;
+    }
+
+    pub fn git_history_or_default(&self) -> bool {
+        return self.git_history.unwrap_or(GIT_HISTORY_DEFAULT);
+    }
+
+    pub fn rounding(&self) -> Option<&RoundingPolicy> {
+        return self.rounding.as_ref();
+    }
+
+    pub fn billing(&self) -> Option<&Billing> {
+        return self.billing.as_ref();
+    }
+
+    pub fn get_week_start(&self) -> Result<Weekday, String> {
+        return match &self.week_start {
+            Some(week_start) => week_start.parse::<Weekday>().map_err(|_| {
+                format!(
+                    "'{}' isn't a day of the week, so 'week_start' in the config is invalid. Try e.g. 'monday' or 'sun'.",
+                    week_start
+                )
+            }),
+            None => Ok(WEEK_START_DEFAULT),
+        };
+    }
+
+    /// Ranges would quietly come out wrong with the wrong week start, so an invalid one stops here.
+    pub fn week_start_or_default(&self) -> Weekday {
+        match self.get_week_start() {
+            Ok(week_start) => return week_start,
+            Err(err_msg) => exit_with_error(&err_msg),
+        }
+    }
+
+    pub fn break_policy(&self) -> Option<&BreakPolicy> {
+        return self.break_policy.as_ref();
+    }
+
+    pub fn working_time_limits(&self) -> Option<&WorkingTimeLimits> {
+        return self.working_time_limits.as_ref();
+    }
+
+    pub fn reminders(&self) -> Option<&ReminderConfig> {
+        return self.reminders.as_ref();
+    }
+
+    pub fn event_hook(&self, event_name: &str) -> Option<&String> {
+        return self.event_hooks.as_ref()?.get(event_name);
+    }
+
+    pub fn check_event_hook_names(&self) -> Result<(), String> {
+        for event_name in self.event_hooks.iter().flat_map(|x| x.keys()) {
+            if !StateEvent::ALL.iter().any(|x| x.as_str() == event_name) {
+                let allowed: Vec<&str> = StateEvent::ALL.iter().map(|x| x.as_str()).collect();
+                return Err(format!(
+                    "'{}' in 'event_hooks' isn't an event. Hooks can be set for: {}.",
+                    event_name,
+                    allowed.join(", ")
+                ));
+            }
+        }
+        return Ok(());
+    }
+
     pub fn get_seconds_behind(&self) -> i64 {
         let minutes_behind: i64 = self.minutes_behind;
         let seconds_in_addition: i64 = self.seconds_behind_in_addition.unwrap_or(0);
@@ -107,8 +243,19 @@ impl ToFile for Config {
     }
 
     fn write(&self) {
-        let path: &String = &self.get_path();
-        write_config(path, self);
+        save_config(self);
+    }
+
+    fn to_file_string(&self) -> String {
+        return self.as_string();
+    }
+
+    fn read_stored_string(&self) -> Option<String> {
+        let storage: Box<dyn Storage> = get_storage();
+        return match storage.has_config() {
+            true => Some(storage.read_config().as_string()),
+            false => None,
+        };
     }
 }
 
@@ -124,8 +271,8 @@ pub fn read_config(path: &String) -> Config {
 }
 
 pub fn create_default_config_if_not_exists() {
-    let config_path: String = expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
-    if !Path::new(&config_path).exists() {
+    let storage: Box<dyn Storage> = get_storage();
+    if !storage.has_config() {
         let default_config: Config = Config::new(
             DEFAULT_TIME_MINS,
             DEFAULT_PUNCH_IN_TASK.to_owned(),
@@ -134,21 +281,79 @@ pub fn create_default_config_if_not_exists() {
             Some(0),
             Some(SHOW_TIMES_IN_HOURS_DEFAULT),
         );
-        write_config(&config_path, &default_config);
+        storage.write_config(&default_config);
     }
 }
 
 pub fn get_config() -> Config {
-    let config_path: String = get_config_path();
-    let config: Config = read_config(&config_path);
-    return config;
+    return get_storage().read_config();
 }
 
 pub fn get_config_path() -> String {
-    return expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
+    return expand_path(&(get_data_dir() + &(CONFIG_FILE.to_owned())));
 }
 
 pub fn update_config(config: Config) {
-    let config_path: String = expand_path(&(BASE_DIR.to_owned() + &(CONFIG_FILE.to_owned())));
-    write_config(&config_path, &config)
+    save_config(&config);
+}
+
+fn save_config(config: &Config) {
+    let storage: Box<dyn Storage> = get_storage();
+    let before: Option<String> = match storage.has_config() {
+        true => Some(storage.read_config().as_string()),
+        false => None,
+    };
+    storage.write_config(config);
+    record_in_journal(JournalTarget::Config, before, Some(config.as_string()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn config_with_week_start(week_start: Option<&str>) -> Config {
+        let mut config: Config =
+            Config::new(480, "work".to_string(), "break".to_string(), 0, None, None);
+        config.week_start = week_start.map(|x| x.to_string());
+        return config;
+    }
+
+    #[test_case(None, Ok(Weekday::Mon) ; "defaults to monday")]
+    #[test_case(Some("sunday"), Ok(Weekday::Sun) ; "full name")]
+    #[test_case(Some("Sat"), Ok(Weekday::Sat) ; "short name")]
+    fn test_get_week_start(week_start: Option<&str>, expected: Result<Weekday, String>) {
+        assert_eq!(
+            config_with_week_start(week_start).get_week_start(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_check_event_hook_names() {
+        let mut config: Config = config_with_week_start(None);
+        assert_eq!(config.check_event_hook_names(), Ok(()));
+        config.event_hooks = Some(BTreeMap::from([
+            ("back-in".to_string(), "true".to_string()),
+            ("out".to_string(), "true".to_string()),
+        ]));
+        assert_eq!(config.check_event_hook_names(), Ok(()));
+        config
+            .event_hooks
+            .as_mut()
+            .unwrap()
+            .insert("punch-out".to_string(), "true".to_string());
+        assert!(config
+            .check_event_hook_names()
+            .unwrap_err()
+            .starts_with("'punch-out' in 'event_hooks' isn't an event."));
+    }
+
+    #[test]
+    fn test_invalid_week_start_is_reported() {
+        let err_msg: String = config_with_week_start(Some("sundy"))
+            .get_week_start()
+            .unwrap_err();
+        assert!(err_msg.starts_with("'sundy' isn't a day of the week"));
+    }
 }
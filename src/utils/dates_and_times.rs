@@ -1,4 +1,6 @@
-use chrono::{DateTime, Duration, Local, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use chrono_tz::Tz;
+use std::env;
 use std::mem;
 
 pub struct DateRange(pub NaiveDate, pub NaiveDate);
@@ -23,7 +25,207 @@ pub fn convert_date_to_date_str(date: NaiveDate) -> String {
     return date.format("%Y-%m-%d").to_string();
 }
 
+/// Calendar-aligned periods that ranges can be split into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    pub fn from_string(name: &str) -> Result<Self, String> {
+        return match name.to_lowercase().as_str() {
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "quarter" => Ok(Self::Quarter),
+            "year" => Ok(Self::Year),
+            other => Err(format!(
+                "'{}' isn't a period. Try one of: week, month, quarter, year.",
+                other
+            )),
+        };
+    }
+}
+
+pub fn get_week_range(date: NaiveDate, week_start: Weekday) -> (NaiveDate, NaiveDate) {
+    let days_since_start: u32 =
+        (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    let start: NaiveDate = date - Duration::days(days_since_start as i64);
+    return (start, start + Duration::days(6));
+}
+
+pub fn get_month_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start: NaiveDate = date
+        .with_day(1)
+        .expect("The first of a month always exists");
+    let next_month: NaiveDate = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .expect("The first of a month always exists");
+    return (start, next_month - Duration::days(1));
+}
+
+pub fn get_quarter_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first_month: u32 = 3 * (date.month0() / 3) + 1;
+    let start: NaiveDate = NaiveDate::from_ymd_opt(date.year(), first_month, 1)
+        .expect("The first of a month always exists");
+    let (_, end) = get_month_range(start + Duration::days(62));
+    return (start, end);
+}
+
+pub fn get_year_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    return (
+        NaiveDate::from_ymd_opt(date.year(), 1, 1).expect("January 1st always exists"),
+        NaiveDate::from_ymd_opt(date.year(), 12, 31).expect("December 31st always exists"),
+    );
+}
+
+/// The period of the given kind that the date falls in.
+pub fn get_period_range(
+    date: NaiveDate,
+    period: Period,
+    week_start: Weekday,
+) -> (NaiveDate, NaiveDate) {
+    return match period {
+        Period::Week => get_week_range(date, week_start),
+        Period::Month => get_month_range(date),
+        Period::Quarter => get_quarter_range(date),
+        Period::Year => get_year_range(date),
+    };
+}
+
+/// The periods covering the dates from `start_date` to `end_date`, including those only partly covered.
+pub fn split_into_periods(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    period: Period,
+    week_start: Weekday,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut periods: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    let mut period_date: NaiveDate = start_date;
+    while period_date <= end_date {
+        let (period_start, period_end) = get_period_range(period_date, period, week_start);
+        periods.push((period_start, period_end));
+        period_date = period_end + Duration::days(1);
+    }
+    return periods;
+}
+
 #[allow(dead_code)]
 pub fn get_todays_date_str() -> String {
     return convert_date_to_date_str(get_local_now().date_naive());
 }
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    return name.parse::<Tz>().map_err(|_| {
+        format!(
+            "'{}' isn't a known IANA timezone, e.g. 'Europe/London' or 'America/New_York'.",
+            name
+        )
+    });
+}
+
+pub fn get_system_timezone_name() -> Option<String> {
+    return iana_time_zone::get_timezone().ok();
+}
+
+pub fn get_working_timezone_name() -> Option<String> {
+    // The working timezone is set through TZ at startup, see `set_working_timezone`.
+    return match env::var("TZ") {
+        Ok(name) if parse_timezone(&name).is_ok() => Some(name),
+        _ => get_system_timezone_name(),
+    };
+}
+
+pub fn set_working_timezone(name: &str) {
+    // chrono's `Local` follows TZ, so this makes all local times use the working timezone.
+    env::set_var("TZ", name);
+}
+
+pub fn get_date_in_timezone(time: &DateTime<Local>, timezone_name: Option<&String>) -> NaiveDate {
+    return match timezone_name.map(|x| parse_timezone(x)) {
+        Some(Ok(tz)) => time.with_timezone(&tz).date_naive(),
+        _ => time.date_naive(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn date(date_str: &str) -> NaiveDate {
+        return NaiveDate::parse_from_str(date_str, "%Y-%m-%d").unwrap();
+    }
+
+    #[test_case("2026-10-14", Weekday::Mon, "2026-10-12", "2026-10-18" ; "midweek from monday")]
+    #[test_case("2026-10-12", Weekday::Mon, "2026-10-12", "2026-10-18" ; "on the week start")]
+    #[test_case("2026-10-18", Weekday::Mon, "2026-10-12", "2026-10-18" ; "on the last day")]
+    #[test_case("2026-10-14", Weekday::Sun, "2026-10-11", "2026-10-17" ; "midweek from sunday")]
+    #[test_case("2026-10-17", Weekday::Sun, "2026-10-11", "2026-10-17" ; "saturday from sunday")]
+    #[test_case("2027-01-01", Weekday::Mon, "2026-12-28", "2027-01-03" ; "across a year")]
+    fn test_get_week_range(day: &str, week_start: Weekday, start: &str, end: &str) {
+        assert_eq!(
+            get_week_range(date(day), week_start),
+            (date(start), date(end))
+        );
+    }
+
+    #[test_case("2026-01-01", "2026-01-01", "2026-03-31" ; "first day of q1")]
+    #[test_case("2026-05-20", "2026-04-01", "2026-06-30" ; "middle of q2")]
+    #[test_case("2026-09-30", "2026-07-01", "2026-09-30" ; "last day of q3")]
+    #[test_case("2026-11-30", "2026-10-01", "2026-12-31" ; "q4")]
+    #[test_case("2024-02-29", "2024-01-01", "2024-03-31" ; "leap day")]
+    fn test_get_quarter_range(day: &str, start: &str, end: &str) {
+        assert_eq!(get_quarter_range(date(day)), (date(start), date(end)));
+    }
+
+    #[test_case(Period::Week, "2026-10-12", "2026-10-18" ; "week")]
+    #[test_case(Period::Month, "2026-10-01", "2026-10-31" ; "month")]
+    #[test_case(Period::Quarter, "2026-10-01", "2026-12-31" ; "quarter")]
+    #[test_case(Period::Year, "2026-01-01", "2026-12-31" ; "year")]
+    fn test_get_period_range(period: Period, start: &str, end: &str) {
+        assert_eq!(
+            get_period_range(date("2026-10-14"), period, Weekday::Mon),
+            (date(start), date(end))
+        );
+    }
+
+    #[test_case(Period::Week, "2026-10-07", "2026-10-20", vec![("2026-10-05", "2026-10-11"), ("2026-10-12", "2026-10-18"), ("2026-10-19", "2026-10-25")] ; "partial weeks at both ends")]
+    #[test_case(Period::Month, "2026-01-31", "2026-03-01", vec![("2026-01-01", "2026-01-31"), ("2026-02-01", "2026-02-28"), ("2026-03-01", "2026-03-31")] ; "months of different lengths")]
+    #[test_case(Period::Quarter, "2026-11-15", "2027-01-10", vec![("2026-10-01", "2026-12-31"), ("2027-01-01", "2027-03-31")] ; "quarters across a year")]
+    #[test_case(Period::Year, "2026-06-01", "2026-06-01", vec![("2026-01-01", "2026-12-31")] ; "a single date")]
+    fn test_split_into_periods(
+        period: Period,
+        start: &str,
+        end: &str,
+        expected: Vec<(&str, &str)>,
+    ) {
+        let expected_periods: Vec<(NaiveDate, NaiveDate)> =
+            expected.iter().map(|(x, y)| (date(x), date(y))).collect();
+        assert_eq!(
+            split_into_periods(date(start), date(end), period, Weekday::Mon),
+            expected_periods
+        );
+    }
+
+    #[test]
+    fn test_split_into_periods_with_sunday_weeks() {
+        assert_eq!(
+            split_into_periods(
+                date("2026-10-11"),
+                date("2026-10-18"),
+                Period::Week,
+                Weekday::Sun
+            ),
+            vec![
+                (date("2026-10-11"), date("2026-10-17")),
+                (date("2026-10-18"), date("2026-10-24"))
+            ]
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env::var;
+use std::fs::{read_dir, read_to_string, remove_file, ReadDir};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::units::day::DAILY_DIR;
+use crate::user_interaction::prompt::{can_prompt, prompt_for_secret};
+use crate::utils::balance::BALANCE_FILE;
+use crate::utils::config::CONFIG_FILE;
+use crate::utils::fatal::exit_with_error;
+use crate::utils::file_io::expand_path;
+use crate::utils::journal::JOURNAL_FILE;
+use crate::utils::profiles::get_data_dir;
+
+pub const ENCRYPTION_FILE: &str = "encryption";
+pub const DEFAULT_KEYFILE: &str = "~/.punch-card.key";
+pub const PASSPHRASE_VAR: &str = "PUNCH_PASSPHRASE";
+const ENCRYPTED_HEADER: &str = "punch-encrypted-v1\n";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+const SALT_LENGTH: usize = 16;
+
+// Each data dir's key is only worked out once per run, so a passphrase is asked for at most once
+// even when a command reads several profiles.
+static KEYS: Mutex<BTreeMap<String, Result<[u8; KEY_LENGTH], String>>> =
+    Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EncryptionSettings {
+    keyfile: Option<String>,
+    passphrase_salt: Option<String>,
+}
+
+impl EncryptionSettings {
+    pub fn with_keyfile(keyfile: String) -> Self {
+        return Self {
+            keyfile: Some(keyfile),
+            passphrase_salt: None,
+        };
+    }
+
+    pub fn with_new_passphrase_salt() -> Self {
+        let salt: [u8; SALT_LENGTH] = rand::random();
+        return Self {
+            keyfile: None,
+            passphrase_salt: Some(to_hex(&salt)),
+        };
+    }
+
+    pub fn get_keyfile(&self) -> Option<&String> {
+        return self.keyfile.as_ref();
+    }
+
+    pub fn describe(&self) -> String {
+        return match &self.keyfile {
+            Some(keyfile) => format!("a key from '{}'", keyfile),
+            None => format!("a passphrase (from ${} or asked for)", PASSPHRASE_VAR),
+        };
+    }
+
+    fn derive_key(&self) -> Result<[u8; KEY_LENGTH], String> {
+        if let Some(keyfile) = &self.keyfile {
+            let key_hex: String = read_to_string(expand_path(keyfile))
+                .map_err(|err| format!("Couldn't read the keyfile '{}': {}", keyfile, err))?;
+            let key_bytes: Vec<u8> = from_hex(key_hex.trim())?;
+            return key_bytes
+                .try_into()
+                .map_err(|_| format!("The keyfile '{}' doesn't hold a 32 byte key.", keyfile));
+        }
+        let passphrase: String = match var(PASSPHRASE_VAR) {
+            Ok(passphrase) => passphrase,
+            Err(_) if can_prompt() => prompt_for_secret("Passphrase for the punch data:"),
+            Err(_) => {
+                return Err(format!(
+                "The data is encrypted with a passphrase. Set ${} to use punch without a terminal.",
+                PASSPHRASE_VAR
+            ))
+            }
+        };
+        return self.derive_key_from_passphrase(&passphrase);
+    }
+
+    fn derive_key_from_passphrase(&self, passphrase: &str) -> Result<[u8; KEY_LENGTH], String> {
+        let salt: Vec<u8> = from_hex(
+            self.passphrase_salt
+                .as_ref()
+                .ok_or("The encryption settings have neither a keyfile nor a salt.")?,
+        )?;
+        let mut key: [u8; KEY_LENGTH] = [0; KEY_LENGTH];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|err| format!("Couldn't derive a key from the passphrase: {}", err))?;
+        return Ok(key);
+    }
+}
+
+pub fn get_encryption_settings_path() -> String {
+    return expand_path(&(get_data_dir() + ENCRYPTION_FILE));
+}
+
+pub fn get_encryption_settings() -> Option<EncryptionSettings> {
+    let yaml_str: String = read_to_string(get_encryption_settings_path()).ok()?;
+    return Some(serde_yaml::from_str(&yaml_str).expect("Encryption settings are malformed!"));
+}
+
+pub fn set_encryption_settings(settings: Option<&EncryptionSettings>) {
+    let path: String = get_encryption_settings_path();
+    match settings {
+        // Written directly since these settings are needed to read everything else.
+        Some(settings) => std::fs::write(&path, serde_yaml::to_string(settings).unwrap())
+            .expect("Couldn't write the encryption settings!"),
+        None => {
+            if Path::new(&path).exists() {
+                remove_file(&path).expect("Couldn't remove the encryption settings!");
+            }
+        }
+    }
+}
+
+fn get_cached_key(data_dir: &str) -> Option<Result<[u8; KEY_LENGTH], String>> {
+    return KEYS
+        .lock()
+        .expect("Key cache poisoned!")
+        .get(data_dir)
+        .cloned();
+}
+
+fn try_get_key(settings: &EncryptionSettings) -> Result<[u8; KEY_LENGTH], String> {
+    let data_dir: String = expand_path(&get_data_dir());
+    if let Some(key_result) = get_cached_key(&data_dir) {
+        return key_result;
+    }
+    // Not derived under the lock, as it can wait on a passphrase prompt.
+    let key_result: Result<[u8; KEY_LENGTH], String> = settings.derive_key();
+    KEYS.lock()
+        .expect("Key cache poisoned!")
+        .insert(data_dir, key_result.clone());
+    return key_result;
+}
+
+fn get_key(settings: &EncryptionSettings) -> [u8; KEY_LENGTH] {
+    match try_get_key(settings) {
+        Ok(key) => return key,
+        Err(err_msg) => exit_with_error(&err_msg),
+    }
+}
+
+/// Works out the key up front for `punch serve`, which can't stop to ask for a passphrase.
+pub fn derive_key_for_unattended_use() -> Result<(), String> {
+    let settings: EncryptionSettings = match get_encryption_settings() {
+        Some(settings) => settings,
+        None => return Ok(()),
+    };
+    let has_key: bool = get_cached_key(&expand_path(&get_data_dir())).is_some();
+    if !has_key && settings.keyfile.is_none() && var(PASSPHRASE_VAR).is_err() {
+        return Err(format!(
+            "The data is encrypted with a passphrase, which 'punch serve' can't ask for. Set ${} first.",
+            PASSPHRASE_VAR
+        ));
+    }
+    return try_get_key(&settings).map(|_| ());
+}
+
+/// Asks for a new passphrase twice, as a typo would leave the data unreadable.
+pub fn ask_for_new_passphrase() -> Result<String, String> {
+    if !can_prompt() {
+        return Err(format!(
+            "Set ${} to choose a passphrase without a terminal.",
+            PASSPHRASE_VAR
+        ));
+    }
+    let passphrase: String = prompt_for_secret("New passphrase for the punch data:");
+    if passphrase.len() == 0 {
+        return Err("The passphrase can't be empty.".to_string());
+    }
+    if prompt_for_secret("Repeat the passphrase:") != passphrase {
+        return Err("The passphrases don't match.".to_string());
+    }
+    return Ok(passphrase);
+}
+
+/// Uses the key from `passphrase` for the rest of the run instead of asking for it.
+pub fn use_passphrase(settings: &EncryptionSettings, passphrase: &str) -> Result<(), String> {
+    let key: [u8; KEY_LENGTH] = settings.derive_key_from_passphrase(passphrase)?;
+    let data_dir: String = expand_path(&get_data_dir());
+    let mut keys = KEYS.lock().expect("Key cache poisoned!");
+    if keys.contains_key(&data_dir) {
+        return Err("The key was already worked out for this run.".to_string());
+    }
+    keys.insert(data_dir, Ok(key));
+    return Ok(());
+}
+
+/// Checks the key opens `encrypted_contents`, so nothing is rewritten with a wrong passphrase.
+pub fn check_key_decrypts(
+    settings: &EncryptionSettings,
+    encrypted_contents: &str,
+) -> Result<(), String> {
+    return decrypt(encrypted_contents, &try_get_key(settings)?)
+        .map(|_| ())
+        .map_err(|_| {
+            "That key doesn't decrypt the existing data, so nothing was changed.".to_string()
+        });
+}
+
+pub fn is_encrypted(contents: &str) -> bool {
+    return contents.starts_with(ENCRYPTED_HEADER);
+}
+
+/// Whether a path holds days, config or balance data, which are what get encrypted.
+pub fn is_encryptable_path(path: &str) -> bool {
+    let base_dir: String = expand_path(&get_data_dir());
+    let expanded_path: String = expand_path(path);
+    let relative_path: &str = match expanded_path.strip_prefix(&base_dir) {
+        Some(relative_path) => relative_path,
+        None => return false,
+    };
+    if relative_path.ends_with("-temp") {
+        return false;
+    }
+    return relative_path.starts_with(DAILY_DIR)
+        || [CONFIG_FILE, BALANCE_FILE, JOURNAL_FILE].contains(&relative_path);
+}
+
+pub fn get_encryptable_paths() -> Vec<String> {
+    let base_dir: String = expand_path(&get_data_dir());
+    let mut paths: Vec<String> = [CONFIG_FILE, BALANCE_FILE, JOURNAL_FILE]
+        .iter()
+        .map(|x| base_dir.clone() + x)
+        .filter(|x| Path::new(x).exists())
+        .collect();
+    let entries_result: Result<ReadDir, std::io::Error> = read_dir(base_dir + DAILY_DIR);
+    if let Ok(entries) = entries_result {
+        let mut day_paths: Vec<String> = entries
+            .filter_map(|x| x.ok())
+            .map(|x| x.path().to_string_lossy().to_string())
+            .filter(|x| is_encryptable_path(x))
+            .collect();
+        day_paths.sort();
+        paths.append(&mut day_paths);
+    }
+    return paths;
+}
+
+pub fn encrypt_if_enabled(path: &str, contents: String) -> String {
+    if !is_encryptable_path(path) {
+        return contents;
+    }
+    return match get_encryption_settings() {
+        Some(settings) => encrypt(&contents, &get_key(&settings)),
+        None => contents,
+    };
+}
+
+/// Like `encrypt_if_enabled`, for data that isn't stored in a file, e.g. rows of the SQLite database.
+pub fn encrypt_contents_if_enabled(contents: String) -> String {
+    return match get_encryption_settings() {
+        Some(settings) => encrypt(&contents, &get_key(&settings)),
+        None => contents,
+    };
+}
+
+pub fn decrypt_if_needed(contents: String) -> Result<String, String> {
+    if !is_encrypted(&contents) {
+        return Ok(contents);
+    }
+    let settings: EncryptionSettings = get_encryption_settings().ok_or(
+        "Found encrypted data but encryption isn't set up. Was the 'encryption' file removed?",
+    )?;
+    return decrypt(&contents, &get_key(&settings));
+}
+
+pub fn encrypt(plaintext: &str, key: &[u8; KEY_LENGTH]) -> String {
+    let cipher: XChaCha20Poly1305 = XChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce_bytes: [u8; NONCE_LENGTH] = rand::random();
+    let mut sealed: Vec<u8> = nonce_bytes.to_vec();
+    sealed.append(
+        &mut cipher
+            .encrypt(XNonce::from_slice(&nonce_bytes), plaintext.as_bytes())
+            .expect("Encryption failed!"),
+    );
+    return ENCRYPTED_HEADER.to_string() + &to_hex(&sealed) + "\n";
+}
+
+pub fn decrypt(contents: &str, key: &[u8; KEY_LENGTH]) -> Result<String, String> {
+    let sealed: Vec<u8> = from_hex(contents.trim_start_matches(ENCRYPTED_HEADER).trim())?;
+    if sealed.len() < NONCE_LENGTH {
+        return Err("Encrypted data is too short.".to_string());
+    }
+    let (nonce_bytes, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    let cipher: XChaCha20Poly1305 = XChaCha20Poly1305::new(Key::from_slice(key));
+    let plaintext: Vec<u8> = cipher
+        .decrypt(XNonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| "Couldn't decrypt the data: wrong key or the data was tampered with.")?;
+    return String::from_utf8(plaintext).map_err(|err| err.to_string());
+}
+
+pub fn generate_key_hex() -> String {
+    let key: [u8; KEY_LENGTH] = rand::random();
+    return to_hex(&key);
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    return bytes.iter().map(|x| format!("{:02x}", x)).collect();
+}
+
+fn from_hex(hex_str: &str) -> Result<Vec<u8>, String> {
+    if !hex_str.len().is_multiple_of(2) {
+        return Err("Expected an even number of hex digits.".to_string());
+    }
+    return (0..hex_str.len())
+        .step_by(2)
+        .map(|i| {
+            hex_str
+                .get(i..i + 2)
+                .and_then(|x| u8::from_str_radix(x, 16).ok())
+                .ok_or(format!("'{}' isn't valid hex.", hex_str))
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::profiles::with_data_dir;
+
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let key: [u8; KEY_LENGTH] = [7; KEY_LENGTH];
+        let encrypted: String = encrypt("note: call ACME about TICKET-12\n", &key);
+        assert!(encrypted.starts_with(ENCRYPTED_HEADER));
+        assert!(!encrypted.contains("ACME"));
+        assert_eq!(
+            decrypt(&encrypted, &key),
+            Ok("note: call ACME about TICKET-12\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key_and_tampering() {
+        let key: [u8; KEY_LENGTH] = [7; KEY_LENGTH];
+        let encrypted: String = encrypt("minutes_behind: 0\n", &key);
+        assert!(decrypt(&encrypted, &[8; KEY_LENGTH]).is_err());
+
+        let last_digit: char = encrypted.trim_end().chars().last().unwrap();
+        let tampered_digit: char = if last_digit == '0' { '1' } else { '0' };
+        let tampered: String = encrypted.trim_end()[..encrypted.trim_end().len() - 1].to_string()
+            + &tampered_digit.to_string();
+        assert!(decrypt(&tampered, &key).is_err());
+    }
+
+    #[test]
+    fn test_keys_are_kept_per_data_dir() {
+        let dir = std::env::temp_dir().join(format!("punch-keys-{}", std::process::id()));
+        let data_dirs: Vec<String> = ["first", "second"]
+            .iter()
+            .map(|x| dir.join(x).to_string_lossy().to_string() + "/")
+            .collect();
+        let mut keys: Vec<[u8; KEY_LENGTH]> = Vec::new();
+        for data_dir in &data_dirs {
+            std::fs::create_dir_all(data_dir).unwrap();
+            let keyfile: String = data_dir.clone() + "key";
+            std::fs::write(&keyfile, generate_key_hex()).unwrap();
+            let settings: EncryptionSettings = EncryptionSettings::with_keyfile(keyfile);
+            keys.push(with_data_dir(data_dir, || try_get_key(&settings)).unwrap());
+        }
+        let first_settings: EncryptionSettings =
+            EncryptionSettings::with_keyfile(data_dirs[0].clone() + "key");
+        let first_key_again: Result<[u8; KEY_LENGTH], String> =
+            with_data_dir(&data_dirs[0], || try_get_key(&first_settings));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_ne!(keys[0], keys[1]);
+        assert_eq!(first_key_again, Ok(keys[0]));
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes: Vec<u8> = vec![0, 1, 171, 255];
+        assert_eq!(to_hex(&bytes), "0001abff");
+        assert_eq!(from_hex("0001abff"), Ok(bytes));
+        assert!(from_hex("0g").is_err());
+    }
+}
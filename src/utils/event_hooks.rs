@@ -0,0 +1,154 @@
+use chrono::{DateTime, Local};
+use serde_json::json;
+use std::sync::Mutex;
+
+use crate::units::day::Day;
+use crate::units::interval::DATETIME_FMT;
+use crate::utils::config::{get_config, Config};
+use crate::utils::hooks::run_hook_command_with_input;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateEvent {
+    In,
+    Out,
+    Pause,
+    Resume,
+    Task,
+    BackIn,
+    Note,
+}
+
+/// A hook waiting for the command (or API request) to finish, with everything it will be given.
+struct QueuedHook {
+    event: StateEvent,
+    hook: String,
+    env_vars: Vec<(String, String)>,
+    payload: String,
+}
+
+// Hooks only run once every write for the command is done, so they never see a half-updated state.
+static QUEUED_HOOKS: Mutex<Vec<QueuedHook>> = Mutex::new(Vec::new());
+
+impl StateEvent {
+    pub const ALL: [StateEvent; 7] = [
+        Self::In,
+        Self::Out,
+        Self::Pause,
+        Self::Resume,
+        Self::Task,
+        Self::BackIn,
+        Self::Note,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Self::In => "in",
+            Self::Out => "out",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Task => "task",
+            Self::BackIn => "back-in",
+            Self::Note => "note",
+        };
+    }
+}
+
+pub fn render_event_payload(
+    event: StateEvent,
+    previous_task: Option<String>,
+    new_task: Option<String>,
+    at: &DateTime<Local>,
+    day: &Day,
+) -> String {
+    let payload = json!({
+        "event": event.as_str(),
+        "previous_task": previous_task,
+        "new_task": new_task,
+        "time": at.format(DATETIME_FMT).to_string(),
+        "day_start": day.get_day_start_as_str(),
+        "day_end": day.get_day_end_as_str(),
+        "day": day,
+    });
+    return payload.to_string();
+}
+
+/// Queues the hook set for `event` in the config, if there is one, to run with `run_queued_event_hooks`.
+pub fn queue_event_hook(
+    event: StateEvent,
+    previous_task: Option<String>,
+    new_task: Option<String>,
+    at: &DateTime<Local>,
+    day: &Day,
+) {
+    let config: Config = get_config();
+    if let Err(err_msg) = config.check_event_hook_names() {
+        eprintln!("{}", err_msg);
+    }
+    let hook: String = match config.event_hook(event.as_str()) {
+        Some(hook) => hook.clone(),
+        None => return,
+    };
+    let env_vars: Vec<(String, String)> = vec![
+        ("PUNCH_EVENT".to_string(), event.as_str().to_string()),
+        (
+            "PUNCH_PREVIOUS_TASK".to_string(),
+            previous_task.clone().unwrap_or_default(),
+        ),
+        (
+            "PUNCH_NEW_TASK".to_string(),
+            new_task.clone().unwrap_or_default(),
+        ),
+    ];
+    let payload: String = render_event_payload(event, previous_task, new_task, at, day);
+    QUEUED_HOOKS
+        .lock()
+        .expect("Event hook lock poisoned!")
+        .push(QueuedHook {
+            event: event,
+            hook: hook,
+            env_vars: env_vars,
+            payload: payload,
+        });
+}
+
+/// Runs the queued hooks in order. Everything has been written by now, so a failing hook is only reported.
+pub fn run_queued_event_hooks() {
+    let queued_hooks: Vec<QueuedHook> =
+        std::mem::take(&mut *QUEUED_HOOKS.lock().expect("Event hook lock poisoned!"));
+    for queued in queued_hooks {
+        if let Err(err_msg) =
+            run_hook_command_with_input(&queued.hook, queued.env_vars, &queued.payload)
+        {
+            eprintln!("The '{}' hook failed: {}", queued.event.as_str(), err_msg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use serde_json::Value;
+
+    #[test]
+    fn test_render_event_payload() {
+        let start: DateTime<Local> = Local.with_ymd_and_hms(2026, 10, 5, 9, 0, 0).unwrap();
+        let mut day: Day = Day::new(&start, "coding".to_string(), 480, None);
+        let at: DateTime<Local> = start + Duration::hours(2);
+        day.start_break_at("Break".to_string(), &at).unwrap();
+        let payload: Value = serde_json::from_str(&render_event_payload(
+            StateEvent::Pause,
+            Some("coding".to_string()),
+            Some("Break".to_string()),
+            &at,
+            &day,
+        ))
+        .unwrap();
+        assert_eq!(payload["event"], "pause");
+        assert_eq!(payload["previous_task"], "coding");
+        assert_eq!(payload["new_task"], "Break");
+        assert_eq!(payload["day_end"], Value::Null);
+        assert_eq!(payload["day"]["on_break"], true);
+        assert_eq!(payload["day"]["timeblocks"][1]["task_name"], "Break");
+    }
+}
@@ -0,0 +1,19 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Set by `punch serve`, where one bad request mustn't stop the server for everyone else.
+static UNWIND_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+pub fn unwind_on_error() {
+    UNWIND_ON_ERROR.store(true, Ordering::SeqCst);
+}
+
+/// Stops on an error nothing can be done about, e.g. data that can't be decrypted.
+///
+/// A CLI command exits, while `punch serve` unwinds back to the request, which gets a `500`.
+pub fn exit_with_error(err_msg: &str) -> ! {
+    if UNWIND_ON_ERROR.load(Ordering::SeqCst) {
+        std::panic::panic_any(err_msg.to_string());
+    }
+    eprintln!("{}", err_msg);
+    std::process::exit(1);
+}
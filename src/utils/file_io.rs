@@ -1,4 +1,7 @@
 use crate::utils::config::get_config;
+use crate::utils::encryption::{decrypt_if_needed, encrypt_if_enabled};
+use crate::utils::fatal::exit_with_error;
+use crate::utils::lock::without_data_lock;
 use std::env;
 use std::env::var;
 use std::fs::{create_dir_all, read_to_string, remove_file, File, OpenOptions};
@@ -9,6 +12,7 @@ pub const BASE_DIR: &str = "~/.punch-card/";
 
 pub fn write_file(path: &str, contents: String) {
     let path_str_to_write: String = expand_path(path);
+    let contents: String = encrypt_if_enabled(&path_str_to_write, contents);
     let path_to_write: &Path = Path::new(&path_str_to_write);
     if path_to_write.exists() {
         remove_file(path_str_to_write.clone()).expect("Should be able to delete");
@@ -25,9 +29,28 @@ pub fn write_file(path: &str, contents: String) {
     }
 }
 
+pub fn append_to_file(path: &str, contents: String) {
+    let path_str_to_write: String = expand_path(path);
+    let file_result: Result<File, std::io::Error> = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path_str_to_write);
+    if let Ok(mut file) = file_result {
+        file.write_all(contents.as_bytes())
+            .expect("Couldn't write to file!");
+    } else {
+        panic!("Couldn't open file {path}");
+    }
+}
+
 pub fn read_file(path: &str) -> Result<String, std::io::Error> {
     let path_to_read = expand_path(path);
-    return read_to_string(path_to_read);
+    let contents: String = read_to_string(path_to_read)?;
+    // There's nothing a caller could do with data it can't decrypt, so this stops here.
+    return match decrypt_if_needed(contents) {
+        Ok(decrypted) => Ok(decrypted),
+        Err(err_msg) => exit_with_error(&format!("Couldn't read '{}': {}", path, err_msg)),
+    };
 }
 
 pub fn create_dir_if_not_exists(path: &str) {
@@ -72,12 +95,15 @@ pub fn edit_file(path: &str) {
 
     println!("Opening config with '{}'...", editor);
 
-    std::process::Command::new(editor)
-        .args(args)
-        .spawn()
-        .expect("Error: Failed to run editor, you can set 'editor_path' in the config")
-        .wait()
-        .expect("Error: Editor returned a non-zero status");
+    // Other punch commands can run while the editor is open, so the edit is checked before saving.
+    without_data_lock(|| {
+        std::process::Command::new(editor)
+            .args(args)
+            .spawn()
+            .expect("Error: Failed to run editor, you can set 'editor_path' in the config")
+            .wait()
+            .expect("Error: Editor returned a non-zero status")
+    });
 
     println!("Editor closed.");
 }
@@ -92,28 +118,29 @@ pub trait ToFile {
     fn get_path(&self) -> String;
 
     fn write(&self);
+
+    fn to_file_string(&self) -> String;
+
+    /// What's currently stored for this, as it would be written by `to_file_string`.
+    fn read_stored_string(&self) -> Option<String>;
 }
 
 pub trait SafeFileEdit<T: FromString<T, E> + ToFile, E>: ToFile + FromString<T, E> {
     fn safe_edit_from_file(&self) {
+        // The current contents are written out rather than copied since they may not be in a file.
         let std_path: String = self.get_path();
         let temp_path: String = (&std_path).to_string() + "-temp";
-        std::process::Command::new("cp")
-            .args([&std_path, &temp_path])
-            .output()
-            .expect("Failed to create temporary data!");
+        let original: String = self.to_file_string();
+        write_file(&temp_path, original.clone());
 
         edit_file(&temp_path);
         let yaml_str: String = read_file(&temp_path).unwrap();
         let new_result: Result<T, E> = T::try_from_string(&yaml_str);
         match new_result {
-            Ok(new_value) => {
-                std::process::Command::new("rm")
-                    .arg(&std_path)
-                    .output()
-                    .expect("Failed to clean up the temporary data!");
-                new_value.write();
-            }
+            _ if self.read_stored_string() != Some(original) => println!(
+                "Another punch command changed this while it was being edited. Please try again."
+            ),
+            Ok(new_value) => new_value.write(),
             Err(_) => println!("Invalid Config created. Please try again"),
         };
         std::process::Command::new("rm")
@@ -0,0 +1,124 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+use crate::utils::config::get_config;
+use crate::utils::file_io::{expand_path, write_file};
+use crate::utils::profiles::get_data_dir;
+
+const GITIGNORE_CONTENTS: &str = "*-temp\nheartbeats/\njournal\nlock\nprofiles/\nprofiles.cfg\n";
+const DEFAULT_COMMIT_MESSAGE: &str = "punch";
+
+static COMMAND_DESCRIPTION: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_command_description(description: String) {
+    *COMMAND_DESCRIPTION.lock().expect("History lock poisoned!") = Some(description);
+}
+
+pub fn describe_command(command_args: &[String], time: &str) -> String {
+    // e.g. "punch task 'review' at 14:05"
+    let mut description: String = DEFAULT_COMMIT_MESSAGE.to_string();
+    if let Some(name) = command_args.first() {
+        description += &format!(" {}", name);
+    }
+    for arg in command_args.iter().skip(1) {
+        description += &format!(" '{}'", arg);
+    }
+    return description + &format!(" at {}", time);
+}
+
+fn get_history_dir() -> String {
+    return expand_path(&get_data_dir());
+}
+
+pub fn run_git(args: &[&str]) -> Result<String, String> {
+    let output_result: Result<Output, std::io::Error> = Command::new("git")
+        .arg("-C")
+        .arg(get_history_dir())
+        .args(args)
+        .output();
+    return match output_result {
+        Ok(output) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(output) => Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(err) => Err(format!("Couldn't run git: {}", err)),
+    };
+}
+
+fn get_identity_args() -> Vec<String> {
+    // Commits need an identity, so fall back to one if the user hasn't configured git.
+    if run_git(&["config", "user.email"]).is_ok() {
+        return Vec::new();
+    }
+    return vec![
+        "-c".to_string(),
+        "user.name=punch".to_string(),
+        "-c".to_string(),
+        "user.email=punch@localhost".to_string(),
+    ];
+}
+
+fn commit(message: &str) -> Result<(), String> {
+    if run_git(&["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(());
+    }
+    let mut args: Vec<String> = get_identity_args();
+    args.extend(["commit", "-q", "-m", message].map(|x| x.to_string()));
+    let args_as_str: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+    return run_git(&args_as_str).map(|_| ());
+}
+
+fn create_repo_if_not_exists() -> Result<(), String> {
+    if has_history() {
+        return Ok(());
+    }
+    run_git(&["init", "-q"])?;
+    write_file(
+        &(get_history_dir() + ".gitignore"),
+        GITIGNORE_CONTENTS.to_string(),
+    );
+    run_git(&["add", "-A"])?;
+    return commit("Start tracking history");
+}
+
+/// Whether there's a history, even if `git_history` has since been turned off.
+pub fn has_history() -> bool {
+    return Path::new(&(get_history_dir() + ".git")).exists();
+}
+
+pub fn record_change(path: &str) {
+    if !get_config().git_history_or_default() {
+        return;
+    }
+    let message: String = COMMAND_DESCRIPTION
+        .lock()
+        .expect("History lock poisoned!")
+        .clone()
+        .unwrap_or(DEFAULT_COMMIT_MESSAGE.to_string());
+    let result: Result<(), String> = create_repo_if_not_exists()
+        .and_then(|_| run_git(&["add", "--", path]).map(|_| ()))
+        .and_then(|_| commit(&message));
+    if let Err(err_msg) = result {
+        eprintln!("Couldn't record the change in the history: {}", err_msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(vec![], "punch at 14:05" ; "no command")]
+    #[test_case(vec!["out"], "punch out at 14:05" ; "no arguments")]
+    #[test_case(vec!["task", "review"], "punch task 'review' at 14:05" ; "one argument")]
+    #[test_case(vec!["note", "a b"], "punch note 'a b' at 14:05" ; "argument with spaces")]
+    fn test_describe_command(args: Vec<&str>, expected: &str) {
+        let command_args: Vec<String> = args.iter().map(|x| x.to_string()).collect();
+        assert_eq!(describe_command(&command_args, "14:05"), expected);
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::Write;
+use std::process::{Child, Command, ExitStatus, Stdio};
+
+pub fn run_hook_command(command: &str, env_vars: Vec<(String, String)>) -> Result<(), String> {
+    // Hooks are run through the shell so users can use pipes, redirects and so on.
+    let status_result: Result<ExitStatus, std::io::Error> = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env_vars)
+        .status();
+    return match status_result {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Hook '{}' failed with {}", command, status)),
+        Err(err) => Err(format!("Couldn't run hook '{}': {}", command, err)),
+    };
+}
+
+/// Runs a hook like `run_hook_command`, giving it `input` (e.g. a JSON payload) on stdin.
+pub fn run_hook_command_with_input(
+    command: &str,
+    env_vars: Vec<(String, String)>,
+    input: &str,
+) -> Result<(), String> {
+    let child_result: Result<Child, std::io::Error> = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(env_vars)
+        .stdin(Stdio::piped())
+        .spawn();
+    let mut child: Child =
+        child_result.map_err(|err| format!("Couldn't run hook '{}': {}", command, err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes stdin early, which isn't a failure.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    return match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Hook '{}' failed with {}", command, status)),
+        Err(err) => Err(format!("Couldn't run hook '{}': {}", command, err)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_gets_input_on_stdin() {
+        assert_eq!(
+            run_hook_command_with_input("grep -q '\"event\"'", Vec::new(), "{\"event\": \"x\"}"),
+            Ok(())
+        );
+        assert!(run_hook_command_with_input("grep -q missing", Vec::new(), "{}").is_err());
+    }
+}
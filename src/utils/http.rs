@@ -0,0 +1,278 @@
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// The parts of an HTTP/1.1 request the local API needs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+    /// Keyed by the lowercase header name.
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// The path split into its non-empty segments, e.g. `/days/2026-10-05` gives `["days", "2026-10-05"]`.
+    pub fn get_path_segments(&self) -> Vec<&str> {
+        return self.path.split('/').filter(|x| x.len() > 0).collect();
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        return self.headers.get(&name.to_lowercase()).map(|x| x.as_str());
+    }
+
+    /// The host name from the `Host` header without the port, e.g. `localhost:7421` gives `localhost`.
+    pub fn get_host_name(&self) -> Option<&str> {
+        let host: &str = self.get_header("host")?;
+        if host.starts_with('[') {
+            return host.split_inclusive(']').next();
+        }
+        return host.split(':').next();
+    }
+
+    /// Whether the body is declared as JSON, ignoring parameters like `; charset=utf-8`.
+    pub fn has_json_content_type(&self) -> bool {
+        return match self.get_header("content-type") {
+            Some(content_type) => content_type
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .eq_ignore_ascii_case("application/json"),
+            None => false,
+        };
+    }
+
+    /// The body as a JSON object, with an empty body treated as an empty object.
+    pub fn get_json_body(&self) -> Result<Value, String> {
+        if self.body.trim().len() == 0 {
+            return Ok(json!({}));
+        }
+        let value: Value = serde_json::from_str(&self.body)
+            .map_err(|err| format!("The body isn't valid JSON: {}", err))?;
+        if !value.is_object() {
+            return Err("The body should be a JSON object.".to_string());
+        }
+        return Ok(value);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl HttpResponse {
+    pub fn ok(body: Value) -> Self {
+        return Self {
+            status: 200,
+            body: body,
+        };
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        return Self {
+            status: status,
+            body: json!({ "error": message }),
+        };
+    }
+
+    pub fn render(&self) -> String {
+        let body_str: String = self.body.to_string();
+        return format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            get_reason_phrase(self.status),
+            body_str.len(),
+            body_str
+        );
+    }
+
+    pub fn write_to(&self, stream: &mut impl Write) -> Result<(), String> {
+        return stream
+            .write_all(self.render().as_bytes())
+            .and_then(|_| stream.flush())
+            .map_err(|err| format!("Couldn't send the response: {}", err));
+    }
+}
+
+fn get_reason_phrase(status: u16) -> &'static str {
+    return match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    };
+}
+
+pub fn read_request(reader: &mut impl BufRead) -> Result<HttpRequest, String> {
+    let read_line = |reader: &mut dyn BufRead| -> Result<String, String> {
+        let mut line: String = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|err| format!("Couldn't read the request: {}", err))?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    };
+    let request_line: String = read_line(reader)?;
+    let parts: Vec<&str> = request_line.split_whitespace().collect();
+    let (method, target) = match parts.as_slice() {
+        [method, target, version] if version.starts_with("HTTP/") => (*method, *target),
+        _ => return Err(format!("Invalid request line '{}'", request_line)),
+    };
+
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    loop {
+        let header: String = read_line(reader)?;
+        if header.len() == 0 {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let content_length: usize = match headers.get("content-length") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid Content-Length '{}'", value))?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_BYTES {
+        return Err(format!("The body can be at most {} bytes.", MAX_BODY_BYTES));
+    }
+    let mut body_bytes: Vec<u8> = vec![0; content_length];
+    reader
+        .read_exact(&mut body_bytes)
+        .map_err(|err| format!("Couldn't read the request body: {}", err))?;
+    let body: String =
+        String::from_utf8(body_bytes).map_err(|_| "The body isn't valid UTF-8.".to_string())?;
+
+    let (path, query_str) = target.split_once('?').unwrap_or((target, ""));
+    return Ok(HttpRequest {
+        method: method.to_uppercase(),
+        path: decode_url_component(path)?,
+        query: parse_query(query_str)?,
+        headers: headers,
+        body: body,
+    });
+}
+
+pub fn parse_query(query_str: &str) -> Result<BTreeMap<String, String>, String> {
+    let mut query: BTreeMap<String, String> = BTreeMap::new();
+    for pair in query_str.split('&').filter(|x| x.len() > 0) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        query.insert(decode_url_component(key)?, decode_url_component(value)?);
+    }
+    return Ok(query);
+}
+
+/// Decodes `%XX` escapes and `+` for spaces, e.g. `last%20week` or `last+week` give `last week`.
+pub fn decode_url_component(component: &str) -> Result<String, String> {
+    let bytes: &[u8] = component.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut ind: usize = 0;
+    while ind < bytes.len() {
+        match bytes[ind] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex: &str = component
+                    .get(ind + 1..ind + 3)
+                    .ok_or(format!("Invalid escape in '{}'", component))?;
+                let byte: u8 = u8::from_str_radix(hex, 16)
+                    .map_err(|_| format!("Invalid escape in '{}'", component))?;
+                decoded.push(byte);
+                ind += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        ind += 1;
+    }
+    return String::from_utf8(decoded).map_err(|_| format!("'{}' isn't valid UTF-8", component));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Read};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    #[test]
+    fn test_read_request_with_query_and_body() {
+        let raw: &str = "POST /task?dry+run=1&when=last%20week HTTP/1.1\r\nHost: localhost\r\ncontent-length: 18\r\n\r\n{\"task\": \"review\"}";
+        let request: HttpRequest = read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.get_path_segments(), vec!["task"]);
+        assert_eq!(request.query.get("dry run"), Some(&"1".to_string()));
+        assert_eq!(request.query.get("when"), Some(&"last week".to_string()));
+        assert_eq!(request.get_json_body().unwrap()["task"], "review");
+        assert_eq!(request.get_header("Content-Length"), Some("18"));
+        assert_eq!(request.get_host_name(), Some("localhost"));
+        assert!(!request.has_json_content_type());
+    }
+
+    #[test]
+    fn test_host_name_and_content_type() {
+        let read = |headers: &str| {
+            let raw: String = format!("POST / HTTP/1.1\r\n{}\r\n", headers);
+            return read_request(&mut BufReader::new(raw.as_bytes())).unwrap();
+        };
+        let request: HttpRequest =
+            read("Host: 127.0.0.1:7421\r\nContent-Type: application/json; charset=utf-8\r\n");
+        assert_eq!(request.get_host_name(), Some("127.0.0.1"));
+        assert!(request.has_json_content_type());
+        assert_eq!(read("HOST: [::1]:7421\r\n").get_host_name(), Some("[::1]"));
+        assert_eq!(read("").get_host_name(), None);
+        assert!(!read("Content-Type: text/plain\r\n").has_json_content_type());
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let read = |raw: &str| read_request(&mut BufReader::new(raw.as_bytes()));
+        assert!(read("nonsense\r\n\r\n").is_err());
+        assert!(read("GET / HTTP/1.1\r\nContent-Length: ten\r\n\r\n").is_err());
+        assert!(read("GET /%zz HTTP/1.1\r\n\r\n").is_err());
+        let request: HttpRequest = read("POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n[]").unwrap();
+        assert!(request.get_json_body().is_err());
+    }
+
+    #[test]
+    fn test_render_response() {
+        assert_eq!(
+            HttpResponse::error(404, "No day").render(),
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: 18\r\nConnection: close\r\n\r\n{\"error\":\"No day\"}"
+        );
+    }
+
+    #[test]
+    fn test_round_trip_on_localhost() {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let request: HttpRequest =
+                read_request(&mut BufReader::new(stream.try_clone().unwrap())).unwrap();
+            let response: HttpResponse = HttpResponse::ok(json!({ "path": request.path }));
+            response.write_to(&mut &stream).unwrap();
+        });
+        let mut client: TcpStream = TcpStream::connect(address).unwrap();
+        client
+            .write_all(b"GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response: String = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("{\"path\":\"/status\"}"));
+    }
+}
@@ -0,0 +1,386 @@
+use chrono::prelude::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process;
+use std::sync::Mutex;
+
+use crate::storage::{get_storage, Storage};
+use crate::units::day::Day;
+use crate::units::interval::Dt;
+use crate::utils::balance::BalanceLedger;
+use crate::utils::config::Config;
+use crate::utils::file_io::{expand_path, read_file, write_file, FromString};
+use crate::utils::profiles::get_data_dir;
+
+pub const JOURNAL_FILE: &str = "journal";
+const MAX_JOURNAL_ENTRIES: usize = 50;
+
+// Set per run (or per request with `punch serve`) for commands whose changes should be undoable.
+static CURRENT_ENTRY: Mutex<Option<(String, String, DateTime<Local>)>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum JournalTarget {
+    Day(String),
+    Config,
+    Balance,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalChange {
+    target: JournalTarget,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    id: String,
+    command: String,
+    time: Dt,
+    changes: Vec<JournalChange>,
+}
+
+impl JournalEntry {
+    pub fn get_command(&self) -> &String {
+        return &self.command;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    applied: usize,
+}
+
+impl Journal {
+    pub fn as_string(&self) -> String {
+        return serde_yaml::to_string(&self).unwrap();
+    }
+
+    pub fn add_change(
+        &mut self,
+        id: &str,
+        command: &str,
+        time: &DateTime<Local>,
+        change: JournalChange,
+    ) {
+        // A new change makes anything that was undone impossible to redo.
+        self.entries.truncate(self.applied);
+        let is_same_command: bool = self.entries.last().map(|x| x.id == id).unwrap_or(false);
+        if !is_same_command {
+            self.entries.push(JournalEntry {
+                id: id.to_string(),
+                command: command.to_string(),
+                time: Dt(*time),
+                changes: Vec::new(),
+            });
+        }
+        let entry: &mut JournalEntry = self.entries.last_mut().expect("Entry was just added!");
+        // Only the state from before the whole command matters for undoing it.
+        match entry.changes.iter_mut().find(|x| x.target == change.target) {
+            Some(existing) => existing.after = change.after,
+            None => entry.changes.push(change),
+        }
+        if self.entries.len() > MAX_JOURNAL_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.applied = self.entries.len();
+    }
+
+    pub fn undo(&mut self) -> Option<JournalEntry> {
+        if self.applied == 0 {
+            return None;
+        }
+        self.applied -= 1;
+        return Some(self.entries[self.applied].clone());
+    }
+
+    pub fn redo(&mut self) -> Option<JournalEntry> {
+        if self.applied >= self.entries.len() {
+            return None;
+        }
+        self.applied += 1;
+        return Some(self.entries[self.applied - 1].clone());
+    }
+}
+
+pub fn start_journal_entry(command: String, now: &DateTime<Local>) {
+    let id: String = format!(
+        "{}-{}",
+        process::id(),
+        now.timestamp_nanos_opt().unwrap_or(0)
+    );
+    *CURRENT_ENTRY.lock().expect("Journal lock poisoned!") = Some((id, command, *now));
+}
+
+pub fn get_journal_path() -> String {
+    return expand_path(&(get_data_dir() + JOURNAL_FILE));
+}
+
+pub fn get_journal() -> Journal {
+    let journal_path: String = get_journal_path();
+    if !Path::new(&journal_path).exists() {
+        return Journal::default();
+    }
+    let yaml_str: String = read_file(&journal_path).unwrap();
+    return serde_yaml::from_str(&yaml_str).expect("Journal file is malformed!");
+}
+
+pub fn write_journal(journal: &Journal) {
+    write_file(&get_journal_path(), journal.as_string());
+}
+
+pub fn record_in_journal(target: JournalTarget, before: Option<String>, after: Option<String>) {
+    let current_entry: Option<(String, String, DateTime<Local>)> = CURRENT_ENTRY
+        .lock()
+        .expect("Journal lock poisoned!")
+        .clone();
+    let (id, command, time) = match current_entry {
+        Some(entry) => entry,
+        None => return,
+    };
+    if before == after {
+        return;
+    }
+    let mut journal: Journal = get_journal();
+    journal.add_change(
+        &id,
+        &command,
+        &time,
+        JournalChange {
+            target: target,
+            before: before,
+            after: after,
+        },
+    );
+    write_journal(&journal);
+}
+
+fn apply_state(storage: &dyn Storage, target: &JournalTarget, state: &Option<String>) {
+    match (target, state) {
+        (JournalTarget::Day(_), Some(day_str)) => storage.write_day(&Day::from_string(day_str)),
+        (JournalTarget::Day(date_str), None) => storage.delete_day(date_str),
+        (JournalTarget::Config, Some(config_str)) => {
+            storage.write_config(&Config::from_string(config_str))
+        }
+        (JournalTarget::Config, None) => (),
+        (JournalTarget::Balance, Some(ledger_str)) => storage.write_balance_ledger(
+            &serde_yaml::from_str(ledger_str).expect("Journal file is malformed!"),
+        ),
+        (JournalTarget::Balance, None) => storage.write_balance_ledger(&BalanceLedger::default()),
+    }
+}
+
+fn read_state(storage: &dyn Storage, target: &JournalTarget) -> Option<String> {
+    return match target {
+        JournalTarget::Day(date_str) => storage
+            .read_day_from_date_str(date_str)
+            .ok()
+            .map(|x| x.as_string()),
+        JournalTarget::Config => match storage.has_config() {
+            true => Some(storage.read_config().as_string()),
+            false => None,
+        },
+        JournalTarget::Balance => Some(storage.read_balance_ledger().as_string()),
+    };
+}
+
+fn describe_target(target: &JournalTarget) -> String {
+    return match target {
+        JournalTarget::Day(date_str) => format!("the day '{}'", date_str),
+        JournalTarget::Config => "the config".to_string(),
+        JournalTarget::Balance => "the balance".to_string(),
+    };
+}
+
+/// The targets whose current state isn't the one the entry expects, i.e. that were changed since.
+///
+/// A missing balance is the same as an empty one, as that's what `apply_state` writes for it.
+fn find_changed_targets(
+    changes: &[JournalChange],
+    expected_state: impl Fn(&JournalChange) -> &Option<String>,
+    read_state: impl Fn(&JournalTarget) -> Option<String>,
+) -> Vec<JournalTarget> {
+    let normalise = |target: &JournalTarget, state: Option<String>| -> Option<String> {
+        return match target {
+            JournalTarget::Balance => state.or(Some(BalanceLedger::default().as_string())),
+            _ => state,
+        };
+    };
+    return changes
+        .iter()
+        .filter(|x| {
+            normalise(&x.target, expected_state(x).clone())
+                != normalise(&x.target, read_state(&x.target))
+        })
+        .map(|x| x.target.clone())
+        .collect();
+}
+
+fn check_unchanged_since(
+    storage: &dyn Storage,
+    entry: &JournalEntry,
+    expected_state: impl Fn(&JournalChange) -> &Option<String>,
+    action: &str,
+) -> Result<(), String> {
+    let changed: Vec<JournalTarget> =
+        find_changed_targets(&entry.changes, expected_state, |target| {
+            read_state(storage, target)
+        });
+    if changed.len() == 0 {
+        return Ok(());
+    }
+    let changed_strs: Vec<String> = changed.iter().map(describe_target).collect();
+    return Err(format!(
+        "Can't {} '{}', as {} changed outside of punch since. Run 'punch {} --force' to overwrite it anyway.",
+        action,
+        entry.command,
+        changed_strs.join(" and "),
+        action
+    ));
+}
+
+/// Undoes the last entry, unless what it changed was changed again since (e.g. by hand) and `force`
+/// isn't set.
+pub fn undo_last_entry(force: bool) -> Result<Option<JournalEntry>, String> {
+    let mut journal: Journal = get_journal();
+    let entry: JournalEntry = match journal.undo() {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let storage: Box<dyn Storage> = get_storage();
+    if !force {
+        check_unchanged_since(storage.as_ref(), &entry, |x| &x.after, "undo")?;
+    }
+    for change in entry.changes.iter().rev() {
+        apply_state(storage.as_ref(), &change.target, &change.before);
+    }
+    write_journal(&journal);
+    return Ok(Some(entry));
+}
+
+pub fn redo_next_entry(force: bool) -> Result<Option<JournalEntry>, String> {
+    let mut journal: Journal = get_journal();
+    let entry: JournalEntry = match journal.redo() {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let storage: Box<dyn Storage> = get_storage();
+    if !force {
+        check_unchanged_since(storage.as_ref(), &entry, |x| &x.before, "redo")?;
+    }
+    for change in entry.changes.iter() {
+        apply_state(storage.as_ref(), &change.target, &change.after);
+    }
+    write_journal(&journal);
+    return Ok(Some(entry));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(target: JournalTarget, before: &str, after: &str) -> JournalChange {
+        return JournalChange {
+            target: target,
+            before: Some(before.to_string()),
+            after: Some(after.to_string()),
+        };
+    }
+
+    #[test]
+    fn test_changes_from_one_command_are_grouped() {
+        let mut journal: Journal = Journal::default();
+        let now: DateTime<Local> = Local::now();
+        journal.add_change(
+            "a",
+            "punch out",
+            &now,
+            change(JournalTarget::Config, "1", "2"),
+        );
+        journal.add_change(
+            "a",
+            "punch out",
+            &now,
+            change(JournalTarget::Config, "2", "3"),
+        );
+        journal.add_change(
+            "a",
+            "punch out",
+            &now,
+            change(JournalTarget::Day("2026-10-05".to_string()), "x", "y"),
+        );
+        assert_eq!(journal.entries.len(), 1);
+        let entry: &JournalEntry = &journal.entries[0];
+        assert_eq!(entry.changes.len(), 2);
+        assert_eq!(entry.changes[0].before, Some("1".to_string()));
+        assert_eq!(entry.changes[0].after, Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_new_change_clears_redo() {
+        let mut journal: Journal = Journal::default();
+        let now: DateTime<Local> = Local::now();
+        journal.add_change(
+            "a",
+            "punch in",
+            &now,
+            change(JournalTarget::Config, "1", "2"),
+        );
+        journal.add_change(
+            "b",
+            "punch task",
+            &now,
+            change(JournalTarget::Config, "2", "3"),
+        );
+        assert_eq!(journal.undo().unwrap().get_command(), "punch task");
+        journal.add_change(
+            "c",
+            "punch note",
+            &now,
+            change(JournalTarget::Config, "2", "4"),
+        );
+        assert!(journal.redo().is_none());
+        assert_eq!(journal.undo().unwrap().get_command(), "punch note");
+        assert_eq!(journal.undo().unwrap().get_command(), "punch in");
+        assert!(journal.undo().is_none());
+        assert_eq!(journal.redo().unwrap().get_command(), "punch in");
+    }
+
+    #[test]
+    fn test_finds_targets_changed_since() {
+        let changes: Vec<JournalChange> = vec![
+            change(JournalTarget::Day("2026-10-05".to_string()), "x", "y"),
+            change(JournalTarget::Config, "1", "2"),
+        ];
+        let read_state = |target: &JournalTarget| match target {
+            JournalTarget::Day(_) => Some("y".to_string()),
+            _ => Some("5".to_string()),
+        };
+        assert_eq!(
+            find_changed_targets(&changes, |x| &x.after, read_state),
+            vec![JournalTarget::Config]
+        );
+        assert_eq!(
+            find_changed_targets(&changes, |x| &x.before, read_state),
+            vec![
+                JournalTarget::Day("2026-10-05".to_string()),
+                JournalTarget::Config
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_balance_matches_empty_one() {
+        let changes: Vec<JournalChange> = vec![JournalChange {
+            target: JournalTarget::Balance,
+            before: None,
+            after: Some("adjustments: []".to_string()),
+        }];
+        let empty_ledger = |_: &JournalTarget| Some(BalanceLedger::default().as_string());
+        assert_eq!(
+            find_changed_targets(&changes, |x| &x.before, empty_ledger),
+            Vec::<JournalTarget>::new()
+        );
+    }
+}
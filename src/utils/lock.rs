@@ -0,0 +1,128 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::utils::fatal::exit_with_error;
+use crate::utils::file_io::expand_path;
+use crate::utils::profiles::get_data_dir;
+
+pub const LOCK_FILE: &str = "lock";
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+const LOCK_WAIT_NOTICE_AFTER: Duration = Duration::from_millis(200);
+const COMMAND_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The lock a CLI command holds while it reads and writes, see `hold_data_lock`.
+static HELD_LOCK: Mutex<Option<DataLock>> = Mutex::new(None);
+
+/// Holds the lock on a profile's data until it's dropped or the process exits.
+///
+/// The lock is taken by the OS on the lock file, so it's shared between CLI runs and `punch serve`.
+pub struct DataLock {
+    _file: File,
+}
+
+pub fn get_lock_path() -> String {
+    return expand_path(&(get_data_dir() + LOCK_FILE));
+}
+
+fn open_lock_file(path: &str) -> Result<File, String> {
+    return OpenOptions::new()
+        .create(true)
+        .write(true)
+        .open(path)
+        .map_err(|err| format!("Couldn't open the lock file '{}': {}", path, err));
+}
+
+/// Locks the file at `path`, giving up once `timeout` has passed while something else holds it.
+pub fn lock_file_at_within(path: &str, timeout: Duration) -> Result<DataLock, String> {
+    let file: File = open_lock_file(path)?;
+    let started: Instant = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(DataLock { _file: file }),
+            Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                sleep(LOCK_RETRY_INTERVAL)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err("Another punch command is using the data. Try again.".to_string())
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(format!("Couldn't lock '{}': {}", path, err))
+            }
+        }
+    }
+}
+
+pub fn lock_data_within(timeout: Duration) -> Result<DataLock, String> {
+    return lock_file_at_within(&get_lock_path(), timeout);
+}
+
+/// Takes the lock for the current command, saying so if another command has it for a while.
+pub fn hold_data_lock() -> Result<(), String> {
+    let lock: DataLock = match lock_data_within(LOCK_WAIT_NOTICE_AFTER) {
+        Ok(lock) => lock,
+        Err(_) => {
+            eprintln!("Waiting for another punch command to finish...");
+            lock_data_within(COMMAND_LOCK_TIMEOUT)?
+        }
+    };
+    *HELD_LOCK.lock().expect("Data lock poisoned!") = Some(lock);
+    return Ok(());
+}
+
+pub fn release_data_lock() {
+    HELD_LOCK.lock().expect("Data lock poisoned!").take();
+}
+
+/// Lets other commands use the data while `wait` runs, e.g. for a prompt, an editor or a timer,
+/// then takes the lock back. Anything read before may have changed, so it should be read again.
+pub fn without_data_lock<T>(wait: impl FnOnce() -> T) -> T {
+    let was_held: bool = HELD_LOCK
+        .lock()
+        .expect("Data lock poisoned!")
+        .take()
+        .is_some();
+    let result: T = wait();
+    if was_held {
+        if let Err(err_msg) = hold_data_lock() {
+            exit_with_error(&err_msg);
+        }
+    }
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::process;
+
+    fn lock_path(name: &str) -> String {
+        let path = temp_dir().join(format!("punch-{}-{}", name, process::id()));
+        return path.to_str().unwrap().to_string();
+    }
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let path: String = lock_path("lock-exclusive");
+        let lock: DataLock = lock_file_at_within(&path, Duration::ZERO).unwrap();
+        assert!(lock_file_at_within(&path, Duration::from_millis(50)).is_err());
+        drop(lock);
+        assert!(lock_file_at_within(&path, Duration::from_millis(50)).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_waits_for_lock_to_be_released() {
+        let path: String = lock_path("lock-wait");
+        let lock: DataLock = lock_file_at_within(&path, Duration::ZERO).unwrap();
+        let releaser = std::thread::spawn(move || {
+            sleep(Duration::from_millis(50));
+            drop(lock);
+        });
+        assert!(lock_file_at_within(&path, Duration::from_secs(5)).is_ok());
+        releaser.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,4 +1,15 @@
+pub mod activity;
+pub mod balance;
 pub mod config;
 pub mod dates_and_times;
+pub mod encryption;
+pub mod event_hooks;
+pub mod fatal;
 pub mod file_io;
+pub mod history;
+pub mod hooks;
+pub mod http;
+pub mod journal;
+pub mod lock;
+pub mod profiles;
 pub mod work_summary;
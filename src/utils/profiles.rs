@@ -0,0 +1,260 @@
+use chrono::prelude::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs::{read_dir, read_to_string, ReadDir};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::utils::file_io::{expand_path, BASE_DIR};
+
+pub const PROFILES_FILE: &str = "profiles.cfg";
+pub const PROFILES_DIR: &str = "profiles/";
+pub const DEFAULT_PROFILE: &str = "default";
+
+// Set from `--profile` or the top-level config when punch starts. Reports across profiles
+// switch it while reading each one.
+static ACTIVE_PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+thread_local! {
+    // Set while the server handles a request, so it uses the data dir it was started with.
+    static DATA_DIR_OVERRIDE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProfileSettings {
+    default_profile: Option<String>,
+}
+
+impl ProfileSettings {
+    pub fn get_default_profile(&self) -> String {
+        return self
+            .default_profile
+            .clone()
+            .unwrap_or(DEFAULT_PROFILE.to_string());
+    }
+
+    pub fn set_default_profile(&mut self, name: &str) {
+        self.default_profile = Some(name.to_string());
+    }
+}
+
+pub fn get_profile_settings_path() -> String {
+    return expand_path(&(BASE_DIR.to_owned() + PROFILES_FILE));
+}
+
+pub fn get_profile_settings() -> ProfileSettings {
+    return match read_to_string(get_profile_settings_path()) {
+        Ok(yaml_str) => serde_yaml::from_str(&yaml_str).expect("Profile settings are malformed!"),
+        Err(_) => ProfileSettings::default(),
+    };
+}
+
+pub fn write_profile_settings(settings: &ProfileSettings) {
+    // Kept in plain YAML since it's needed before knowing which profile's data to read.
+    std::fs::write(
+        get_profile_settings_path(),
+        serde_yaml::to_string(settings).unwrap(),
+    )
+    .expect("Couldn't write the profile settings!");
+}
+
+pub fn set_active_profile(name: &str) {
+    *ACTIVE_PROFILE.lock().expect("Profile lock poisoned!") = Some(name.to_string());
+}
+
+pub fn get_active_profile() -> String {
+    let active_profile: Option<String> = ACTIVE_PROFILE
+        .lock()
+        .expect("Profile lock poisoned!")
+        .clone();
+    return active_profile.unwrap_or_else(|| get_profile_settings().get_default_profile());
+}
+
+/// The data directory of a profile. The default profile lives directly in the base directory.
+pub fn get_profile_data_dir(name: &str) -> String {
+    if name == DEFAULT_PROFILE {
+        return BASE_DIR.to_string();
+    }
+    return BASE_DIR.to_owned() + PROFILES_DIR + name + "/";
+}
+
+pub fn get_data_dir() -> String {
+    if let Some(data_dir) = DATA_DIR_OVERRIDE.with(|x| x.borrow().clone()) {
+        return data_dir;
+    }
+    return get_profile_data_dir(&get_active_profile());
+}
+
+/// Runs `run` with the data in `data_dir` (ending in '/'), whatever the active profile is.
+pub fn with_data_dir<T>(data_dir: &str, run: impl FnOnce() -> T) -> T {
+    let previous: Option<String> =
+        DATA_DIR_OVERRIDE.with(|x| x.replace(Some(data_dir.to_string())));
+    let result: T = run();
+    DATA_DIR_OVERRIDE.with(|x| x.replace(previous));
+    return result;
+}
+
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles: Vec<String> = Vec::new();
+    let entries_result: Result<ReadDir, std::io::Error> =
+        read_dir(expand_path(&(BASE_DIR.to_owned() + PROFILES_DIR)));
+    if let Ok(entries) = entries_result {
+        profiles = entries
+            .filter_map(|x| x.ok())
+            .filter(|x| x.path().is_dir())
+            .map(|x| x.file_name().to_string_lossy().to_string())
+            .collect();
+    }
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    return profiles;
+}
+
+pub fn profile_exists(name: &str) -> bool {
+    if validate_profile_name(name).is_err() {
+        return false;
+    }
+    return (name == DEFAULT_PROFILE)
+        || Path::new(&expand_path(&get_profile_data_dir(name))).exists();
+}
+
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    let name_regex: Regex = Regex::new(r"^[A-Za-z0-9_\-]+$").unwrap();
+    if !name_regex.is_match(name) {
+        return Err(format!(
+            "'{}' isn't a valid profile name. Use letters, numbers, '-' and '_'.",
+            name
+        ));
+    }
+    return Ok(());
+}
+
+pub type WorkIntervals = Vec<(DateTime<Local>, DateTime<Local>)>;
+
+pub struct ProfileOverlap {
+    pub first_profile: String,
+    pub second_profile: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// Finds times worked in more than one profile, i.e. double-booked hours.
+pub fn find_cross_profile_overlaps(
+    work_per_profile: &Vec<(String, WorkIntervals)>,
+) -> Vec<ProfileOverlap> {
+    let mut overlaps: Vec<ProfileOverlap> = Vec::new();
+    for (i, (first_profile, first_intervals)) in work_per_profile.iter().enumerate() {
+        for (second_profile, second_intervals) in work_per_profile.iter().skip(i + 1) {
+            for (first_start, first_end) in first_intervals.iter() {
+                for (second_start, second_end) in second_intervals.iter() {
+                    let start: DateTime<Local> = *first_start.max(second_start);
+                    let end: DateTime<Local> = *first_end.min(second_end);
+                    if start < end {
+                        overlaps.push(ProfileOverlap {
+                            first_profile: first_profile.clone(),
+                            second_profile: second_profile.clone(),
+                            start: start,
+                            end: end,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    overlaps.sort_by_key(|x| x.start);
+    return overlaps;
+}
+
+/// Takes `--profile <name>` or `--profile=<name>` from before the subcommand.
+pub fn take_profile_arg(args: Vec<String>) -> Result<(Option<String>, Vec<String>), String> {
+    let mut remaining: Vec<String> = args;
+    let profile: Option<String> = match remaining.get(1).map(|x| x.as_str()) {
+        Some("--profile") => {
+            if remaining.len() < 3 {
+                return Err("'--profile' must be followed by a profile name.".to_string());
+            }
+            let name: String = remaining.remove(2);
+            remaining.remove(1);
+            Some(name)
+        }
+        Some(arg) if arg.starts_with("--profile=") => {
+            let name: String = arg.trim_start_matches("--profile=").to_string();
+            remaining.remove(1);
+            Some(name)
+        }
+        _ => None,
+    };
+    return Ok((profile, remaining));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use test_case::test_case;
+
+    #[test_case("clientA", true ; "letters")]
+    #[test_case("client_b-2", true ; "with underscores, dashes and numbers")]
+    #[test_case("", false ; "empty")]
+    #[test_case("../days", false ; "path traversal")]
+    #[test_case("client a", false ; "with a space")]
+    fn test_validate_profile_name(name: &str, is_valid: bool) {
+        assert_eq!(validate_profile_name(name).is_ok(), is_valid);
+    }
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        return args.iter().map(|x| x.to_string()).collect();
+    }
+
+    #[test_case(&["punch", "in"], None, &["punch", "in"] ; "no profile")]
+    #[test_case(&["punch", "--profile", "clientA", "in"], Some("clientA"), &["punch", "in"] ; "separate value")]
+    #[test_case(&["punch", "--profile=clientA", "task", "x"], Some("clientA"), &["punch", "task", "x"] ; "joined value")]
+    #[test_case(&["punch", "task", "--profile"], None, &["punch", "task", "--profile"] ; "only before the subcommand")]
+    fn test_take_profile_arg(
+        args: &[&str],
+        expected_profile: Option<&str>,
+        expected_args: &[&str],
+    ) {
+        let (profile, remaining) = take_profile_arg(to_args(args)).unwrap();
+        assert_eq!(profile, expected_profile.map(|x| x.to_string()));
+        assert_eq!(remaining, to_args(expected_args));
+    }
+
+    #[test]
+    fn test_take_profile_arg_without_name() {
+        assert!(take_profile_arg(to_args(&["punch", "--profile"])).is_err());
+    }
+
+    #[test]
+    fn test_find_cross_profile_overlaps() {
+        let at = |hour: u32, minute: u32| -> DateTime<Local> {
+            Local
+                .with_ymd_and_hms(2026, 10, 5, hour, minute, 0)
+                .unwrap()
+        };
+        let work_per_profile = vec![
+            (
+                "clientA".to_string(),
+                vec![(at(9, 0), at(11, 0)), (at(13, 0), at(14, 0))],
+            ),
+            ("clientB".to_string(), vec![(at(10, 30), at(12, 0))]),
+            ("clientC".to_string(), vec![(at(14, 0), at(15, 0))]),
+        ];
+        let overlaps: Vec<ProfileOverlap> = find_cross_profile_overlaps(&work_per_profile);
+        assert_eq!(overlaps.len(), 1);
+        assert_eq!(overlaps[0].first_profile, "clientA");
+        assert_eq!(overlaps[0].second_profile, "clientB");
+        assert_eq!(overlaps[0].start, at(10, 30));
+        assert_eq!(overlaps[0].end, at(11, 0));
+    }
+
+    #[test]
+    fn test_profile_data_dirs() {
+        assert_eq!(get_profile_data_dir(DEFAULT_PROFILE), BASE_DIR);
+        assert_eq!(
+            get_profile_data_dir("clientA"),
+            "~/.punch-card/profiles/clientA/"
+        );
+    }
+}
@@ -17,4 +17,20 @@ impl WorkSummary {
             summary: summary,
         };
     }
+
+    pub fn get_category(&self) -> String {
+        return self.category.clone();
+    }
+
+    pub fn get_project(&self) -> String {
+        return self.project.clone();
+    }
+
+    pub fn get_task(&self) -> String {
+        return self.task.clone();
+    }
+
+    pub fn get_summary(&self) -> String {
+        return self.summary.clone();
+    }
 }